@@ -0,0 +1,37 @@
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use pica_record::ByteRecord;
+use pica_schema::{Schema, Validator};
+use serde::Deserialize;
+
+use super::{Lint, Status};
+
+#[derive(Debug, Deserialize)]
+pub struct Avram {
+    /// The path of the schema, which is resolved relative to the
+    /// directory of the rule set.
+    schema: PathBuf,
+    #[serde(skip)]
+    validator: Option<Validator>,
+}
+
+impl Lint for Avram {
+    fn prepare(&mut self, base_dir: &Path) -> io::Result<()> {
+        let validator = Schema::from_path(base_dir.join(&self.schema))
+            .and_then(Validator::new)
+            .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?;
+
+        self.validator = Some(validator);
+        Ok(())
+    }
+
+    fn check(&mut self, record: &ByteRecord) -> Status {
+        let validator = self
+            .validator
+            .as_ref()
+            .expect("schema is loaded by `prepare`");
+
+        Status::from(!validator.validate(record).is_empty())
+    }
+}
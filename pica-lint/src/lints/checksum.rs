@@ -1,15 +1,26 @@
+use std::io;
+
 use pica_path::{Path, PathExt};
 use pica_record::ByteRecord;
 use serde::Deserialize;
 
 use super::{Lint, Status};
+use crate::util::{deserialize_path, read_path_lists};
 
 #[derive(Debug, Deserialize)]
 pub struct Checksum {
+    #[serde(deserialize_with = "deserialize_path")]
     path: Path,
 }
 
 impl Lint for Checksum {
+    fn prepare(
+        &mut self,
+        base_dir: &std::path::Path,
+    ) -> io::Result<()> {
+        read_path_lists(&mut self.path, base_dir)
+    }
+
     fn check<'a>(&mut self, record: &ByteRecord) -> Status {
         for value in record.path(&self.path, &Default::default()).iter()
         {
@@ -1,4 +1,5 @@
 use std::fmt::Debug;
+use std::io;
 
 use chrono::NaiveDate;
 use pica_path::{Path, PathExt};
@@ -6,9 +7,11 @@ use pica_record::ByteRecord;
 use serde::Deserialize;
 
 use super::{Lint, Status};
+use crate::util::{deserialize_path, read_path_lists};
 
 #[derive(Deserialize, Debug)]
 pub struct Date {
+    #[serde(deserialize_with = "deserialize_path")]
     path: Path,
     #[serde(default = "default_fmt")]
     format: String,
@@ -21,6 +24,13 @@ fn default_fmt() -> String {
 }
 
 impl Lint for Date {
+    fn prepare(
+        &mut self,
+        base_dir: &std::path::Path,
+    ) -> io::Result<()> {
+        read_path_lists(&mut self.path, base_dir)
+    }
+
     fn check(&mut self, record: &ByteRecord) -> Status {
         let result = record
             .path(&self.path, &Default::default())
@@ -1,23 +1,43 @@
-use pica_matcher::{MatcherOptions, RecordMatcher};
+use std::io;
+
+use pica_matcher::{MatcherOptions, NormalizationForm, RecordMatcher};
 use pica_record::ByteRecord;
 use serde::Deserialize;
 
 use super::{Lint, Status};
+use crate::util::{deserialize_matcher, read_matcher_lists};
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "kebab-case")]
 pub struct Filter {
+    #[serde(deserialize_with = "deserialize_matcher")]
     filter: RecordMatcher,
     #[serde(default)]
     case_ignore: bool,
     #[serde(default)]
+    case_fold: bool,
+    #[serde(default)]
+    diacritic_ignore: bool,
+    #[serde(default)]
+    normalization: Option<NormalizationForm>,
+    #[serde(default)]
     invert: bool,
 }
 
 impl Lint for Filter {
+    fn prepare(
+        &mut self,
+        base_dir: &std::path::Path,
+    ) -> io::Result<()> {
+        read_matcher_lists(&mut self.filter, base_dir)
+    }
+
     fn check(&mut self, record: &ByteRecord) -> Status {
-        let options =
-            MatcherOptions::new().case_ignore(self.case_ignore);
+        let options = MatcherOptions::new()
+            .case_ignore(self.case_ignore)
+            .case_fold(self.case_fold)
+            .diacritic_ignore(self.diacritic_ignore)
+            .normalization(self.normalization);
 
         let mut result = self.filter.is_match(record, &options);
         if self.invert {
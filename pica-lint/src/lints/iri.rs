@@ -1,17 +1,27 @@
 use std::fmt::Debug;
+use std::io;
 
 use pica_path::{Path, PathExt};
 use pica_record::ByteRecord;
 use serde::Deserialize;
 
 use super::{Lint, Status};
+use crate::util::{deserialize_path, read_path_lists};
 
 #[derive(Deserialize, Debug)]
 pub struct Iri {
+    #[serde(deserialize_with = "deserialize_path")]
     path: Path,
 }
 
 impl Lint for Iri {
+    fn prepare(
+        &mut self,
+        base_dir: &std::path::Path,
+    ) -> io::Result<()> {
+        read_path_lists(&mut self.path, base_dir)
+    }
+
     fn check(&mut self, record: &ByteRecord) -> Status {
         record
             .path(&self.path, &Default::default())
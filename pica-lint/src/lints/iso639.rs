@@ -1,4 +1,5 @@
 use std::collections::BTreeSet;
+use std::io;
 use std::sync::Mutex;
 
 use once_cell::sync::Lazy;
@@ -7,9 +8,11 @@ use pica_record::ByteRecord;
 use serde::Deserialize;
 
 use super::{Lint, Status};
+use crate::util::{deserialize_path, read_path_lists};
 
 #[derive(Debug, Deserialize)]
 pub struct Iso639 {
+    #[serde(deserialize_with = "deserialize_path")]
     path: Path,
 }
 
@@ -84,6 +87,13 @@ static ISO639_CODES: Lazy<Mutex<BTreeSet<Vec<u8>>>> = Lazy::new(|| {
 });
 
 impl Lint for Iso639 {
+    fn prepare(
+        &mut self,
+        base_dir: &std::path::Path,
+    ) -> io::Result<()> {
+        read_path_lists(&mut self.path, base_dir)
+    }
+
     fn check(&mut self, record: &ByteRecord) -> Status {
         let codes = ISO639_CODES.lock().unwrap();
         record
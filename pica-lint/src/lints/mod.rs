@@ -1,7 +1,11 @@
+use std::io;
+use std::path::Path;
+
 use bstr::BString;
 use pica_record::ByteRecord;
 use serde::Deserialize;
 
+use self::avram::Avram;
 use self::checksum::Checksum;
 use self::date::Date;
 use self::filter::Filter;
@@ -11,6 +15,7 @@ use self::orcid::Orcid;
 use self::refcheck::RefCheck;
 use self::unicode::Unicode;
 
+mod avram;
 mod checksum;
 mod date;
 mod filter;
@@ -23,6 +28,7 @@ mod unicode;
 #[derive(Debug, Deserialize)]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum Lints {
+    Avram(Avram),
     Checksum(Checksum),
     Date(Date),
     Filter(Filter),
@@ -52,6 +58,14 @@ impl From<bool> for Status {
 }
 
 pub trait Lint {
+    /// Prepares the lint after the rule set has been read, e.g. reads
+    /// the lists of list file references (`0 in @codes.txt`), whereby
+    /// relative paths are resolved against `base_dir`, the directory
+    /// of the rule set.
+    fn prepare(&mut self, _base_dir: &Path) -> io::Result<()> {
+        Ok(())
+    }
+
     fn preprocess(&mut self, _record: &ByteRecord) {}
     fn check(&mut self, record: &ByteRecord) -> Status;
     fn finish(&mut self) -> Vec<(BString, Status)> {
@@ -60,8 +74,23 @@ pub trait Lint {
 }
 
 impl Lint for Lints {
+    fn prepare(&mut self, base_dir: &Path) -> io::Result<()> {
+        match self {
+            Self::Avram(ref mut l) => l.prepare(base_dir),
+            Self::Checksum(ref mut l) => l.prepare(base_dir),
+            Self::Date(ref mut l) => l.prepare(base_dir),
+            Self::Filter(ref mut l) => l.prepare(base_dir),
+            Self::Iri(ref mut l) => l.prepare(base_dir),
+            Self::Iso639(ref mut l) => l.prepare(base_dir),
+            Self::Orcid(ref mut l) => l.prepare(base_dir),
+            Self::RefCheck(ref mut l) => l.prepare(base_dir),
+            Self::Unicode(ref mut l) => l.prepare(base_dir),
+        }
+    }
+
     fn check(&mut self, record: &ByteRecord) -> Status {
         match self {
+            Self::Avram(ref mut l) => l.check(record),
             Self::Checksum(ref mut l) => l.check(record),
             Self::Date(ref mut l) => l.check(record),
             Self::Filter(ref mut l) => l.check(record),
@@ -1,4 +1,5 @@
 use std::collections::{BTreeMap, BTreeSet};
+use std::io;
 
 use bstr::BString;
 use pica_matcher::RecordMatcher;
@@ -7,12 +8,19 @@ use pica_record::ByteRecord;
 use serde::Deserialize;
 
 use super::{Lint, Status};
+use crate::util::{
+    deserialize_matcher_opt, deserialize_path, read_matcher_lists,
+    read_path_lists,
+};
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct RefCheck {
+    #[serde(deserialize_with = "deserialize_path")]
     src: Path,
+    #[serde(deserialize_with = "deserialize_path")]
     dst: Path,
+    #[serde(default, deserialize_with = "deserialize_matcher_opt")]
     filter: Option<RecordMatcher>,
     #[serde(skip, default)]
     seen: BTreeSet<BString>,
@@ -21,6 +29,21 @@ pub struct RefCheck {
 }
 
 impl Lint for RefCheck {
+    fn prepare(
+        &mut self,
+        base_dir: &std::path::Path,
+    ) -> io::Result<()> {
+        read_path_lists(&mut self.src, base_dir)?;
+        read_path_lists(&mut self.dst, base_dir)?;
+
+        match self.filter {
+            Some(ref mut filter) => {
+                read_matcher_lists(filter, base_dir)
+            }
+            None => Ok(()),
+        }
+    }
+
     fn preprocess(&mut self, record: &ByteRecord) {
         let values = record.path(&self.dst, &Default::default());
         if !values.is_empty() {
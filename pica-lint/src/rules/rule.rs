@@ -1,3 +1,6 @@
+use std::io;
+use std::path::Path;
+
 use bstr::BString;
 use pica_record::ByteRecord;
 use serde::Deserialize;
@@ -23,6 +26,11 @@ impl Rule {
         self.id = id.into();
     }
 
+    /// Prepares the lint of the rule (see [Lint::prepare]).
+    pub fn prepare(&mut self, base_dir: &Path) -> io::Result<()> {
+        self.lint.prepare(base_dir)
+    }
+
     pub fn preprocess(&mut self, record: &ByteRecord) {
         self.lint.preprocess(record)
     }
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 use std::fs::read_to_string;
-use std::io::Result;
+use std::io::{Error, ErrorKind, Result};
 use std::path::Path;
 
 use bstr::ByteSlice;
@@ -14,6 +14,7 @@ use crate::formatter::Formatter;
 use crate::lints::Status;
 use crate::rules::Level;
 use crate::stats::Stats;
+use crate::util::{deserialize_matcher_opt, read_matcher_lists};
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "kebab-case")]
@@ -22,6 +23,7 @@ pub struct RuleSet {
     pub name: String,
     #[serde(default)]
     pub description: String,
+    #[serde(default, deserialize_with = "deserialize_matcher_opt")]
     pub scope: Option<RecordMatcher>,
     #[serde(rename = "rule")]
     pub rules: HashMap<String, Rule>,
@@ -29,12 +31,32 @@ pub struct RuleSet {
 
 impl RuleSet {
     /// Create a new rule set from a spec file.
+    ///
+    /// File references in filter expressions (e.g. `0 in @codes.txt`)
+    /// and schema files of `avram` lints are resolved relative to the
+    /// directory of the spec file.
     pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+        let content = read_to_string(path)?;
+        let invalid = |e: &dyn std::fmt::Display| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("invalid rule set `{}`: {e}", path.display()),
+            )
+        };
+
         let mut rs: RuleSet =
-            toml::from_str(&read_to_string(path)?).unwrap();
+            toml::from_str(&content).map_err(|e| invalid(&e))?;
+
+        if let Some(ref mut scope) = rs.scope {
+            read_matcher_lists(scope, base_dir)
+                .map_err(|e| invalid(&e))?;
+        }
 
         for (id, rule) in rs.rules.iter_mut() {
             rule.set_id(id);
+            rule.prepare(base_dir).map_err(|e| invalid(&e))?;
         }
 
         Ok(rs)
@@ -1,9 +1,11 @@
 use std::ffi::OsStr;
 use std::fs::File;
-use std::io::{self, BufReader, Read};
+use std::io::{self, BufReader, ErrorKind, Read};
 use std::path::Path;
 
 use flate2::read::GzDecoder;
+use pica_matcher::{ParseContext, RecordMatcher};
+use serde::{de, Deserialize, Deserializer};
 
 pub(crate) fn reader<P: AsRef<Path>>(
     path: P,
@@ -24,3 +26,59 @@ pub(crate) fn reader<P: AsRef<Path>>(
 
     Ok(BufReader::new(reader))
 }
+
+/// Deserializes a path expression without reading its lists, which
+/// are read relative to the rule set by [read_path_lists].
+pub(crate) fn deserialize_path<'de, D>(
+    deserializer: D,
+) -> Result<pica_path::Path, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    pica_path::Path::parse(&s).map_err(de::Error::custom)
+}
+
+/// Deserializes a filter expression without reading its lists, which
+/// are read relative to the rule set by [read_matcher_lists].
+pub(crate) fn deserialize_matcher<'de, D>(
+    deserializer: D,
+) -> Result<RecordMatcher, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    RecordMatcher::parse(&s).map_err(de::Error::custom)
+}
+
+/// Deserializes an optional filter expression (see
+/// [deserialize_matcher]).
+pub(crate) fn deserialize_matcher_opt<'de, D>(
+    deserializer: D,
+) -> Result<Option<RecordMatcher>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_matcher(deserializer).map(Some)
+}
+
+/// Reads the lists of a path expression, whereby relative paths are
+/// resolved against the directory of the rule set.
+pub(crate) fn read_path_lists(
+    path: &mut pica_path::Path,
+    base_dir: &Path,
+) -> io::Result<()> {
+    path.read_lists(&ParseContext::new().base_dir(base_dir))
+        .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+}
+
+/// Reads the lists of a filter expression, whereby relative paths are
+/// resolved against the directory of the rule set.
+pub(crate) fn read_matcher_lists(
+    matcher: &mut RecordMatcher,
+    base_dir: &Path,
+) -> io::Result<()> {
+    matcher
+        .read_lists(&ParseContext::new().base_dir(base_dir))
+        .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+}
@@ -0,0 +1,24 @@
+use std::fs::{read_to_string, remove_file};
+use std::process::Command;
+
+#[test]
+fn list_files_relative_to_rule_set() -> anyhow::Result<()> {
+    let output = std::env::temp_dir()
+        .join(format!("pica-lint-lists-{}.csv", std::process::id()));
+
+    let status = Command::new(env!("CARGO_BIN_EXE_pica-lint"))
+        .arg("--rule-set")
+        .arg("tests/data/lists/rules.toml")
+        .arg("--output")
+        .arg(&output)
+        .arg("tests/data/avram/records.dat")
+        .status()?;
+
+    assert!(status.success());
+
+    let result = read_to_string(&output)?;
+    remove_file(&output)?;
+
+    assert_eq!(result, "idn,rule,level\n118540238,LISTS-001,error\n");
+    Ok(())
+}
@@ -1,23 +1,185 @@
 use std::fmt::{self, Display};
 
+use bstr::BStr;
 use nom::branch::alt;
 use nom::bytes::complete::{is_not, tag};
-use nom::character::complete::{char, multispace0, multispace1};
-use nom::combinator::{map, map_res, value, verify};
+use nom::character::complete::{
+    alphanumeric1, char, digit1, multispace0, multispace1,
+};
+use nom::combinator::{map, map_res, not, value, verify};
+use nom::error::context;
 use nom::multi::fold_many0;
-use nom::sequence::{delimited, preceded};
+use nom::sequence::{delimited, preceded, terminated};
 use nom::IResult;
-use pica_record::parser::ParseResult;
+use pica_record::parser as record_parser;
+#[cfg(feature = "serde")]
+use serde::{Serialize, Serializer};
+
+pub use crate::error::ParserError;
+
+/// The result type of the parsers of matcher expressions.
+pub type ParseResult<'a, O> = IResult<&'a [u8], O, ParserError<'a>>;
+
+/// Turns a parser of `pica-record`, which doesn't provide any error
+/// information, into a parser that reports what was `expected`.
+fn lift<'a, O>(
+    parser: impl Fn(&'a [u8]) -> record_parser::ParseResult<'a, O>,
+    expected: &'static str,
+) -> impl Fn(&'a [u8]) -> ParseResult<'a, O> {
+    move |i| {
+        parser(i)
+            .map_err(|e| e.map(|_| ParserError::expected(i, expected)))
+    }
+}
+
+/// Parse a subfield code.
+pub fn parse_subfield_code(i: &[u8]) -> ParseResult<char> {
+    lift(record_parser::parse_subfield_code, "subfield code")(i)
+}
+
+/// Parse a tag.
+pub(crate) fn parse_tag(i: &[u8]) -> ParseResult<&BStr> {
+    lift(record_parser::parse_tag, "tag")(i)
+}
+
+/// Parse the digits of an occurrence.
+pub(crate) fn parse_occurrence_digits(i: &[u8]) -> ParseResult<&BStr> {
+    lift(record_parser::parse_occurrence_digits, "occurrence")(i)
+}
 
 /// Boolean Operators.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum BooleanOp {
-    And, // and, "&&"
-    Or,  // or, "||"
+    And,     // and, "&&"
+    Or,      // or, "||"
+    Xor,     // xor, "^"
+    Implies, // implies, "=>"
+}
+
+impl BooleanOp {
+    /// Returns the precedence of the operator. `&&` binds tighter than
+    /// `^`, which binds tighter than `||`; `=>` binds weakest.
+    pub(crate) fn precedence(&self) -> u8 {
+        match *self {
+            BooleanOp::Implies => 0,
+            BooleanOp::Or => 1,
+            BooleanOp::Xor => 2,
+            BooleanOp::And => 3,
+        }
+    }
+
+    /// Applies the operator. The right operand is evaluated only if
+    /// the result depends on it.
+    pub(crate) fn eval(
+        &self,
+        lhs: bool,
+        rhs: impl FnOnce() -> bool,
+    ) -> bool {
+        match *self {
+            BooleanOp::And => lhs && rhs(),
+            BooleanOp::Or => lhs || rhs(),
+            BooleanOp::Xor => lhs != rhs(),
+            BooleanOp::Implies => !lhs || rhs(),
+        }
+    }
+
+    /// Returns the name of the connective in the serialized AST.
+    #[cfg(feature = "serde")]
+    pub(crate) fn name(&self) -> &'static str {
+        match *self {
+            BooleanOp::And => "and",
+            BooleanOp::Or => "or",
+            BooleanOp::Xor => "xor",
+            BooleanOp::Implies => "implies",
+        }
+    }
+}
+
+impl Display for BooleanOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            BooleanOp::And => write!(f, "&&"),
+            BooleanOp::Or => write!(f, "||"),
+            BooleanOp::Xor => write!(f, "^"),
+            BooleanOp::Implies => write!(f, "=>"),
+        }
+    }
+}
+
+/// Parse a keyword, which must not be followed by an alphanumeric
+/// character (e.g. `xor` in `a? xor b?`, but not in `a? xor003@?`).
+fn keyword<'a>(
+    word: &'static str,
+) -> impl FnMut(&'a [u8]) -> ParseResult<'a, &'a [u8]> {
+    terminated(tag(word), not(alphanumeric1))
+}
+
+/// Parse the operator of an exclusive disjunction (`^` or `xor`).
+pub(crate) fn parse_xor_op(i: &[u8]) -> ParseResult<&[u8]> {
+    alt((tag("^"), keyword("xor")))(i)
+}
+
+/// Parse the operator of an implication (`=>` or `implies`).
+pub(crate) fn parse_implies_op(i: &[u8]) -> ParseResult<&[u8]> {
+    alt((tag("=>"), keyword("implies")))(i)
+}
+
+/// Quantifier
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) enum Quantifier {
+    All, // all, "ALL"
+    #[default]
+    Any, // any, "ANY"
+}
+
+impl Quantifier {
+    /// Returns `true` if the predicate holds for at least one item
+    /// (`ANY`) or for all items (`ALL`). If there are no items, the
+    /// result is `false` for both quantifiers.
+    pub(crate) fn eval<I: IntoIterator>(
+        &self,
+        items: I,
+        predicate: impl FnMut(I::Item) -> bool,
+    ) -> bool {
+        let mut items = items.into_iter().peekable();
+        match self {
+            Quantifier::Any => items.any(predicate),
+            Quantifier::All => {
+                items.peek().is_some() && items.all(predicate)
+            }
+        }
+    }
+}
+
+impl Display for Quantifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Quantifier::All => write!(f, "ALL"),
+            Quantifier::Any => write!(f, "ANY"),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Quantifier {
+    fn serialize<S: Serializer>(
+        &self,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+/// Parse a quantifier.
+pub(crate) fn parse_quantifier(i: &[u8]) -> ParseResult<Quantifier> {
+    alt((
+        value(Quantifier::All, tag("ALL")),
+        value(Quantifier::Any, tag("ANY")),
+    ))(i)
 }
 
 /// Strip whitespaces from the beginning and end.
-pub(crate) fn ws<'a, F: 'a, O, E: nom::error::ParseError<&'a [u8]>>(
+pub(crate) fn ws<'a, F, O, E: nom::error::ParseError<&'a [u8]>>(
     inner: F,
 ) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], O, E>
 where
@@ -26,18 +188,27 @@ where
     delimited(multispace0, inner, multispace0)
 }
 
+/// Parse a non-negative integer.
+pub(crate) fn parse_usize(i: &[u8]) -> ParseResult<usize> {
+    map_res(digit1, |s| {
+        std::str::from_utf8(s).unwrap().parse::<usize>()
+    })(i)
+}
+
 /// Relational Operator
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) enum RelationalOp {
-    Eq,         // equal, "=="
-    Ne,         // not equal, "!="
-    Gt,         // greater than, ">"
-    Ge,         // greater than or equal, ">="
-    Lt,         // less than, "<"
-    Le,         // less than or equal, "<="
-    StartsWith, // starts with, "=^"
-    EndsWith,   // ends with, "=$"
-    Similar,    // similar, "=*"
+    Eq,           // equal, "=="
+    Ne,           // not equal, "!="
+    Gt,           // greater than, ">"
+    Ge,           // greater than or equal, ">="
+    Lt,           // less than, "<"
+    Le,           // less than or equal, "<="
+    StartsWith,   // starts with, "=^"
+    EndsWith,     // ends with, "=$"
+    Similar,      // similar, "=*"
+    Contains,     // contains, "=?"
+    ContainsWord, // contains word(s), "=%"
 }
 
 impl Display for RelationalOp {
@@ -52,35 +223,62 @@ impl Display for RelationalOp {
             RelationalOp::StartsWith => write!(f, "=^"),
             RelationalOp::EndsWith => write!(f, "=$"),
             RelationalOp::Similar => write!(f, "=*"),
+            RelationalOp::Contains => write!(f, "=?"),
+            RelationalOp::ContainsWord => write!(f, "=%"),
         }
     }
 }
 
+#[cfg(feature = "serde")]
+impl Serialize for RelationalOp {
+    fn serialize<S: Serializer>(
+        &self,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
 /// Parse RelationalOp which can be used for string comparisons.
 pub(crate) fn parse_relational_op_str(
     i: &[u8],
 ) -> ParseResult<RelationalOp> {
-    alt((
-        value(RelationalOp::Eq, tag("==")),
-        value(RelationalOp::Ne, tag("!=")),
-        value(RelationalOp::StartsWith, tag("=^")),
-        value(RelationalOp::EndsWith, tag("=$")),
-        value(RelationalOp::Similar, tag("=*")),
-    ))(i)
+    context(
+        "comparison operator",
+        alt((
+            value(RelationalOp::Eq, tag("==")),
+            value(RelationalOp::Ne, tag("!=")),
+            value(RelationalOp::StartsWith, tag("=^")),
+            value(RelationalOp::EndsWith, tag("=$")),
+            value(RelationalOp::Similar, tag("=*")),
+            value(RelationalOp::Contains, tag("=?")),
+            value(RelationalOp::ContainsWord, tag("=%")),
+        )),
+    )(i)
+}
+
+/// Parse any RelationalOp.
+pub(crate) fn parse_relational_op(
+    i: &[u8],
+) -> ParseResult<RelationalOp> {
+    alt((parse_relational_op_usize, parse_relational_op_str))(i)
 }
 
 /// Parse RelationalOp which can be used for usize comparisons.
 pub(crate) fn parse_relational_op_usize(
     i: &[u8],
 ) -> ParseResult<RelationalOp> {
-    alt((
-        value(RelationalOp::Eq, tag("==")),
-        value(RelationalOp::Ne, tag("!=")),
-        value(RelationalOp::Ge, tag(">=")),
-        value(RelationalOp::Gt, tag(">")),
-        value(RelationalOp::Le, tag("<=")),
-        value(RelationalOp::Lt, tag("<")),
-    ))(i)
+    context(
+        "comparison operator",
+        alt((
+            value(RelationalOp::Eq, tag("==")),
+            value(RelationalOp::Ne, tag("!=")),
+            value(RelationalOp::Ge, tag(">=")),
+            value(RelationalOp::Gt, tag(">")),
+            value(RelationalOp::Le, tag("<=")),
+            value(RelationalOp::Lt, tag("<")),
+        )),
+    )(i)
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -192,8 +390,41 @@ fn parse_string_double_quoted(i: &[u8]) -> ParseResult<String> {
     )
 }
 
+/// A string literal, which is formatted such that it can be parsed by
+/// [parse_string]. Strings containing a single quote are enclosed in
+/// double quotes, otherwise single quotes are used.
+pub(crate) struct Quoted<'a>(pub(crate) &'a str);
+
+impl Display for Quoted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let quote = if self.0.contains('\'') && !self.0.contains('"') {
+            '"'
+        } else {
+            '\''
+        };
+
+        write!(f, "{quote}")?;
+        for c in self.0.chars() {
+            match c {
+                '\\' => write!(f, "\\\\")?,
+                '\n' => write!(f, "\\n")?,
+                '\r' => write!(f, "\\r")?,
+                '\t' => write!(f, "\\t")?,
+                '\u{08}' => write!(f, "\\b")?,
+                '\u{0C}' => write!(f, "\\f")?,
+                c => write!(f, "{c}")?,
+            }
+        }
+
+        write!(f, "{quote}")
+    }
+}
+
 pub(crate) fn parse_string(i: &[u8]) -> ParseResult<String> {
-    alt((parse_string_single_quoted, parse_string_double_quoted))(i)
+    context(
+        "string literal",
+        alt((parse_string_single_quoted, parse_string_double_quoted)),
+    )(i)
 }
 
 #[cfg(test)]
@@ -267,4 +498,36 @@ mod tests {
             "\tc".to_string()
         );
     }
+
+    #[test]
+    fn test_boolean_op_eval() {
+        for (lhs, rhs) in
+            [(false, false), (false, true), (true, false), (true, true)]
+        {
+            assert_eq!(BooleanOp::And.eval(lhs, || rhs), lhs && rhs);
+            assert_eq!(BooleanOp::Or.eval(lhs, || rhs), lhs || rhs);
+            assert_eq!(BooleanOp::Xor.eval(lhs, || rhs), lhs ^ rhs);
+            assert_eq!(
+                BooleanOp::Implies.eval(lhs, || rhs),
+                !lhs || rhs
+            );
+        }
+
+        assert!(!BooleanOp::And.eval(false, || unreachable!()));
+        assert!(BooleanOp::Implies.eval(false, || unreachable!()));
+    }
+
+    #[test]
+    fn test_parse_boolean_ops() {
+        assert_done_and_eq!(parse_xor_op(b"^"), b"^".as_slice());
+        assert_done_and_eq!(parse_xor_op(b"xor"), b"xor".as_slice());
+        assert_done_and_eq!(parse_implies_op(b"=>"), b"=>".as_slice());
+        assert_done_and_eq!(
+            parse_implies_op(b"implies"),
+            b"implies".as_slice()
+        );
+        assert_error!(parse_implies_op(b"=="));
+        assert_error!(parse_xor_op(b"xor003@?"));
+        assert_error!(parse_implies_op(b"impliesa?"));
+    }
 }
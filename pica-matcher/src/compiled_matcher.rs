@@ -0,0 +1,576 @@
+//! Compiled record matchers, which are optimized for the evaluation
+//! against many records.
+
+use std::collections::HashMap;
+use std::slice::Iter;
+
+use pica_record::{Field, Record, Subfield};
+use regex::bytes::{RegexSet, RegexSetBuilder};
+
+use crate::common::{BooleanOp, Quantifier, RelationalOp};
+use crate::field_matcher::{
+    Cardinality, CardinalityMatcher, SingletonMatcher,
+};
+use crate::subfield_matcher::{self, Matcher};
+use crate::{
+    FieldMatcher, MatcherOptions, OccurrenceMatcher, RecordMatcher,
+    SubfieldMatcher, TagMatcher,
+};
+
+/// The minimal number of field matchers, which are evaluated against a
+/// record, before the fields of the record are indexed by tag.
+const INDEX_THRESHOLD: usize = 4;
+
+/// A [RecordMatcher], which is compiled for a fast evaluation.
+///
+/// Compiling a matcher rewrites the expression into an equivalent,
+/// but cheaper form:
+///
+/// * Cheap checks (e.g. the existence of a field or subfield) are
+///   evaluated before expensive ones (e.g. regular expressions or
+///   string similarity), so that connectives short-circuit early.
+/// * Disjunctions of subfield matchers on fields with the same tag and
+///   occurrence are merged, and regular expressions (with the
+///   quantifier `ANY`) on the same subfield(s) are combined into a
+///   single [RegexSet], which is built when the matcher is compiled.
+///   All other regular expressions are built when the expression is
+///   parsed.
+/// * If the matcher consists of many field matchers, the fields of a
+///   record are indexed by tag, so that each matcher only visits the
+///   fields with a matching tag.
+///
+/// # Example
+///
+/// ```rust
+/// use pica_matcher::{MatcherOptions, RecordMatcher};
+/// use pica_record::RecordRef;
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> anyhow::Result<()> {
+///     let matcher = RecordMatcher::new(
+///         "012A.a =~ '^ab' || 012A.a =~ 'c$' || 012A.a =~ '^X'",
+///     )?;
+///     let compiled = matcher.compile(&MatcherOptions::default());
+///
+///     let record =
+///         RecordRef::new(vec![("012A", None, vec![('a', "abc")])]);
+///     assert!(compiled.is_match(&record));
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct CompiledMatcher<'a> {
+    root: FieldNode<'a>,
+    options: MatcherOptions,
+    indexed: bool,
+}
+
+impl<'a> CompiledMatcher<'a> {
+    /// Compiles a record matcher with the given options.
+    pub(crate) fn new(
+        matcher: &'a RecordMatcher,
+        options: &MatcherOptions,
+    ) -> Self {
+        let root = FieldNode::new(&matcher.field_matcher, options);
+
+        Self {
+            indexed: root.leaves() >= INDEX_THRESHOLD,
+            options: options.clone(),
+            root,
+        }
+    }
+
+    /// Returns `true` if the given record matches against the
+    /// compiled matcher.
+    pub fn is_match<T: AsRef<[u8]>>(&self, record: &Record<T>) -> bool {
+        let index = FieldIndex::new(record, self.indexed);
+        self.root.is_match(&index, &self.options)
+    }
+}
+
+/// The fields of a record, which are optionally indexed by tag.
+struct FieldIndex<'r, T: AsRef<[u8]>> {
+    record: &'r Record<T>,
+    tags: Option<HashMap<&'r [u8], Vec<&'r Field<T>>>>,
+}
+
+impl<'r, T: AsRef<[u8]>> FieldIndex<'r, T> {
+    fn new(record: &'r Record<T>, indexed: bool) -> Self {
+        let tags = indexed.then(|| {
+            let mut tags: HashMap<&[u8], Vec<&Field<T>>> =
+                HashMap::new();
+            for field in record.iter() {
+                tags.entry(field.tag().as_ref())
+                    .or_default()
+                    .push(field);
+            }
+
+            tags
+        });
+
+        Self { record, tags }
+    }
+
+    /// Returns the fields with the given tag or all fields, if no tag
+    /// is given or the fields aren't indexed.
+    fn fields(&self, tag: Option<&[u8]>) -> Fields<'_, T> {
+        match (&self.tags, tag) {
+            (Some(tags), Some(tag)) => Fields::Indexed(
+                tags.get(tag).map(Vec::as_slice).unwrap_or(&[]).iter(),
+            ),
+            _ => Fields::All(self.record.iter()),
+        }
+    }
+}
+
+/// An iterator over the fields of a [FieldIndex].
+enum Fields<'r, T: AsRef<[u8]>> {
+    All(Iter<'r, Field<T>>),
+    Indexed(Iter<'r, &'r Field<T>>),
+}
+
+impl<T: AsRef<[u8]>> Clone for Fields<'_, T> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::All(iter) => Self::All(iter.clone()),
+            Self::Indexed(iter) => Self::Indexed(iter.clone()),
+        }
+    }
+}
+
+impl<'r, T: AsRef<[u8]>> Iterator for Fields<'r, T> {
+    type Item = &'r Field<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::All(iter) => iter.next(),
+            Self::Indexed(iter) => iter.next().copied(),
+        }
+    }
+}
+
+/// Returns the tag of a tag matcher, which matches exactly one tag.
+fn simple_tag(tag_matcher: &TagMatcher) -> Option<&[u8]> {
+    match tag_matcher {
+        TagMatcher::Simple(tag) => Some(tag.as_ref()),
+        TagMatcher::Pattern(_) => None,
+    }
+}
+
+/// A node of a compiled field matcher.
+#[derive(Debug)]
+enum FieldNode<'a> {
+    And(Vec<FieldNode<'a>>),
+    Or(Vec<FieldNode<'a>>),
+    Xor(Box<(FieldNode<'a>, FieldNode<'a>)>),
+    Not(Box<FieldNode<'a>>),
+    Subfields {
+        tag_matcher: &'a TagMatcher,
+        occurrence_matcher: &'a OccurrenceMatcher,
+        subfields: SubfieldNode<'a>,
+    },
+    Leaf(&'a FieldMatcher),
+}
+
+impl<'a> FieldNode<'a> {
+    fn new(
+        matcher: &'a FieldMatcher,
+        options: &MatcherOptions,
+    ) -> Self {
+        match matcher {
+            // Field matchers with the quantifier `ALL` are evaluated
+            // by the matcher itself, because the nodes of subfield
+            // matchers assume that one matching field is sufficient.
+            FieldMatcher::Singleton(SingletonMatcher::Subfields(m))
+                if m.quantifier == Quantifier::Any =>
+            {
+                Self::Subfields {
+                    tag_matcher: &m.tag_matcher,
+                    occurrence_matcher: &m.occurrence_matcher,
+                    subfields: SubfieldNode::new(
+                        &m.subfield_matcher,
+                        options,
+                    ),
+                }
+            }
+            FieldMatcher::Group(m) => Self::new(m, options),
+            FieldMatcher::Not(m) => {
+                Self::Not(Box::new(Self::new(m, options)))
+            }
+            FieldMatcher::Composite { lhs, op, rhs } => {
+                let lhs = Self::new(lhs, options);
+                let rhs = Self::new(rhs, options);
+
+                match op {
+                    BooleanOp::And => Self::and(vec![lhs, rhs]),
+                    BooleanOp::Or => Self::or(vec![lhs, rhs], options),
+                    BooleanOp::Xor => Self::Xor(Box::new((lhs, rhs))),
+                    BooleanOp::Implies => Self::or(
+                        vec![Self::Not(Box::new(lhs)), rhs],
+                        options,
+                    ),
+                }
+            }
+            m => Self::Leaf(m),
+        }
+    }
+
+    /// Creates a conjunction, whose operands are ordered by cost.
+    fn and(operands: Vec<Self>) -> Self {
+        let mut nodes = vec![];
+        for operand in operands {
+            match operand {
+                Self::And(children) => nodes.extend(children),
+                node => nodes.push(node),
+            }
+        }
+
+        nodes.sort_by_key(Self::cost);
+        Self::And(nodes)
+    }
+
+    /// Creates a disjunction, whose operands are ordered by cost.
+    /// Operands on fields with the same tag and occurrence are merged,
+    /// because `∃f: p(f) ∨ ∃f: q(f)` is equivalent to `∃f: p(f) ∨
+    /// q(f)`.
+    fn or(operands: Vec<Self>, options: &MatcherOptions) -> Self {
+        let mut nodes: Vec<Self> = vec![];
+        for operand in operands {
+            let children = match operand {
+                Self::Or(children) => children,
+                node => vec![node],
+            };
+
+            for child in children {
+                let (tag_matcher, occurrence_matcher, subfields) =
+                    match child {
+                        Self::Subfields {
+                            tag_matcher,
+                            occurrence_matcher,
+                            subfields,
+                        } => {
+                            (tag_matcher, occurrence_matcher, subfields)
+                        }
+                        node => {
+                            nodes.push(node);
+                            continue;
+                        }
+                    };
+
+                let target = nodes.iter_mut().find(|node| {
+                    matches!(node, Self::Subfields {
+                        tag_matcher: t, occurrence_matcher: o, ..
+                    } if *t == tag_matcher && *o == occurrence_matcher)
+                });
+
+                match target {
+                    Some(Self::Subfields {
+                        subfields: ref mut merged,
+                        ..
+                    }) => {
+                        let lhs = std::mem::replace(
+                            merged,
+                            SubfieldNode::Or(vec![]),
+                        );
+                        *merged = SubfieldNode::or(
+                            vec![lhs, subfields],
+                            options,
+                        );
+                    }
+                    _ => nodes.push(Self::Subfields {
+                        tag_matcher,
+                        occurrence_matcher,
+                        subfields,
+                    }),
+                }
+            }
+        }
+
+        if nodes.len() == 1 {
+            return nodes.remove(0);
+        }
+
+        nodes.sort_by_key(Self::cost);
+        Self::Or(nodes)
+    }
+
+    /// Returns the estimated cost of the evaluation.
+    fn cost(&self) -> usize {
+        match self {
+            Self::And(nodes) | Self::Or(nodes) => {
+                nodes.iter().map(Self::cost).sum()
+            }
+            Self::Xor(nodes) => nodes.0.cost() + nodes.1.cost(),
+            Self::Not(node) => node.cost(),
+            Self::Subfields { subfields, .. } => 2 + subfields.cost(),
+            Self::Leaf(FieldMatcher::Singleton(
+                SingletonMatcher::Exists(_),
+            )) => 1,
+            Self::Leaf(FieldMatcher::Cardinality(_)) => 4,
+            Self::Leaf(_) => 8,
+        }
+    }
+
+    /// Returns the number of field matchers.
+    fn leaves(&self) -> usize {
+        match self {
+            Self::And(nodes) | Self::Or(nodes) => {
+                nodes.iter().map(Self::leaves).sum()
+            }
+            Self::Xor(nodes) => nodes.0.leaves() + nodes.1.leaves(),
+            Self::Not(node) => node.leaves(),
+            _ => 1,
+        }
+    }
+
+    /// Returns the tag of the fields, which are visited by the node,
+    /// or `None`, if the node visits fields of different tags.
+    fn tag(&self) -> Option<&[u8]> {
+        match self {
+            Self::Subfields { tag_matcher, .. } => {
+                simple_tag(tag_matcher)
+            }
+            Self::Leaf(FieldMatcher::Singleton(
+                SingletonMatcher::Exists(m),
+            )) => simple_tag(&m.tag_matcher),
+            Self::Leaf(FieldMatcher::Cardinality(
+                CardinalityMatcher {
+                    lhs,
+                    rhs: Cardinality::Value(_),
+                    ..
+                },
+            )) => simple_tag(&lhs.tag_matcher),
+            Self::Leaf(FieldMatcher::Singleton(
+                SingletonMatcher::Subfields(m),
+            )) => simple_tag(&m.tag_matcher),
+            _ => None,
+        }
+    }
+
+    fn is_match<T: AsRef<[u8]>>(
+        &self,
+        index: &FieldIndex<T>,
+        options: &MatcherOptions,
+    ) -> bool {
+        match self {
+            Self::And(nodes) => {
+                nodes.iter().all(|node| node.is_match(index, options))
+            }
+            Self::Or(nodes) => {
+                nodes.iter().any(|node| node.is_match(index, options))
+            }
+            Self::Xor(nodes) => {
+                nodes.0.is_match(index, options)
+                    != nodes.1.is_match(index, options)
+            }
+            Self::Not(node) => !node.is_match(index, options),
+            Self::Subfields {
+                tag_matcher,
+                occurrence_matcher,
+                subfields,
+            } => index.fields(self.tag()).any(|field| {
+                **tag_matcher == field.tag()
+                    && **occurrence_matcher == field.occurrence()
+                    && subfields.is_match(field.subfields(), options)
+            }),
+            Self::Leaf(m) => {
+                m.is_match(index.fields(self.tag()), options)
+            }
+        }
+    }
+}
+
+/// A node of a compiled subfield matcher.
+#[derive(Debug)]
+enum SubfieldNode<'a> {
+    And(Vec<SubfieldNode<'a>>),
+    Or(Vec<SubfieldNode<'a>>),
+    Xor(Box<(SubfieldNode<'a>, SubfieldNode<'a>)>),
+    Not(Box<SubfieldNode<'a>>),
+    Regex {
+        codes: &'a [char],
+        set: RegexSet,
+        invert: bool,
+    },
+    Leaf(&'a subfield_matcher::SingletonMatcher),
+}
+
+impl<'a> SubfieldNode<'a> {
+    fn new(
+        matcher: &'a SubfieldMatcher,
+        options: &MatcherOptions,
+    ) -> Self {
+        use subfield_matcher::SingletonMatcher::Regex;
+
+        match matcher {
+            SubfieldMatcher::Singleton(m) => match m {
+                Regex(regex) if regex.quantifier == Quantifier::Any => {
+                    match build_set([&regex.pattern], options) {
+                        Some(set) => Self::Regex {
+                            codes: &regex.codes,
+                            set,
+                            invert: regex.invert,
+                        },
+                        None => Self::Leaf(m),
+                    }
+                }
+                _ => Self::Leaf(m),
+            },
+            SubfieldMatcher::Group(m) => Self::new(m, options),
+            SubfieldMatcher::Not(m) => {
+                Self::Not(Box::new(Self::new(m, options)))
+            }
+            SubfieldMatcher::Composite { lhs, op, rhs } => {
+                let lhs = Self::new(lhs, options);
+                let rhs = Self::new(rhs, options);
+
+                match op {
+                    BooleanOp::And => Self::and(vec![lhs, rhs]),
+                    BooleanOp::Or => Self::or(vec![lhs, rhs], options),
+                    BooleanOp::Xor => Self::Xor(Box::new((lhs, rhs))),
+                    BooleanOp::Implies => Self::or(
+                        vec![Self::Not(Box::new(lhs)), rhs],
+                        options,
+                    ),
+                }
+            }
+        }
+    }
+
+    /// Creates a conjunction, whose operands are ordered by cost.
+    fn and(operands: Vec<Self>) -> Self {
+        let mut nodes = vec![];
+        for operand in operands {
+            match operand {
+                Self::And(children) => nodes.extend(children),
+                node => nodes.push(node),
+            }
+        }
+
+        nodes.sort_by_key(Self::cost);
+        Self::And(nodes)
+    }
+
+    /// Creates a disjunction, whose operands are ordered by cost.
+    /// Regular expressions on the same subfield(s) are combined into a
+    /// single [RegexSet].
+    fn or(operands: Vec<Self>, options: &MatcherOptions) -> Self {
+        let mut nodes: Vec<Self> = vec![];
+        for operand in operands {
+            let children = match operand {
+                Self::Or(children) => children,
+                node => vec![node],
+            };
+
+            for child in children {
+                let target = match child {
+                    Self::Regex {
+                        codes,
+                        invert: false,
+                        ..
+                    } => nodes.iter_mut().find(|node| {
+                        matches!(node, Self::Regex {
+                            codes: c, invert: false, ..
+                        } if *c == codes)
+                    }),
+                    _ => None,
+                };
+
+                let merged = match (target, &child) {
+                    (
+                        Some(Self::Regex {
+                            set: ref mut lhs, ..
+                        }),
+                        Self::Regex { set: rhs, .. },
+                    ) => build_set(
+                        lhs.patterns().iter().chain(rhs.patterns()),
+                        options,
+                    )
+                    .map(|set| *lhs = set)
+                    .is_some(),
+                    _ => false,
+                };
+
+                if !merged {
+                    nodes.push(child);
+                }
+            }
+        }
+
+        if nodes.len() == 1 {
+            return nodes.remove(0);
+        }
+
+        nodes.sort_by_key(Self::cost);
+        Self::Or(nodes)
+    }
+
+    /// Returns the estimated cost of the evaluation.
+    fn cost(&self) -> usize {
+        use subfield_matcher::SingletonMatcher::*;
+
+        match self {
+            Self::And(nodes) | Self::Or(nodes) => {
+                nodes.iter().map(Self::cost).sum()
+            }
+            Self::Xor(nodes) => nodes.0.cost() + nodes.1.cost(),
+            Self::Not(node) => node.cost(),
+            Self::Regex { .. } => 8,
+            Self::Leaf(Exists(_) | Cardinality(_)) => 1,
+            Self::Leaf(In(_)) => 2,
+            Self::Leaf(Relation(m))
+                if m.op == RelationalOp::Similar =>
+            {
+                16
+            }
+            Self::Leaf(Relation(_)) => 2,
+            Self::Leaf(Regex(_)) => 8,
+        }
+    }
+
+    fn is_match<T: AsRef<[u8]>>(
+        &self,
+        subfields: &[Subfield<T>],
+        options: &MatcherOptions,
+    ) -> bool {
+        match self {
+            Self::And(nodes) => nodes
+                .iter()
+                .all(|node| node.is_match(subfields, options)),
+            Self::Or(nodes) => nodes
+                .iter()
+                .any(|node| node.is_match(subfields, options)),
+            Self::Xor(nodes) => {
+                nodes.0.is_match(subfields, options)
+                    != nodes.1.is_match(subfields, options)
+            }
+            Self::Not(node) => !node.is_match(subfields, options),
+            Self::Regex { codes, set, invert } => subfields
+                .iter()
+                .filter(|subfield| codes.contains(&subfield.code()))
+                .any(|subfield| {
+                    let value = options
+                        .normalize_unicode(subfield.value().as_ref());
+                    set.is_match(&value) != *invert
+                }),
+            Self::Leaf(m) => m.is_match(subfields, options),
+        }
+    }
+}
+
+/// Builds a set of regular expressions. Only the values are normalized,
+/// the patterns are used as they are. Returns `None`, if the set exceeds
+/// the size limit of the regex engine.
+fn build_set<I, S>(
+    patterns: I,
+    options: &MatcherOptions,
+) -> Option<RegexSet>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    RegexSetBuilder::new(patterns)
+        .case_insensitive(options.is_case_insensitive())
+        .build()
+        .ok()
+}
@@ -0,0 +1,90 @@
+//! The context in which matcher expressions are parsed.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// The context of a matcher expression, which provides the library of
+/// named filters (`%name`) and the base directory of list file
+/// references (`0 in @codes.txt`).
+///
+/// # Example
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+///
+/// use pica_matcher::{ParseContext, RecordMatcher};
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> anyhow::Result<()> {
+///     let ctx = ParseContext::new().definitions(BTreeMap::from([(
+///         "persons".to_string(),
+///         "002@.0 =^ 'Tp'".to_string(),
+///     )]));
+///
+///     let matcher =
+///         RecordMatcher::with_context("%persons && 028A?", &ctx)?;
+///     assert_eq!(matcher.to_string(), "002@.0 =^ 'Tp' && 028A?");
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ParseContext {
+    pub(crate) definitions: BTreeMap<String, String>,
+    base_dir: Option<PathBuf>,
+}
+
+impl ParseContext {
+    /// Creates a new context without any named filters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the library of named filters, which can be referenced as
+    /// `%name` in matcher expressions.
+    pub fn definitions(
+        mut self,
+        definitions: BTreeMap<String, String>,
+    ) -> Self {
+        self.definitions = definitions;
+        self
+    }
+
+    /// Sets the base directory, which is used to resolve relative
+    /// paths of list file references (e.g. `0 in @codes.txt`). Without
+    /// a base directory relative paths are resolved against the
+    /// current working directory.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use pica_matcher::{ParseContext, RecordMatcher};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> anyhow::Result<()> {
+    ///     let ctx = ParseContext::new().base_dir("tests/data");
+    ///     let _matcher =
+    ///         RecordMatcher::with_context("002@.0 in @bbg.txt", &ctx)?;
+    ///     assert!(RecordMatcher::new("002@.0 in @bbg.txt").is_err());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn base_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.base_dir = Some(dir.into());
+        self
+    }
+
+    /// Resolves a relative `path` against the base directory. Absolute
+    /// paths and paths of a context without a base directory are
+    /// returned unchanged.
+    pub(crate) fn resolve_path<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> PathBuf {
+        match self.base_dir {
+            Some(ref dir) if path.as_ref().is_relative() => {
+                dir.join(path.as_ref())
+            }
+            _ => path.as_ref().to_path_buf(),
+        }
+    }
+}
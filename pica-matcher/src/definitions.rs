@@ -0,0 +1,222 @@
+//! Named filter definitions, which can be reused within an expression
+//! (`let persons = 002@.0 =^ 'Tp'; $persons && 028A?`) or shared as a
+//! library of named filters (`%persons`, see
+//! [ParseContext](crate::ParseContext)).
+
+use std::collections::BTreeMap;
+
+use nom::bytes::complete::{tag, take_while1};
+use nom::character::complete::{
+    char, multispace0, multispace1, one_of,
+};
+use nom::combinator::{all_consuming, cut, map_res, opt, verify};
+use nom::error::{context, ParseError};
+use nom::sequence::{pair, terminated, tuple};
+use nom::Finish;
+
+use crate::common::{ws, ParseResult};
+use crate::error::{ParserError, SyntaxError};
+use crate::field_matcher::parse_field_matcher_with;
+use crate::subfield_matcher::parse_subfield_matcher_with;
+
+static EMPTY: BTreeMap<String, String> = BTreeMap::new();
+
+/// The named filters, which can be referenced while parsing an
+/// expression: the definitions of the expression (`$name`) and the
+/// library of named filters (`%name`).
+#[derive(Debug, Clone)]
+pub(crate) struct Definitions<'a> {
+    library: &'a BTreeMap<String, String>,
+    /// The definitions of the expression. The input of a definition
+    /// starts with its expression, which is followed by `;`.
+    locals: Vec<(&'a str, &'a [u8])>,
+    /// The library filters, which are currently parsed, in order to
+    /// detect cyclic definitions.
+    stack: Vec<&'a str>,
+}
+
+impl Default for Definitions<'_> {
+    fn default() -> Self {
+        Self::new(&EMPTY)
+    }
+}
+
+impl<'a> Definitions<'a> {
+    /// Creates definitions without any local definitions.
+    pub(crate) fn new(library: &'a BTreeMap<String, String>) -> Self {
+        Self {
+            library,
+            locals: vec![],
+            stack: vec![],
+        }
+    }
+}
+
+/// Returns `true` if the byte can be part of a name.
+#[inline]
+fn is_name_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_'
+}
+
+/// Parse the name of a definition. A `$` followed by a single
+/// character refers to a subfield (`003@ $0`), so names must have at
+/// least two characters and must not start with a digit.
+fn parse_name(i: &[u8]) -> ParseResult<&str> {
+    context(
+        "name",
+        verify(
+            map_res(take_while1(is_name_char), std::str::from_utf8),
+            |name: &str| {
+                name.len() >= 2
+                    && !name.starts_with(|c: char| c.is_ascii_digit())
+            },
+        ),
+    )(i)
+}
+
+/// Parse the expression of a definition, which is either a field or a
+/// subfield matcher expression, followed by `;`.
+fn parse_definition_expr<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, ()> {
+    let field = terminated(
+        |i| parse_field_matcher_with(i, defs),
+        ws(char(';')),
+    )(i);
+
+    let err = match field {
+        Ok((rest, _)) => return Ok((rest, ())),
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => e,
+        Err(e) => return Err(e),
+    };
+
+    match terminated(
+        |i| parse_subfield_matcher_with(i, defs),
+        ws(char(';')),
+    )(i)
+    {
+        Ok((rest, _)) => Ok((rest, ())),
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => {
+            Err(nom::Err::Failure(err.or(e)))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Parse a definition (`let name = expr;`) and return its name and
+/// the input, which starts with the expression of the definition.
+fn parse_definition<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, (&'a str, &'a [u8])> {
+    let (i, _) = tuple((multispace0, tag("let"), multispace1))(i)?;
+    let (rest, name) = cut(parse_name)(i)?;
+
+    if defs.locals.iter().any(|(n, _)| *n == name) {
+        return Err(nom::Err::Failure(ParserError::invalid(
+            i,
+            format!("`${name}` is defined more than once"),
+        )));
+    }
+
+    let (expr, _) = cut(ws(char('=')))(rest)?;
+    let (rest, _) = parse_definition_expr(expr, defs)?;
+
+    Ok((rest, (name, expr)))
+}
+
+/// Parse an expression, which may start with definitions (`let name =
+/// expr;`), whereby `parser` parses the expression itself.
+pub(crate) fn parse_definitions<'a, O>(
+    i: &'a [u8],
+    library: &'a BTreeMap<String, String>,
+    parser: impl Fn(&'a [u8], &Definitions<'a>) -> ParseResult<'a, O>,
+) -> ParseResult<'a, O> {
+    let mut defs = Definitions::new(library);
+    let mut i = i;
+
+    loop {
+        let (rest, definition) =
+            opt(|i| parse_definition(i, &defs))(i)?;
+        let Some(definition) = definition else {
+            break;
+        };
+
+        defs.locals.push(definition);
+        i = rest;
+    }
+
+    parser(i, &defs)
+}
+
+/// Parse a reference to a named filter (`$name` or `%name`). The
+/// referenced expression is parsed by `parser`, which parses a `kind`
+/// of expression (e.g. a field matcher).
+///
+/// Definitions have been checked when they were defined, but a
+/// definition may be of the wrong kind (e.g. a subfield matcher, which
+/// is used as a field matcher). Errors in library filters are reported
+/// at the position of the reference.
+pub(crate) fn parse_reference<'a, O, F>(
+    i: &'a [u8],
+    defs: &Definitions,
+    parser: F,
+    kind: &str,
+) -> ParseResult<'a, O>
+where
+    F: for<'b> Fn(&'b [u8], &Definitions) -> ParseResult<'b, O>,
+{
+    let (rest, (sigil, name)) = pair(one_of("$%"), parse_name)(i)?;
+    let invalid = |reason: String| {
+        Err(nom::Err::Failure(ParserError::invalid(i, reason)))
+    };
+
+    if sigil == '$' {
+        let Some(pos) =
+            defs.locals.iter().position(|(n, _)| *n == name)
+        else {
+            return invalid(format!("`${name}` is not defined"));
+        };
+
+        // A definition may only refer to the preceding definitions.
+        let inner = Definitions {
+            library: defs.library,
+            locals: defs.locals[..pos].to_vec(),
+            stack: defs.stack.clone(),
+        };
+
+        return match terminated(|i| parser(i, &inner), ws(char(';')))(
+            defs.locals[pos].1,
+        ) {
+            Ok((_, matcher)) => Ok((rest, matcher)),
+            Err(_) => invalid(format!("`${name}` is not a {kind}")),
+        };
+    }
+
+    let Some(expr) = defs.library.get(name) else {
+        return invalid(format!("`%{name}` is not defined"));
+    };
+
+    if defs.stack.contains(&name) {
+        return invalid(format!(
+            "`%{name}` is defined in terms of itself"
+        ));
+    }
+
+    let mut inner = Definitions::new(defs.library);
+    inner.stack = defs.stack.clone();
+    inner.stack.push(name);
+
+    let result =
+        all_consuming(ws(|i| parser(i, &inner)))(expr.as_bytes())
+            .finish();
+
+    match result {
+        Ok((_, matcher)) => Ok((rest, matcher)),
+        Err(e) => invalid(format!(
+            "invalid filter `%{name}`: {}",
+            SyntaxError::new(expr, e).message()
+        )),
+    }
+}
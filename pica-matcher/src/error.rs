@@ -1,3 +1,7 @@
+use std::fmt::{self, Display};
+
+use bstr::ByteSlice;
+use nom::error::{ContextError, ErrorKind, FromExternalError};
 use thiserror::Error;
 
 /// An error that can occur when parsing matcher expressions.
@@ -7,10 +11,253 @@ pub enum ParseMatcherError {
     InvalidTagMatcher,
     #[error("invalid occurrence matcher (got `{0}`)")]
     InvalidOccurrenceMatcher(String),
-    #[error("invalid subfield matcher (got `{0}`)")]
-    InvalidSubfieldMatcher(String),
-    #[error("invalid field matcher (got `{0}`)")]
-    InvalidFieldMatcher(String),
-    #[error("invalid record matcher (got `{0}`)")]
-    InvalidRecordMatcher(String),
+    #[error("invalid subfield matcher: {0}")]
+    InvalidSubfieldMatcher(SyntaxError),
+    #[error("invalid field matcher: {0}")]
+    InvalidFieldMatcher(SyntaxError),
+    #[error("invalid record matcher: {0}")]
+    InvalidRecordMatcher(SyntaxError),
+    #[error("invalid normalization form (got `{0}`)")]
+    InvalidNormalizationForm(String),
+    #[error("invalid similarity metric (got `{0}`)")]
+    InvalidStrsimMetric(String),
+    #[error("invalid scope (got `{0}`)")]
+    InvalidScope(String),
+    #[error("invalid list file `{0}`: {1}")]
+    InvalidListFile(String, String),
+}
+
+/// The error type of the (nom) parsers of matcher expressions.
+///
+/// The error keeps track of the remaining input at the position the
+/// parser failed and what was expected at this position. If two
+/// alternatives fail, the error of the alternative that got further
+/// is kept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParserError<'a> {
+    input: &'a [u8],
+    expected: Vec<String>,
+    reason: Option<String>,
+}
+
+impl<'a> ParserError<'a> {
+    /// Creates a new error at the given position, where `expected` was
+    /// expected.
+    pub fn expected(
+        input: &'a [u8],
+        expected: impl Into<String>,
+    ) -> Self {
+        Self {
+            input,
+            expected: vec![expected.into()],
+            reason: None,
+        }
+    }
+
+    /// Creates a new error at the given position, which is
+    /// syntactically valid but rejected for the given reason (e.g. a
+    /// reference to an undefined filter).
+    pub fn invalid(input: &'a [u8], reason: impl Into<String>) -> Self {
+        Self {
+            input,
+            expected: vec![],
+            reason: Some(reason.into()),
+        }
+    }
+}
+
+impl<'a> nom::error::ParseError<&'a [u8]> for ParserError<'a> {
+    fn from_error_kind(input: &'a [u8], _kind: ErrorKind) -> Self {
+        Self {
+            input,
+            expected: vec![],
+            reason: None,
+        }
+    }
+
+    fn append(_input: &'a [u8], _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+
+    fn from_char(input: &'a [u8], c: char) -> Self {
+        Self::expected(input, format!("`{c}`"))
+    }
+
+    fn or(mut self, other: Self) -> Self {
+        match self.input.len().cmp(&other.input.len()) {
+            std::cmp::Ordering::Less => self,
+            std::cmp::Ordering::Greater => other,
+            std::cmp::Ordering::Equal => {
+                for expected in other.expected {
+                    if !self.expected.contains(&expected) {
+                        self.expected.push(expected);
+                    }
+                }
+
+                self.reason = self.reason.or(other.reason);
+                self
+            }
+        }
+    }
+}
+
+impl<'a> ContextError<&'a [u8]> for ParserError<'a> {
+    fn add_context(
+        input: &'a [u8],
+        ctx: &'static str,
+        other: Self,
+    ) -> Self {
+        if input.len() == other.input.len() && other.reason.is_none() {
+            Self::expected(input, ctx)
+        } else {
+            other
+        }
+    }
+}
+
+impl<'a, E> FromExternalError<&'a [u8], E> for ParserError<'a> {
+    fn from_external_error(
+        input: &'a [u8],
+        kind: ErrorKind,
+        _e: E,
+    ) -> Self {
+        <Self as nom::error::ParseError<&'a [u8]>>::from_error_kind(
+            input, kind,
+        )
+    }
+}
+
+/// A syntax error in an expression.
+///
+/// The error contains the position at which the expression could not
+/// be parsed any further and what was expected at this position. The
+/// `Display` implementation renders the position together with the
+/// affected line of the expression and a caret pointing to the error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    input: String,
+    offset: usize,
+    expected: Vec<String>,
+    reason: Option<String>,
+}
+
+impl SyntaxError {
+    /// Creates a syntax error from the (complete) input and the error
+    /// of the parser.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use pica_matcher::{ParseMatcherError, RecordMatcher};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> anyhow::Result<()> {
+    ///     let Err(ParseMatcherError::InvalidRecordMatcher(err)) =
+    ///         RecordMatcher::new("003@.0 == 'a' && 012A.0 =~ '['")
+    ///     else {
+    ///         unreachable!()
+    ///     };
+    ///
+    ///     assert_eq!(err.offset(), 27);
+    ///     assert_eq!((err.line(), err.column()), (1, 28));
+    ///     assert_eq!(err.expected(), ["regular expression"]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn new<T: AsRef<[u8]>>(input: T, err: ParserError) -> Self {
+        let input = input.as_ref();
+        let offset = input.len().saturating_sub(err.input.len());
+
+        Self {
+            input: input.to_str_lossy().to_string(),
+            offset,
+            expected: err.expected,
+            reason: err.reason,
+        }
+    }
+
+    /// Returns the expression that could not be parsed.
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// Returns the byte offset of the error.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns what was expected at the position of the error.
+    pub fn expected(&self) -> &[String] {
+        &self.expected
+    }
+
+    /// Returns the message of the error without its position (e.g.
+    /// "unexpected end of expression, expected `'`").
+    pub fn message(&self) -> String {
+        if let Some(ref reason) = self.reason {
+            return reason.clone();
+        }
+
+        let mut message = if self.offset >= self.input.len() {
+            "unexpected end of expression".to_string()
+        } else {
+            "unexpected input".to_string()
+        };
+
+        if let Some((last, init)) = self.expected.split_last() {
+            if init.is_empty() {
+                message.push_str(&format!(", expected {last}"));
+            } else {
+                message.push_str(&format!(
+                    ", expected {} or {last}",
+                    init.join(", ")
+                ));
+            }
+        }
+
+        message
+    }
+
+    /// Returns the line number (starting at 1) of the error.
+    pub fn line(&self) -> usize {
+        self.input.as_bytes()[..self.offset]
+            .iter()
+            .filter(|&&b| b == b'\n')
+            .count()
+            + 1
+    }
+
+    /// Returns the column (starting at 1) of the error.
+    pub fn column(&self) -> usize {
+        let start = self.line_start();
+        self.input.as_bytes()[start..self.offset].chars().count() + 1
+    }
+
+    /// Returns the byte offset of the start of the erroneous line.
+    fn line_start(&self) -> usize {
+        self.input.as_bytes()[..self.offset]
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map(|pos| pos + 1)
+            .unwrap_or_default()
+    }
+}
+
+impl Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (line, column) = (self.line(), self.column());
+        write!(
+            f,
+            "{} at line {line}, column {column}",
+            self.message()
+        )?;
+
+        let start = self.line_start();
+        let text =
+            self.input[start..].lines().next().unwrap_or_default();
+        let gutter = " ".repeat(line.to_string().len());
+
+        write!(f, "\n{gutter} |\n{line} | {text}")?;
+        write!(f, "\n{gutter} | {}^", " ".repeat(column - 1))
+    }
 }
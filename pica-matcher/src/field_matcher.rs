@@ -1,36 +1,50 @@
 //! Matcher that works on PICA+ [Fields](pica_record::Field).
 
-use std::ops::{BitAnd, BitOr, Not};
+use std::collections::HashSet;
+use std::fmt::{self, Display};
+use std::ops::{BitAnd, BitOr, BitXor, Not};
 
+use bstr::ByteSlice;
 use nom::branch::alt;
 use nom::bytes::complete::tag;
-use nom::character::complete::{char, digit1};
-use nom::combinator::{all_consuming, cut, map, map_res, opt};
-use nom::multi::many1;
-use nom::sequence::{pair, preceded, terminated, tuple};
+use nom::character::complete::char;
+use nom::combinator::{all_consuming, cut, map, opt};
+use nom::multi::many0;
+use nom::sequence::{
+    delimited, pair, preceded, separated_pair, terminated, tuple,
+};
 use nom::Finish;
-use pica_record::parser::ParseResult;
 use pica_record::Field;
+#[cfg(feature = "serde")]
+use serde::ser::{Serialize, SerializeMap, Serializer};
 
 use crate::common::{
-    parse_relational_op_usize, ws, BooleanOp, RelationalOp,
+    parse_implies_op, parse_quantifier, parse_relational_op,
+    parse_relational_op_usize, parse_usize, parse_xor_op, ws,
+    BooleanOp, ParseResult, Quantifier, RelationalOp,
 };
+use crate::definitions::{parse_reference, Definitions};
+use crate::list::{ListCache, LoadLists};
 use crate::occurrence_matcher::{
     parse_occurrence_matcher, OccurrenceMatcher,
 };
+use crate::scope::field_key;
 use crate::subfield_matcher::{
-    self, parse_subfield_matcher, parse_subfield_singleton_matcher,
-    Matcher,
+    self, parse_date_relation, parse_length_relation,
+    parse_subfield_codes, parse_subfield_matcher_with,
+    parse_subfield_singleton_matcher, relate, Matcher, SubfieldCodes,
 };
 use crate::tag_matcher::parse_tag_matcher;
+use crate::trace::{fmt_field, Trace};
 use crate::{
-    MatcherOptions, ParseMatcherError, SubfieldMatcher, TagMatcher,
+    MatcherOptions, ParseContext, ParseMatcherError, Scope,
+    SubfieldMatcher, SyntaxError, TagMatcher,
 };
 
 /// A field matcher that checks if a field exists.
 #[derive(Debug, PartialEq, Eq)]
 pub struct ExistsMatcher {
-    tag_matcher: TagMatcher,
+    pub(crate) tag_matcher: TagMatcher,
     occurrence_matcher: OccurrenceMatcher,
 }
 
@@ -77,8 +91,10 @@ impl ExistsMatcher {
     pub fn new(data: &str) -> Result<Self, ParseMatcherError> {
         all_consuming(parse_exists_matcher)(data.as_bytes())
             .finish()
-            .map_err(|_| {
-                ParseMatcherError::InvalidFieldMatcher(data.into())
+            .map_err(|e| {
+                ParseMatcherError::InvalidFieldMatcher(
+                    SyntaxError::new(data, e),
+                )
             })
             .map(|(_, matcher)| matcher)
     }
@@ -95,14 +111,62 @@ impl ExistsMatcher {
                 && self.occurrence_matcher == field.occurrence()
         })
     }
+
+    /// Returns the evaluation trace of the matcher.
+    fn trace<'a, T: AsRef<[u8]> + 'a>(
+        &self,
+        fields: impl IntoIterator<Item = &'a Field<T>> + Clone,
+        options: &MatcherOptions,
+    ) -> Trace {
+        let matches = fields
+            .clone()
+            .into_iter()
+            .filter(|field| {
+                self.tag_matcher == field.tag()
+                    && self.occurrence_matcher == field.occurrence()
+            })
+            .map(fmt_field)
+            .collect();
+
+        Trace::new(self.to_string(), self.is_match(fields, options))
+            .with_matches(matches)
+    }
+}
+
+impl Display for ExistsMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}?", self.tag_matcher, self.occurrence_matcher)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for ExistsMatcher {
+    fn serialize<S: Serializer>(
+        &self,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut map = s.serialize_map(Some(3))?;
+        map.serialize_entry("type", "exists")?;
+        map.serialize_entry("tag", &self.tag_matcher)?;
+        map.serialize_entry("occurrence", &self.occurrence_matcher)?;
+        map.end()
+    }
 }
 
 /// A field matcher that checks for fields statifies subfield criterion.
+///
+/// With the quantifier `ANY` (the default) at least one field with a
+/// matching tag and occurrence must satisfy the subfield matcher; with
+/// `ALL` every such field must satisfy it (e.g. `ALL 044H{9?}`). In
+/// dot notation the quantifier applies to the subfield values too, so
+/// `ALL 044H.a =~ '^[A-Z]'` requires every `$a` of every `044H` field
+/// to match. If there is no such field, the matcher isn't satisfied.
 #[derive(Debug, PartialEq, Eq)]
 pub struct SubfieldsMatcher {
-    tag_matcher: TagMatcher,
-    occurrence_matcher: OccurrenceMatcher,
-    subfield_matcher: SubfieldMatcher,
+    pub(crate) quantifier: Quantifier,
+    pub(crate) tag_matcher: TagMatcher,
+    pub(crate) occurrence_matcher: OccurrenceMatcher,
+    pub(crate) subfield_matcher: SubfieldMatcher,
 }
 
 /// Parse a subfields matcher expression.
@@ -111,6 +175,7 @@ fn parse_subfields_matcher_dot(
 ) -> ParseResult<SubfieldsMatcher> {
     map(
         tuple((
+            map(opt(ws(parse_quantifier)), Option::unwrap_or_default),
             parse_tag_matcher,
             parse_occurrence_matcher,
             preceded(
@@ -118,27 +183,42 @@ fn parse_subfields_matcher_dot(
                 parse_subfield_singleton_matcher,
             ),
         )),
-        |(t, o, s)| SubfieldsMatcher {
-            tag_matcher: t,
-            occurrence_matcher: o,
-            subfield_matcher: s,
+        |(q, t, o, mut s)| {
+            if let SubfieldMatcher::Singleton(ref mut m) = s {
+                if q == Quantifier::All {
+                    m.set_quantifier(q.clone());
+                }
+            }
+
+            SubfieldsMatcher {
+                quantifier: q,
+                tag_matcher: t,
+                occurrence_matcher: o,
+                subfield_matcher: s,
+            }
         },
     )(i)
 }
 
-fn parse_subfields_matcher_bracket(
-    i: &[u8],
-) -> ParseResult<SubfieldsMatcher> {
+fn parse_subfields_matcher_bracket<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, SubfieldsMatcher> {
     map(
         tuple((
+            map(opt(ws(parse_quantifier)), Option::unwrap_or_default),
             parse_tag_matcher,
             parse_occurrence_matcher,
             preceded(
                 ws(char('{')),
-                cut(terminated(parse_subfield_matcher, ws(char('}')))),
+                cut(terminated(
+                    |i| parse_subfield_matcher_with(i, defs),
+                    ws(char('}')),
+                )),
             ),
         )),
-        |(t, o, s)| SubfieldsMatcher {
+        |(q, t, o, s)| SubfieldsMatcher {
+            quantifier: q,
             tag_matcher: t,
             occurrence_matcher: o,
             subfield_matcher: s,
@@ -146,10 +226,58 @@ fn parse_subfields_matcher_bracket(
     )(i)
 }
 
-fn parse_subfields_matcher(i: &[u8]) -> ParseResult<SubfieldsMatcher> {
-    alt((parse_subfields_matcher_dot, parse_subfields_matcher_bracket))(
-        i,
-    )
+/// Parse a subfields matcher expression, which compares dates (e.g.
+/// `date(001A.0, '%d-%m-%y') >= '2020-01-01'`).
+fn parse_subfields_matcher_date(
+    i: &[u8],
+) -> ParseResult<SubfieldsMatcher> {
+    map(
+        parse_date_relation(terminated(
+            pair(parse_tag_matcher, parse_occurrence_matcher),
+            char('.'),
+        )),
+        |((t, o), m)| SubfieldsMatcher {
+            quantifier: Quantifier::Any,
+            tag_matcher: t,
+            occurrence_matcher: o,
+            subfield_matcher: SubfieldMatcher::Singleton(
+                subfield_matcher::SingletonMatcher::Relation(m),
+            ),
+        },
+    )(i)
+}
+
+/// Parse a subfields matcher expression, which compares the length of
+/// subfield values (e.g. `len(021A.a) > 200`).
+fn parse_subfields_matcher_length(
+    i: &[u8],
+) -> ParseResult<SubfieldsMatcher> {
+    map(
+        parse_length_relation(terminated(
+            pair(parse_tag_matcher, parse_occurrence_matcher),
+            char('.'),
+        )),
+        |((t, o), m)| SubfieldsMatcher {
+            quantifier: Quantifier::Any,
+            tag_matcher: t,
+            occurrence_matcher: o,
+            subfield_matcher: SubfieldMatcher::Singleton(
+                subfield_matcher::SingletonMatcher::Relation(m),
+            ),
+        },
+    )(i)
+}
+
+fn parse_subfields_matcher<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, SubfieldsMatcher> {
+    alt((
+        parse_subfields_matcher_dot,
+        |i| parse_subfields_matcher_bracket(i, defs),
+        parse_subfields_matcher_date,
+        parse_subfields_matcher_length,
+    ))(i)
 }
 
 impl SubfieldsMatcher {
@@ -179,53 +307,430 @@ impl SubfieldsMatcher {
     /// }
     /// ```
     pub fn new(data: &str) -> Result<Self, ParseMatcherError> {
-        all_consuming(parse_subfields_matcher)(data.as_bytes())
-            .finish()
-            .map_err(|_| {
-                ParseMatcherError::InvalidFieldMatcher(data.into())
-            })
-            .map(|(_, matcher)| matcher)
+        all_consuming(|i| {
+            parse_subfields_matcher(i, &Definitions::default())
+        })(data.as_bytes())
+        .finish()
+        .map_err(|e| {
+            ParseMatcherError::InvalidFieldMatcher(SyntaxError::new(
+                data, e,
+            ))
+        })
+        .and_then(|(_, matcher)| {
+            matcher.with_lists(&ParseContext::default())
+        })
     }
 
-    /// Returns `true` if at least one field exists with a matching tag
-    /// and occurrence and a subfield matching the subfield matcher's
-    /// criteria.
+    /// Returns `true` if at least one field (or all fields, if the
+    /// quantifier is `ALL`) exists with a matching tag and occurrence
+    /// and a subfield matching the subfield matcher's criteria.
     pub fn is_match<'a, T: AsRef<[u8]> + 'a>(
         &self,
         fields: impl IntoIterator<Item = &'a Field<T>> + Clone,
         options: &MatcherOptions,
     ) -> bool {
-        fields.into_iter().any(|field| {
+        let fields = fields.into_iter().filter(|field| {
             self.tag_matcher == field.tag()
                 && self.occurrence_matcher == field.occurrence()
-                && self
+        });
+
+        self.quantifier.eval(fields, |field| {
+            self.subfield_matcher.is_match(field.subfields(), options)
+        })
+    }
+
+    /// Returns the evaluation trace of the matcher. The trace contains
+    /// the evaluation of the subfield matcher for each field with a
+    /// matching tag and occurrence.
+    fn trace<'a, T: AsRef<[u8]> + 'a>(
+        &self,
+        fields: impl IntoIterator<Item = &'a Field<T>> + Clone,
+        options: &MatcherOptions,
+    ) -> Trace {
+        let children: Vec<Trace> = fields
+            .into_iter()
+            .filter(|field| {
+                self.tag_matcher == field.tag()
+                    && self.occurrence_matcher == field.occurrence()
+            })
+            .map(|field| {
+                let trace = self
                     .subfield_matcher
-                    .is_match(field.subfields(), options)
+                    .trace(field.subfields(), options);
+                let result =
+                    Trace::new(fmt_field(field), trace.result());
+
+                // The expression of a singleton matcher is already part
+                // of the parent's expression, so only its matches are
+                // kept.
+                if trace.children().is_empty() {
+                    result.with_matches(trace.matches().to_vec())
+                } else {
+                    result.with_children(vec![trace])
+                }
+            })
+            .collect();
+
+        let matches: Vec<String> = children
+            .iter()
+            .filter(|trace| trace.result())
+            .map(|trace| trace.expr().to_string())
+            .collect();
+
+        let result = self.quantifier.eval(&children, Trace::result);
+
+        Trace::new(self.to_string(), result)
+            .with_matches(matches)
+            .with_children(children)
+    }
+}
+
+impl Display for SubfieldsMatcher {
+    /// Formats the matcher in dot notation (`003@.0 == 'abc'`), if
+    /// possible, and in curly bracket notation otherwise. In dot
+    /// notation the quantifier of the field applies to the subfield
+    /// matcher too, so the dot notation is used only if both
+    /// quantifiers are equal.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.quantifier == Quantifier::All {
+            write!(f, "{} ", self.quantifier)?;
+        }
+
+        write!(f, "{}{}", self.tag_matcher, self.occurrence_matcher)?;
+
+        match self.subfield_matcher {
+            SubfieldMatcher::Singleton(ref m)
+                if !m.is_function_relation()
+                    && m.quantifier() == self.quantifier =>
+            {
+                write!(f, ".{m:#}")
+            }
+            ref m => write!(f, "{{{m}}}"),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for SubfieldsMatcher {
+    fn serialize<S: Serializer>(
+        &self,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut map = s.serialize_map(Some(5))?;
+        map.serialize_entry("type", "subfields")?;
+        map.serialize_entry("quantifier", &self.quantifier)?;
+        map.serialize_entry("tag", &self.tag_matcher)?;
+        map.serialize_entry("occurrence", &self.occurrence_matcher)?;
+        map.serialize_entry("subfields", &self.subfield_matcher)?;
+        map.end()
+    }
+}
+
+/// A reference to the values of one or more subfields of a field (e.g.
+/// `060R.a`).
+#[derive(Debug, PartialEq, Eq)]
+struct FieldReference {
+    tag_matcher: TagMatcher,
+    occurrence_matcher: OccurrenceMatcher,
+    codes: Vec<char>,
+}
+
+/// Parse a field reference.
+fn parse_field_reference(i: &[u8]) -> ParseResult<FieldReference> {
+    map(
+        tuple((
+            parse_tag_matcher,
+            parse_occurrence_matcher,
+            preceded(char('.'), parse_subfield_codes),
+        )),
+        |(t, o, codes)| FieldReference {
+            tag_matcher: t,
+            occurrence_matcher: o,
+            codes,
+        },
+    )(i)
+}
+
+impl FieldReference {
+    /// Returns `true` if the field has a matching tag and occurrence.
+    fn is_referenced<T: AsRef<[u8]>>(&self, field: &Field<T>) -> bool {
+        self.tag_matcher == field.tag()
+            && self.occurrence_matcher == field.occurrence()
+    }
+
+    /// Returns `true` if both references select the same fields.
+    fn same_fields(&self, other: &Self) -> bool {
+        self.tag_matcher == other.tag_matcher
+            && self.occurrence_matcher == other.occurrence_matcher
+    }
+
+    /// Returns the values of the referenced subfields of a field.
+    fn field_values<'a, T: AsRef<[u8]> + 'a>(
+        &self,
+        field: &'a Field<T>,
+    ) -> Vec<&'a [u8]> {
+        field
+            .subfields()
+            .iter()
+            .filter(|subfield| self.codes.contains(&subfield.code()))
+            .map(|subfield| subfield.value().as_ref())
+            .collect()
+    }
+
+    /// Returns the values of all referenced subfields.
+    fn values<'a, T: AsRef<[u8]> + 'a>(
+        &self,
+        fields: impl IntoIterator<Item = &'a Field<T>>,
+    ) -> Vec<&'a [u8]> {
+        fields
+            .into_iter()
+            .filter(|field| self.is_referenced(field))
+            .flat_map(|field| self.field_values(field))
+            .collect()
+    }
+}
+
+impl Display for FieldReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}.{}",
+            self.tag_matcher,
+            self.occurrence_matcher,
+            SubfieldCodes(&self.codes)
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for FieldReference {
+    fn serialize<S: Serializer>(
+        &self,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut map = s.serialize_map(Some(3))?;
+        map.serialize_entry("tag", &self.tag_matcher)?;
+        map.serialize_entry("occurrence", &self.occurrence_matcher)?;
+        map.serialize_entry("codes", &self.codes)?;
+        map.end()
+    }
+}
+
+/// The values of the left-hand and right-hand side of a reference
+/// matcher, which are compared with each other.
+type ValuePair<'a> = (Vec<&'a [u8]>, Vec<&'a [u8]>);
+
+/// A field matcher that compares subfield values against the values of
+/// other subfields of the record (e.g. `060R.b >= 060R.a`).
+///
+/// The matcher is satisfied if at least one value of the left-hand
+/// side is related to at least one value (`ANY`, the default) or to
+/// all values (`ALL`) of the right-hand side. The quantifier applies
+/// to the right-hand side only; the left-hand side is always
+/// quantified by `ANY`. If both sides reference the same fields (i.e.
+/// the tag and occurrence matchers are equal, as in `060R.b >=
+/// 060R.a`), the values are only compared within the same field. The
+/// ordering operators compare both values as integers, if possible,
+/// and as strings otherwise. If one of the references doesn't yield
+/// any value, the matcher isn't satisfied.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ReferenceMatcher {
+    lhs: FieldReference,
+    op: RelationalOp,
+    quantifier: Quantifier,
+    rhs: FieldReference,
+}
+
+/// Parse a reference matcher expression.
+fn parse_reference_matcher(i: &[u8]) -> ParseResult<ReferenceMatcher> {
+    map(
+        tuple((
+            ws(parse_field_reference),
+            ws(parse_relational_op),
+            map(opt(ws(parse_quantifier)), Option::unwrap_or_default),
+            ws(parse_field_reference),
+        )),
+        |(lhs, op, quantifier, rhs)| ReferenceMatcher {
+            lhs,
+            op,
+            quantifier,
+            rhs,
+        },
+    )(i)
+}
+
+impl ReferenceMatcher {
+    /// Create a new reference matcher from a string slice.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use pica_matcher::field_matcher::ReferenceMatcher;
+    /// use pica_record::FieldRef;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> anyhow::Result<()> {
+    ///     let matcher = ReferenceMatcher::new("003@.0 == 007G.0")?;
+    ///
+    ///     assert!(matcher.is_match(
+    ///         vec![
+    ///             &FieldRef::new("003@", None, vec![('0', "123")]),
+    ///             &FieldRef::new("007G", None, vec![('0', "123")]),
+    ///         ],
+    ///         &Default::default()
+    ///     ));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn new(data: &str) -> Result<Self, ParseMatcherError> {
+        all_consuming(parse_reference_matcher)(data.as_bytes())
+            .finish()
+            .map_err(|e| {
+                ParseMatcherError::InvalidFieldMatcher(
+                    SyntaxError::new(data, e),
+                )
+            })
+            .map(|(_, matcher)| matcher)
+    }
+
+    /// Returns `true` if the values of the left-hand side are related
+    /// to the values of the right-hand side.
+    pub fn is_match<'a, T: AsRef<[u8]> + 'a>(
+        &self,
+        fields: impl IntoIterator<Item = &'a Field<T>> + Clone,
+        options: &MatcherOptions,
+    ) -> bool {
+        self.groups(fields).iter().any(|(lhs, rhs)| {
+            !rhs.is_empty()
+                && lhs.iter().any(|x| self.is_related(x, rhs, options))
         })
     }
+
+    /// Returns the pairs of left-hand and right-hand side values, which
+    /// are compared with each other. If both sides reference the same
+    /// fields, there is one pair per field; otherwise the values of
+    /// all fields form a single pair.
+    fn groups<'a, T: AsRef<[u8]> + 'a>(
+        &self,
+        fields: impl IntoIterator<Item = &'a Field<T>> + Clone,
+    ) -> Vec<ValuePair<'a>> {
+        if self.lhs.same_fields(&self.rhs) {
+            fields
+                .into_iter()
+                .filter(|field| self.lhs.is_referenced(field))
+                .map(|field| {
+                    (
+                        self.lhs.field_values(field),
+                        self.rhs.field_values(field),
+                    )
+                })
+                .collect()
+        } else {
+            vec![(
+                self.lhs.values(fields.clone()),
+                self.rhs.values(fields),
+            )]
+        }
+    }
+
+    /// Returns `true` if the value is related to any (or all) values of
+    /// the right-hand side.
+    fn is_related(
+        &self,
+        value: &[u8],
+        rhs: &[&[u8]],
+        options: &MatcherOptions,
+    ) -> bool {
+        let related = |y: &&[u8]| relate(&self.op, value, y, options);
+        match self.quantifier {
+            Quantifier::Any => rhs.iter().any(related),
+            Quantifier::All => rhs.iter().all(related),
+        }
+    }
+
+    /// Returns the evaluation trace of the matcher. The matches of the
+    /// trace are the related values of the left-hand side.
+    fn trace<'a, T: AsRef<[u8]> + 'a>(
+        &self,
+        fields: impl IntoIterator<Item = &'a Field<T>> + Clone,
+        options: &MatcherOptions,
+    ) -> Trace {
+        let matches: Vec<String> = self
+            .groups(fields)
+            .iter()
+            .filter(|(_, rhs)| !rhs.is_empty())
+            .flat_map(|(lhs, rhs)| {
+                lhs.iter().filter(|x| self.is_related(x, rhs, options))
+            })
+            .map(|x| x.to_str_lossy().to_string())
+            .collect();
+
+        Trace::new(self.to_string(), !matches.is_empty())
+            .with_matches(matches)
+    }
+}
+
+impl Display for ReferenceMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} ", self.lhs, self.op)?;
+        if self.quantifier != Quantifier::Any {
+            write!(f, "{} ", self.quantifier)?;
+        }
+
+        write!(f, "{}", self.rhs)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for ReferenceMatcher {
+    fn serialize<S: Serializer>(
+        &self,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut map = s.serialize_map(Some(5))?;
+        map.serialize_entry("type", "reference")?;
+        map.serialize_entry("lhs", &self.lhs)?;
+        map.serialize_entry("op", &self.op)?;
+        map.serialize_entry("quantifier", &self.quantifier)?;
+        map.serialize_entry("rhs", &self.rhs)?;
+        map.end()
+    }
 }
 
 /// A field matcher that checks for the singleton matcher.
 #[derive(Debug, PartialEq, Eq)]
 pub enum SingletonMatcher {
     Exists(ExistsMatcher),
+    Reference(ReferenceMatcher),
     Subfields(SubfieldsMatcher),
 }
 
 /// Parse a singleton matcher expression.
-fn parse_singleton_matcher(i: &[u8]) -> ParseResult<SingletonMatcher> {
+fn parse_singleton_matcher<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, SingletonMatcher> {
     alt((
         map(parse_exists_matcher, SingletonMatcher::Exists),
-        map(parse_subfields_matcher, SingletonMatcher::Subfields),
+        map(parse_reference_matcher, SingletonMatcher::Reference),
+        map(
+            |i| parse_subfields_matcher(i, defs),
+            SingletonMatcher::Subfields,
+        ),
     ))(i)
 }
 
 /// Parse a singleton matcher expression (curly bracket notation).
 #[inline]
-fn parse_singleton_matcher_bracket(
-    i: &[u8],
-) -> ParseResult<SingletonMatcher> {
-    map(parse_subfields_matcher_bracket, SingletonMatcher::Subfields)(i)
+fn parse_singleton_matcher_bracket<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, SingletonMatcher> {
+    map(
+        |i| parse_subfields_matcher_bracket(i, defs),
+        SingletonMatcher::Subfields,
+    )(i)
 }
 
 impl SingletonMatcher {
@@ -250,12 +755,18 @@ impl SingletonMatcher {
     /// }
     /// ```
     pub fn new(data: &str) -> Result<Self, ParseMatcherError> {
-        all_consuming(parse_singleton_matcher)(data.as_bytes())
-            .finish()
-            .map_err(|_| {
-                ParseMatcherError::InvalidFieldMatcher(data.into())
-            })
-            .map(|(_, matcher)| matcher)
+        all_consuming(|i| {
+            parse_singleton_matcher(i, &Definitions::default())
+        })(data.as_bytes())
+        .finish()
+        .map_err(|e| {
+            ParseMatcherError::InvalidFieldMatcher(SyntaxError::new(
+                data, e,
+            ))
+        })
+        .and_then(|(_, matcher)| {
+            matcher.with_lists(&ParseContext::default())
+        })
     }
 
     /// Returns `true` if the given field matches against the field
@@ -267,37 +778,753 @@ impl SingletonMatcher {
     ) -> bool {
         match self {
             Self::Exists(m) => m.is_match(fields, options),
+            Self::Reference(m) => m.is_match(fields, options),
             Self::Subfields(m) => m.is_match(fields, options),
         }
     }
+
+    /// Returns the evaluation trace of the matcher.
+    fn trace<'a, T: AsRef<[u8]> + 'a>(
+        &self,
+        fields: impl IntoIterator<Item = &'a Field<T>> + Clone,
+        options: &MatcherOptions,
+    ) -> Trace {
+        match self {
+            Self::Exists(m) => m.trace(fields, options),
+            Self::Reference(m) => m.trace(fields, options),
+            Self::Subfields(m) => m.trace(fields, options),
+        }
+    }
+}
+
+impl Display for SingletonMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Exists(m) => write!(f, "{m}"),
+            Self::Reference(m) => write!(f, "{m}"),
+            Self::Subfields(m) => write!(f, "{m}"),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for SingletonMatcher {
+    fn serialize<S: Serializer>(
+        &self,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Exists(m) => m.serialize(s),
+            Self::Reference(m) => m.serialize(s),
+            Self::Subfields(m) => m.serialize(s),
+        }
+    }
+}
+
+/// The fields with a matching tag, occurrence and (optionally)
+/// subfields, which are counted by a cardinality matcher (e.g.
+/// `#041[AH]/*` or `#044K{9?}`).
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct FieldCount {
+    pub(crate) tag_matcher: TagMatcher,
+    occurrence_matcher: OccurrenceMatcher,
+    subfield_matcher: Option<SubfieldMatcher>,
+}
+
+impl FieldCount {
+    /// Returns `true` if the field is counted.
+    fn is_counted<T: AsRef<[u8]>>(
+        &self,
+        field: &Field<T>,
+        options: &MatcherOptions,
+    ) -> bool {
+        self.tag_matcher == field.tag()
+            && self.occurrence_matcher == field.occurrence()
+            && match self.subfield_matcher {
+                Some(ref matcher) => {
+                    matcher.is_match(field.subfields(), options)
+                }
+                None => true,
+            }
+    }
+
+    /// Returns the number of counted fields.
+    fn count<'a, T: AsRef<[u8]> + 'a>(
+        &self,
+        fields: impl IntoIterator<Item = &'a Field<T>>,
+        options: &MatcherOptions,
+    ) -> usize {
+        fields
+            .into_iter()
+            .filter(|field| self.is_counted(field, options))
+            .count()
+    }
+}
+
+impl Display for FieldCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}{}", self.tag_matcher, self.occurrence_matcher)?;
+        if let Some(ref m) = self.subfield_matcher {
+            write!(f, "{{{m}}}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for FieldCount {
+    /// Serializes the counted fields; `subfields` is `null`, if all
+    /// fields with a matching tag and occurrence are counted.
+    fn serialize<S: Serializer>(
+        &self,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut map = s.serialize_map(Some(3))?;
+        map.serialize_entry("tag", &self.tag_matcher)?;
+        map.serialize_entry("occurrence", &self.occurrence_matcher)?;
+        map.serialize_entry("subfields", &self.subfield_matcher)?;
+        map.end()
+    }
+}
+
+/// Parse the fields counted by a cardinality matcher.
+fn parse_field_count<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, FieldCount> {
+    map(
+        preceded(
+            ws(char('#')),
+            cut(tuple((
+                ws(parse_tag_matcher),
+                ws(parse_occurrence_matcher),
+                opt(preceded(
+                    ws(char('{')),
+                    cut(terminated(
+                        |i| parse_subfield_matcher_with(i, defs),
+                        ws(char('}')),
+                    )),
+                )),
+            ))),
+        ),
+        |(t, o, s)| FieldCount {
+            tag_matcher: t,
+            occurrence_matcher: o,
+            subfield_matcher: s,
+        },
+    )(i)
+}
+
+/// The right-hand side of a cardinality matcher, which is either a
+/// number or the number of other fields (`#041[AH]/* > #044K`).
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Cardinality {
+    Value(usize),
+    Count(Box<FieldCount>),
+}
+
+impl Cardinality {
+    /// Returns the number, which is compared against the counted
+    /// fields.
+    fn value<'a, T: AsRef<[u8]> + 'a>(
+        &self,
+        fields: impl IntoIterator<Item = &'a Field<T>>,
+        options: &MatcherOptions,
+    ) -> usize {
+        match self {
+            Self::Value(value) => *value,
+            Self::Count(count) => count.count(fields, options),
+        }
+    }
+}
+
+impl Display for Cardinality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Value(value) => write!(f, "{value}"),
+            Self::Count(count) => write!(f, "{count}"),
+        }
+    }
+}
+
+/// Parse the right-hand side of a cardinality matcher.
+fn parse_cardinality<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, Cardinality> {
+    alt((
+        map(parse_usize, Cardinality::Value),
+        map(
+            |i| parse_field_count(i, defs),
+            |count| Cardinality::Count(Box::new(count)),
+        ),
+    ))(i)
+}
+
+/// A field matcher that checks the number of occurrences of a field.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CardinalityMatcher {
+    pub(crate) lhs: FieldCount,
+    op: RelationalOp,
+    pub(crate) rhs: Cardinality,
+}
+
+impl CardinalityMatcher {
+    /// Create a new cardinality matcher from a string slice.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use pica_matcher::field_matcher::CardinalityMatcher;
+    /// use pica_record::FieldRef;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> anyhow::Result<()> {
+    ///     let matcher =
+    ///         CardinalityMatcher::new("#003@{0 == '123456789X'} >= 1")?;
+    ///
+    ///     assert!(matcher.is_match(
+    ///         &FieldRef::new("003@", None, vec![('0', "123456789X")]),
+    ///         &Default::default()
+    ///     ));
+    ///
+    ///     let matcher = CardinalityMatcher::new("#00[13]@ > #002@")?;
+    ///     assert!(matcher.is_match(
+    ///         &FieldRef::new("003@", None, vec![('0', "123456789X")]),
+    ///         &Default::default()
+    ///     ));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn new(data: &str) -> Result<Self, ParseMatcherError> {
+        all_consuming(|i| {
+            parse_cardinality_matcher(i, &Definitions::default())
+        })(data.as_bytes())
+        .finish()
+        .map_err(|e| {
+            ParseMatcherError::InvalidFieldMatcher(SyntaxError::new(
+                data, e,
+            ))
+        })
+        .and_then(|(_, matcher)| {
+            matcher.with_lists(&ParseContext::default())
+        })
+    }
+
+    /// Returns `true` if the given field matches against the field
+    /// matcher.
+    pub fn is_match<'a, T: AsRef<[u8]> + 'a>(
+        &self,
+        fields: impl IntoIterator<Item = &'a Field<T>> + Clone,
+        options: &MatcherOptions,
+    ) -> bool {
+        let count = self.lhs.count(fields.clone(), options);
+        compare_usize(&self.op, count, self.rhs.value(fields, options))
+    }
+
+    /// Returns the evaluation trace of the matcher. The matches of the
+    /// trace are the counted fields (of both sides).
+    fn trace<'a, T: AsRef<[u8]> + 'a>(
+        &self,
+        fields: impl IntoIterator<Item = &'a Field<T>> + Clone,
+        options: &MatcherOptions,
+    ) -> Trace {
+        let matches = fields
+            .clone()
+            .into_iter()
+            .filter(|field| {
+                self.lhs.is_counted(field, options)
+                    || match self.rhs {
+                        Cardinality::Count(ref count) => {
+                            count.is_counted(field, options)
+                        }
+                        Cardinality::Value(_) => false,
+                    }
+            })
+            .map(fmt_field)
+            .collect();
+
+        Trace::new(self.to_string(), self.is_match(fields, options))
+            .with_matches(matches)
+    }
+}
+
+impl Display for CardinalityMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.lhs, self.op, self.rhs)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for CardinalityMatcher {
+    /// Serializes the matcher; `subfields` is `null`, if the matcher
+    /// counts all fields with a matching tag and occurrence. A number
+    /// is serialized as `value` and other counted fields as `rhs`.
+    fn serialize<S: Serializer>(
+        &self,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut map = s.serialize_map(Some(6))?;
+        map.serialize_entry("type", "cardinality")?;
+        map.serialize_entry("tag", &self.lhs.tag_matcher)?;
+        map.serialize_entry(
+            "occurrence",
+            &self.lhs.occurrence_matcher,
+        )?;
+        map.serialize_entry("subfields", &self.lhs.subfield_matcher)?;
+        map.serialize_entry("op", &self.op)?;
+        match self.rhs {
+            Cardinality::Value(ref value) => {
+                map.serialize_entry("value", value)?
+            }
+            Cardinality::Count(ref count) => {
+                map.serialize_entry("rhs", count)?
+            }
+        }
+        map.end()
+    }
+}
+
+/// Parse a cardinality matcher expressions.
+fn parse_cardinality_matcher<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, CardinalityMatcher> {
+    map(
+        tuple((
+            |i| parse_field_count(i, defs),
+            cut(ws(parse_relational_op_usize)),
+            cut(|i| parse_cardinality(i, defs)),
+        )),
+        |(lhs, op, rhs)| CardinalityMatcher { lhs, op, rhs },
+    )(i)
+}
+
+/// A pattern of fields with a matching tag and occurrence, which is
+/// used by the arguments of a predicate (e.g. `duplicates(044H)?`).
+#[derive(Debug, PartialEq, Eq)]
+struct FieldPattern {
+    tag_matcher: TagMatcher,
+    occurrence_matcher: OccurrenceMatcher,
+}
+
+/// Parse a field pattern.
+fn parse_field_pattern(i: &[u8]) -> ParseResult<FieldPattern> {
+    map(
+        pair(ws(parse_tag_matcher), ws(parse_occurrence_matcher)),
+        |(t, o)| FieldPattern {
+            tag_matcher: t,
+            occurrence_matcher: o,
+        },
+    )(i)
+}
+
+impl FieldPattern {
+    /// Returns `true` if the field has a matching tag and occurrence.
+    fn is_match<T: AsRef<[u8]>>(&self, field: &Field<T>) -> bool {
+        self.tag_matcher == field.tag()
+            && self.occurrence_matcher == field.occurrence()
+    }
+}
+
+impl Display for FieldPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.tag_matcher, self.occurrence_matcher)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for FieldPattern {
+    fn serialize<S: Serializer>(
+        &self,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut map = s.serialize_map(Some(2))?;
+        map.serialize_entry("tag", &self.tag_matcher)?;
+        map.serialize_entry("occurrence", &self.occurrence_matcher)?;
+        map.end()
+    }
+}
+
+/// A field matcher that checks structural properties of a record,
+/// which don't depend on the values of a particular field (e.g.
+/// `#fields > 500`, `invalid_utf8?` or `duplicates(044H)?`).
+#[derive(Debug, PartialEq, Eq)]
+pub struct PredicateMatcher(Predicate);
+
+/// The predicates of a [PredicateMatcher].
+#[derive(Debug, PartialEq, Eq)]
+enum Predicate {
+    /// Compares the number of fields (`#fields > 500`).
+    Fields { op: RelationalOp, value: usize },
+    /// Compares the length of the record in bytes, including the
+    /// field separators and the trailing newline (`#bytes > 10000`).
+    Bytes { op: RelationalOp, value: usize },
+    /// Checks if a subfield value isn't valid UTF-8 (`invalid_utf8?`).
+    InvalidUtf8,
+    /// Checks if two fields (with a matching tag and occurrence) are
+    /// identical (`duplicates?`, `duplicates(044H)?`).
+    Duplicates(Option<FieldPattern>),
+    /// Checks if all fields of the first pattern precede all fields of
+    /// the second pattern (`before(021A, 028A)?`). Both fields must
+    /// exist.
+    Before(FieldPattern, FieldPattern),
+}
+
+/// Parse a predicate matcher expression.
+fn parse_predicate_matcher(i: &[u8]) -> ParseResult<PredicateMatcher> {
+    map(
+        alt((
+            map(
+                preceded(
+                    pair(ws(char('#')), tag("fields")),
+                    cut(pair(
+                        ws(parse_relational_op_usize),
+                        parse_usize,
+                    )),
+                ),
+                |(op, value)| Predicate::Fields { op, value },
+            ),
+            map(
+                preceded(
+                    pair(ws(char('#')), tag("bytes")),
+                    cut(pair(
+                        ws(parse_relational_op_usize),
+                        parse_usize,
+                    )),
+                ),
+                |(op, value)| Predicate::Bytes { op, value },
+            ),
+            map(terminated(tag("invalid_utf8"), char('?')), |_| {
+                Predicate::InvalidUtf8
+            }),
+            map(
+                delimited(
+                    tag("duplicates"),
+                    opt(delimited(
+                        char('('),
+                        cut(parse_field_pattern),
+                        cut(char(')')),
+                    )),
+                    char('?'),
+                ),
+                Predicate::Duplicates,
+            ),
+            map(
+                delimited(
+                    pair(tag("before"), char('(')),
+                    cut(separated_pair(
+                        parse_field_pattern,
+                        char(','),
+                        parse_field_pattern,
+                    )),
+                    cut(pair(char(')'), char('?'))),
+                ),
+                |(lhs, rhs)| Predicate::Before(lhs, rhs),
+            ),
+        )),
+        PredicateMatcher,
+    )(i)
+}
+
+impl PredicateMatcher {
+    /// Create a new predicate matcher from a string slice.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use pica_matcher::field_matcher::PredicateMatcher;
+    /// use pica_record::FieldRef;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> anyhow::Result<()> {
+    ///     let matcher = PredicateMatcher::new("duplicates(044H)?")?;
+    ///
+    ///     assert!(matcher.is_match(
+    ///         vec![
+    ///             &FieldRef::new("044H", None, vec![('a', "abc")]),
+    ///             &FieldRef::new("044H", None, vec![('a', "abc")]),
+    ///         ],
+    ///         &Default::default()
+    ///     ));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn new(data: &str) -> Result<Self, ParseMatcherError> {
+        all_consuming(parse_predicate_matcher)(data.as_bytes())
+            .finish()
+            .map_err(|e| {
+                ParseMatcherError::InvalidFieldMatcher(
+                    SyntaxError::new(data, e),
+                )
+            })
+            .map(|(_, matcher)| matcher)
+    }
+
+    /// Returns `true` if the given fields satisfy the predicate.
+    pub fn is_match<'a, T: AsRef<[u8]> + 'a>(
+        &self,
+        fields: impl IntoIterator<Item = &'a Field<T>> + Clone,
+        _options: &MatcherOptions,
+    ) -> bool {
+        match &self.0 {
+            Predicate::Fields { op, value } => {
+                compare_usize(op, fields.into_iter().count(), *value)
+            }
+            Predicate::Bytes { op, value } => {
+                let len =
+                    fields.into_iter().map(encoded_len).sum::<usize>();
+                compare_usize(op, len + 1, *value)
+            }
+            Predicate::InvalidUtf8 => {
+                fields.into_iter().any(is_invalid_utf8)
+            }
+            Predicate::Duplicates(pattern) => {
+                duplicates(pattern.as_ref(), fields).next().is_some()
+            }
+            Predicate::Before(lhs, rhs) => {
+                let mut lhs_seen = false;
+                let mut rhs_seen = false;
+
+                for field in fields {
+                    if lhs.is_match(field) {
+                        if rhs_seen {
+                            return false;
+                        }
+                        lhs_seen = true;
+                    }
+
+                    if rhs.is_match(field) {
+                        rhs_seen = true;
+                    }
+                }
+
+                lhs_seen && rhs_seen
+            }
+        }
+    }
+
+    /// Returns the evaluation trace of the matcher. The matches of the
+    /// trace are the fields with invalid UTF-8 or the duplicate
+    /// fields.
+    fn trace<'a, T: AsRef<[u8]> + 'a>(
+        &self,
+        fields: impl IntoIterator<Item = &'a Field<T>> + Clone,
+        options: &MatcherOptions,
+    ) -> Trace {
+        let matches = match &self.0 {
+            Predicate::InvalidUtf8 => fields
+                .clone()
+                .into_iter()
+                .filter(|field| is_invalid_utf8(field))
+                .map(fmt_field)
+                .collect(),
+            Predicate::Duplicates(pattern) => {
+                duplicates(pattern.as_ref(), fields.clone())
+                    .map(fmt_field)
+                    .collect()
+            }
+            _ => vec![],
+        };
+
+        Trace::new(self.to_string(), self.is_match(fields, options))
+            .with_matches(matches)
+    }
+}
+
+/// Compares two numbers with a relational operator.
+fn compare_usize(op: &RelationalOp, lhs: usize, rhs: usize) -> bool {
+    match op {
+        RelationalOp::Eq => lhs == rhs,
+        RelationalOp::Ne => lhs != rhs,
+        RelationalOp::Ge => lhs >= rhs,
+        RelationalOp::Gt => lhs > rhs,
+        RelationalOp::Le => lhs <= rhs,
+        RelationalOp::Lt => lhs < rhs,
+        _ => unreachable!(),
+    }
+}
+
+/// Returns `true` if a subfield value of the field isn't valid UTF-8.
+fn is_invalid_utf8<T: AsRef<[u8]>>(field: &Field<T>) -> bool {
+    field.subfields().iter().any(|subfield| {
+        std::str::from_utf8(subfield.value().as_ref()).is_err()
+    })
+}
+
+/// Returns the fields (with a matching tag and occurrence), which are
+/// identical to a preceding field.
+fn duplicates<'a: 'p, 'p, T: AsRef<[u8]> + 'a>(
+    pattern: Option<&'p FieldPattern>,
+    fields: impl IntoIterator<Item = &'a Field<T>> + 'p,
+) -> impl Iterator<Item = &'a Field<T>> + 'p {
+    let mut seen = HashSet::new();
+    fields
+        .into_iter()
+        .filter(move |field| match pattern {
+            Some(pattern) => pattern.is_match(field),
+            None => true,
+        })
+        .filter(move |field| !seen.insert(encode_field(field)))
+}
+
+/// Returns the length of the field in bytes (`012A/01 \x1fa...\x1e`).
+fn encoded_len<T: AsRef<[u8]>>(field: &Field<T>) -> usize {
+    let occurrence = field
+        .occurrence()
+        .map_or(0, |occurrence| occurrence.to_string().len() + 1);
+
+    field.tag().as_ref().len()
+        + occurrence
+        + field
+            .subfields()
+            .iter()
+            .map(|subfield| subfield.value().as_ref().len() + 2)
+            .sum::<usize>()
+        + 2
+}
+
+/// Returns the bytes of the field, as they are written to a file.
+fn encode_field<T: AsRef<[u8]>>(field: &Field<T>) -> Vec<u8> {
+    let mut data = fmt_field(field).into_bytes();
+    data.push(b' ');
+
+    for subfield in field.subfields() {
+        data.push(b'\x1f');
+        data.push(subfield.code() as u8);
+        data.extend(subfield.value().as_ref());
+    }
+
+    data
+}
+
+impl Display for PredicateMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Predicate::Fields { op, value } => {
+                write!(f, "#fields {op} {value}")
+            }
+            Predicate::Bytes { op, value } => {
+                write!(f, "#bytes {op} {value}")
+            }
+            Predicate::InvalidUtf8 => write!(f, "invalid_utf8?"),
+            Predicate::Duplicates(None) => write!(f, "duplicates?"),
+            Predicate::Duplicates(Some(pattern)) => {
+                write!(f, "duplicates({pattern})?")
+            }
+            Predicate::Before(lhs, rhs) => {
+                write!(f, "before({lhs}, {rhs})?")
+            }
+        }
+    }
 }
 
-/// A field matcher that checks the number of occurrences of a field.
+#[cfg(feature = "serde")]
+impl Serialize for PredicateMatcher {
+    fn serialize<S: Serializer>(
+        &self,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut map = s.serialize_map(None)?;
+        match &self.0 {
+            Predicate::Fields { op, value } => {
+                map.serialize_entry("type", "fields")?;
+                map.serialize_entry("op", op)?;
+                map.serialize_entry("value", value)?;
+            }
+            Predicate::Bytes { op, value } => {
+                map.serialize_entry("type", "bytes")?;
+                map.serialize_entry("op", op)?;
+                map.serialize_entry("value", value)?;
+            }
+            Predicate::InvalidUtf8 => {
+                map.serialize_entry("type", "invalid_utf8")?;
+            }
+            Predicate::Duplicates(pattern) => {
+                map.serialize_entry("type", "duplicates")?;
+                map.serialize_entry("field", pattern)?;
+            }
+            Predicate::Before(lhs, rhs) => {
+                map.serialize_entry("type", "before")?;
+                map.serialize_entry("lhs", lhs)?;
+                map.serialize_entry("rhs", rhs)?;
+            }
+        }
+        map.end()
+    }
+}
+
+/// A field matcher that evaluates a field matcher within the holding
+/// blocks of a record (e.g. `@copy{209A/*.a == 'X' && 209B/*?}`).
+///
+/// With the quantifier `ANY` (the default) the matcher is satisfied if
+/// at least one local block (`@local`) or copy (`@copy`) satisfies the
+/// inner matcher; with `ALL` every block must satisfy it. If the
+/// record doesn't have any holdings, the matcher isn't satisfied.
 #[derive(Debug, PartialEq, Eq)]
-pub struct CardinalityMatcher {
-    tag_matcher: TagMatcher,
-    occurrence_matcher: OccurrenceMatcher,
-    subfield_matcher: Option<SubfieldMatcher>,
-    op: RelationalOp,
-    value: usize,
+pub struct ScopeMatcher {
+    quantifier: Quantifier,
+    scope: Scope,
+    matcher: Box<FieldMatcher>,
 }
 
-impl CardinalityMatcher {
-    /// Create a new cardinality matcher from a string slice.
+/// Parse a scope (`local` or `copy`).
+fn parse_scope(i: &[u8]) -> ParseResult<Scope> {
+    alt((
+        map(tag("local"), |_| Scope::Local),
+        map(tag("copy"), |_| Scope::Copy),
+    ))(i)
+}
+
+/// Parse a scope matcher expression.
+fn parse_scope_matcher<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, ScopeMatcher> {
+    map(
+        tuple((
+            map(opt(ws(parse_quantifier)), Option::unwrap_or_default),
+            preceded(char('@'), cut(parse_scope)),
+            preceded(
+                cut(ws(char('{'))),
+                cut(terminated(
+                    |i| parse_field_matcher_with(i, defs),
+                    ws(char('}')),
+                )),
+            ),
+        )),
+        |(quantifier, scope, matcher)| ScopeMatcher {
+            quantifier,
+            scope,
+            matcher: Box::new(matcher),
+        },
+    )(i)
+}
+
+impl ScopeMatcher {
+    /// Create a new scope matcher from a string slice.
     ///
     /// # Example
     ///
     /// ```rust
-    /// use pica_matcher::field_matcher::CardinalityMatcher;
+    /// use pica_matcher::field_matcher::ScopeMatcher;
     /// use pica_record::FieldRef;
     ///
     /// # fn main() { example().unwrap(); }
     /// fn example() -> anyhow::Result<()> {
-    ///     let matcher =
-    ///         CardinalityMatcher::new("#003@{0 == '123456789X'} >= 1")?;
+    ///     let matcher = ScopeMatcher::new(
+    ///         "@copy{209A/*.a == 'X' && 209B/*.a == 'Y'}",
+    ///     )?;
     ///
-    ///     assert!(matcher.is_match(
-    ///         &FieldRef::new("003@", None, vec![('0', "123456789X")]),
+    ///     assert!(!matcher.is_match(
+    ///         vec![
+    ///             &FieldRef::new("101@", None, vec![('a', "1")]),
+    ///             &FieldRef::new("209A", Some("01"), vec![('a', "X")]),
+    ///             &FieldRef::new("209B", Some("02"), vec![('a', "Y")]),
+    ///         ],
     ///         &Default::default()
     ///     ));
     ///
@@ -305,79 +1532,161 @@ impl CardinalityMatcher {
     /// }
     /// ```
     pub fn new(data: &str) -> Result<Self, ParseMatcherError> {
-        all_consuming(parse_cardinality_matcher)(data.as_bytes())
-            .finish()
-            .map_err(|_| {
-                ParseMatcherError::InvalidFieldMatcher(data.into())
-            })
-            .map(|(_, matcher)| matcher)
+        all_consuming(|i| {
+            parse_scope_matcher(i, &Definitions::default())
+        })(data.as_bytes())
+        .finish()
+        .map_err(|e| {
+            ParseMatcherError::InvalidFieldMatcher(SyntaxError::new(
+                data, e,
+            ))
+        })
+        .and_then(|(_, matcher)| {
+            matcher.with_lists(&ParseContext::default())
+        })
     }
 
-    /// Returns `true` if the given field matches against the field
-    /// matcher.
+    /// Returns `true` if at least one block (or all blocks, if the
+    /// quantifier is `ALL`) satisfies the inner matcher.
     pub fn is_match<'a, T: AsRef<[u8]> + 'a>(
         &self,
         fields: impl IntoIterator<Item = &'a Field<T>> + Clone,
         options: &MatcherOptions,
     ) -> bool {
-        let count = fields
-            .into_iter()
-            .filter(|field| {
-                self.tag_matcher == field.tag()
-                    && self.occurrence_matcher == field.occurrence()
-            })
-            .filter(|field| {
-                if let Some(ref matcher) = self.subfield_matcher {
-                    matcher.is_match(field.subfields(), options)
-                } else {
-                    true
-                }
+        let blocks = self.scope.blocks(fields, field_key);
+        self.quantifier.eval(&blocks, |block| {
+            self.matcher.is_match(block.iter().copied(), options)
+        })
+    }
+
+    /// Returns the evaluation trace of the matcher. The children of
+    /// the trace are the traces of the inner matcher for each block;
+    /// the matches are the first fields of the matching blocks.
+    fn trace<'a, T: AsRef<[u8]> + 'a>(
+        &self,
+        fields: impl IntoIterator<Item = &'a Field<T>> + Clone,
+        options: &MatcherOptions,
+    ) -> Trace {
+        let blocks = self.scope.blocks(fields, field_key);
+        let children: Vec<Trace> = blocks
+            .iter()
+            .map(|block| {
+                self.matcher.trace(block.iter().copied(), options)
             })
-            .count();
-
-        match self.op {
-            RelationalOp::Eq => count == self.value,
-            RelationalOp::Ne => count != self.value,
-            RelationalOp::Ge => count >= self.value,
-            RelationalOp::Gt => count > self.value,
-            RelationalOp::Le => count <= self.value,
-            RelationalOp::Lt => count < self.value,
-            _ => unreachable!(),
+            .collect();
+
+        let matches: Vec<String> = blocks
+            .iter()
+            .zip(children.iter())
+            .filter(|(_, trace)| trace.result())
+            .map(|(block, _)| fmt_field(block[0]))
+            .collect();
+
+        let result = self.quantifier.eval(&children, Trace::result);
+
+        Trace::new(self.to_string(), result)
+            .with_matches(matches)
+            .with_children(children)
+    }
+}
+
+impl Display for ScopeMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.quantifier == Quantifier::All {
+            write!(f, "{} ", self.quantifier)?;
         }
+
+        write!(f, "@{}{{{}}}", self.scope, self.matcher)
     }
 }
 
-/// Parse a cardinality matcher expressions.
-fn parse_cardinality_matcher(
-    i: &[u8],
-) -> ParseResult<CardinalityMatcher> {
-    map(
-        preceded(
-            ws(char('#')),
-            cut(tuple((
-                ws(parse_tag_matcher),
-                ws(parse_occurrence_matcher),
-                opt(preceded(
-                    ws(char('{')),
-                    cut(terminated(
-                        parse_subfield_matcher,
-                        ws(char('}')),
-                    )),
-                )),
-                ws(parse_relational_op_usize),
-                map_res(digit1, |s| {
-                    std::str::from_utf8(s).unwrap().parse::<usize>()
-                }),
-            ))),
-        ),
-        |(t, o, s, op, value)| CardinalityMatcher {
-            tag_matcher: t,
-            occurrence_matcher: o,
-            subfield_matcher: s,
-            op,
-            value,
-        },
-    )(i)
+#[cfg(feature = "serde")]
+impl Serialize for ScopeMatcher {
+    fn serialize<S: Serializer>(
+        &self,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut map = s.serialize_map(Some(4))?;
+        map.serialize_entry("type", "scope")?;
+        map.serialize_entry("quantifier", &self.quantifier)?;
+        map.serialize_entry("scope", &self.scope)?;
+        map.serialize_entry("matcher", &self.matcher)?;
+        map.end()
+    }
+}
+
+impl LoadLists for SubfieldsMatcher {
+    fn load_lists(
+        &mut self,
+        lists: &mut ListCache,
+    ) -> Result<(), ParseMatcherError> {
+        self.subfield_matcher.load_lists(lists)
+    }
+}
+
+impl LoadLists for SingletonMatcher {
+    fn load_lists(
+        &mut self,
+        lists: &mut ListCache,
+    ) -> Result<(), ParseMatcherError> {
+        match self {
+            Self::Subfields(m) => m.load_lists(lists),
+            Self::Exists(_) | Self::Reference(_) => Ok(()),
+        }
+    }
+}
+
+impl LoadLists for FieldCount {
+    fn load_lists(
+        &mut self,
+        lists: &mut ListCache,
+    ) -> Result<(), ParseMatcherError> {
+        match self.subfield_matcher {
+            Some(ref mut m) => m.load_lists(lists),
+            None => Ok(()),
+        }
+    }
+}
+
+impl LoadLists for CardinalityMatcher {
+    fn load_lists(
+        &mut self,
+        lists: &mut ListCache,
+    ) -> Result<(), ParseMatcherError> {
+        self.lhs.load_lists(lists)?;
+        match self.rhs {
+            Cardinality::Count(ref mut count) => count.load_lists(lists),
+            Cardinality::Value(_) => Ok(()),
+        }
+    }
+}
+
+impl LoadLists for ScopeMatcher {
+    fn load_lists(
+        &mut self,
+        lists: &mut ListCache,
+    ) -> Result<(), ParseMatcherError> {
+        self.matcher.load_lists(lists)
+    }
+}
+
+impl LoadLists for FieldMatcher {
+    fn load_lists(
+        &mut self,
+        lists: &mut ListCache,
+    ) -> Result<(), ParseMatcherError> {
+        match self {
+            Self::Singleton(m) => m.load_lists(lists),
+            Self::Cardinality(m) => m.load_lists(lists),
+            Self::Scope(m) => m.load_lists(lists),
+            Self::Predicate(_) => Ok(()),
+            Self::Group(m) | Self::Not(m) => m.load_lists(lists),
+            Self::Composite { lhs, rhs, .. } => {
+                lhs.load_lists(lists)?;
+                rhs.load_lists(lists)
+            }
+        }
+    }
 }
 
 /// A field matcher that allows grouping, negation and connecting of
@@ -386,6 +1695,8 @@ fn parse_cardinality_matcher(
 pub enum FieldMatcher {
     Singleton(SingletonMatcher),
     Cardinality(CardinalityMatcher),
+    Predicate(PredicateMatcher),
+    Scope(ScopeMatcher),
     Group(Box<FieldMatcher>),
     Not(Box<FieldMatcher>),
     Composite {
@@ -419,10 +1730,14 @@ impl FieldMatcher {
     pub fn new(data: &str) -> Result<Self, ParseMatcherError> {
         all_consuming(parse_field_matcher)(data.as_bytes())
             .finish()
-            .map_err(|_| {
-                ParseMatcherError::InvalidFieldMatcher(data.into())
+            .map_err(|e| {
+                ParseMatcherError::InvalidFieldMatcher(
+                    SyntaxError::new(data, e),
+                )
+            })
+            .and_then(|(_, matcher)| {
+                matcher.with_lists(&ParseContext::default())
             })
-            .map(|(_, matcher)| matcher)
     }
 
     /// Returns `true` if the given field matches against the field
@@ -437,14 +1752,168 @@ impl FieldMatcher {
             Self::Group(m) => m.is_match(fields, options),
             Self::Not(m) => !m.is_match(fields, options),
             Self::Cardinality(m) => m.is_match(fields, options),
+            Self::Predicate(m) => m.is_match(fields, options),
+            Self::Scope(m) => m.is_match(fields, options),
+            Self::Composite { lhs, op, rhs } => op
+                .eval(lhs.is_match(fields.clone(), options), || {
+                    rhs.is_match(fields, options)
+                }),
+        }
+    }
+
+    /// Evaluates the matcher against the given field(s) and returns the
+    /// trace of the evaluation.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use pica_matcher::FieldMatcher;
+    /// use pica_record::FieldRef;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> anyhow::Result<()> {
+    ///     let matcher = FieldMatcher::new("003@? || 002@.0 == 'Tp1'")?;
+    ///     let trace = matcher.trace(
+    ///         &FieldRef::new("003@", None, vec![('0', "123456789X")]),
+    ///         &Default::default(),
+    ///     );
+    ///
+    ///     assert!(trace.result());
+    ///     assert_eq!(trace.expr(), "||");
+    ///     assert_eq!(trace.children()[0].matches(), ["003@"]);
+    ///     assert!(!trace.children()[1].result());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn trace<'a, T: AsRef<[u8]> + 'a>(
+        &self,
+        fields: impl IntoIterator<Item = &'a Field<T>> + Clone,
+        options: &MatcherOptions,
+    ) -> Trace {
+        match self {
+            Self::Singleton(m) => m.trace(fields, options),
+            Self::Cardinality(m) => m.trace(fields, options),
+            Self::Predicate(m) => m.trace(fields, options),
+            Self::Scope(m) => m.trace(fields, options),
+            Self::Group(m) => m.trace(fields, options),
+            Self::Not(m) => {
+                let trace = m.trace(fields, options);
+                Trace::connective("!", !trace.result(), [trace])
+            }
             Self::Composite { lhs, op, rhs } => {
-                if *op == BooleanOp::And {
-                    lhs.is_match(fields.clone(), options)
-                        && rhs.is_match(fields, options)
-                } else {
-                    lhs.is_match(fields.clone(), options)
-                        || rhs.is_match(fields, options)
-                }
+                let lhs = lhs.trace(fields.clone(), options);
+                let rhs = rhs.trace(fields, options);
+                let result = op.eval(lhs.result(), || rhs.result());
+
+                Trace::connective(op.to_string(), result, [lhs, rhs])
+            }
+        }
+    }
+
+    /// Returns the matcher without enclosing groups.
+    fn ungrouped(&self) -> &Self {
+        match self {
+            Self::Group(m) => m.ungrouped(),
+            m => m,
+        }
+    }
+
+    /// Returns the operands of a chain of the connective `op`. Chains
+    /// of implications aren't flattened, because `=>` isn't
+    /// associative.
+    #[cfg(feature = "serde")]
+    fn operands(&self, op: &BooleanOp) -> Vec<&Self> {
+        match self.ungrouped() {
+            Self::Composite { lhs, op: o, rhs }
+                if o == op && *op == BooleanOp::Implies =>
+            {
+                vec![lhs, rhs]
+            }
+            Self::Composite { lhs, op: o, rhs } if o == op => {
+                let mut operands = lhs.operands(op);
+                operands.extend(rhs.operands(op));
+                operands
+            }
+            m => vec![m],
+        }
+    }
+}
+
+impl Display for FieldMatcher {
+    /// Formats the matcher in a canonical form. Redundant parentheses
+    /// are omitted; a composite operand is enclosed in parentheses
+    /// only if its operator binds weaker than the enclosing one (or
+    /// if it's the left operand of an implication).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Singleton(m) => write!(f, "{m}"),
+            Self::Cardinality(m) => write!(f, "{m}"),
+            Self::Predicate(m) => write!(f, "{m}"),
+            Self::Scope(m) => write!(f, "{m}"),
+            Self::Group(m) => write!(f, "{m}"),
+            Self::Not(m) => match m.ungrouped() {
+                m @ (Self::Singleton(SingletonMatcher::Exists(_))
+                | Self::Scope(_)
+                | Self::Predicate(PredicateMatcher(
+                    Predicate::InvalidUtf8
+                    | Predicate::Duplicates(_)
+                    | Predicate::Before(..),
+                ))
+                | Self::Not(_)) => write!(f, "!{m}"),
+                m => write!(f, "!({m})"),
+            },
+            Self::Composite { lhs, op, rhs } => {
+                let fmt_operand = |f: &mut fmt::Formatter<'_>,
+                                   m: &Self,
+                                   left: bool|
+                 -> fmt::Result {
+                    match m.ungrouped() {
+                        m @ Self::Composite { op: inner, .. }
+                            if inner.precedence() < op.precedence()
+                                || (left
+                                    && *inner
+                                        == BooleanOp::Implies) =>
+                        {
+                            write!(f, "({m})")
+                        }
+                        m => write!(f, "{m}"),
+                    }
+                };
+
+                fmt_operand(f, lhs, true)?;
+                write!(f, " {op} ")?;
+                fmt_operand(f, rhs, false)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for FieldMatcher {
+    /// Serializes the abstract syntax tree of the matcher. Groups are
+    /// omitted and chains of the same connective are flattened (e.g.
+    /// `{"type": "and", "operands": [...]}`).
+    fn serialize<S: Serializer>(
+        &self,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Singleton(m) => m.serialize(s),
+            Self::Cardinality(m) => m.serialize(s),
+            Self::Predicate(m) => m.serialize(s),
+            Self::Scope(m) => m.serialize(s),
+            Self::Group(m) => m.serialize(s),
+            Self::Not(m) => {
+                let mut map = s.serialize_map(Some(2))?;
+                map.serialize_entry("type", "not")?;
+                map.serialize_entry("operand", m)?;
+                map.end()
+            }
+            Self::Composite { op, .. } => {
+                let mut map = s.serialize_map(Some(2))?;
+                map.serialize_entry("type", op.name())?;
+                map.serialize_entry("operands", &self.operands(op))?;
+                map.end()
             }
         }
     }
@@ -474,6 +1943,18 @@ impl BitOr for FieldMatcher {
     }
 }
 
+impl BitXor for FieldMatcher {
+    type Output = Self;
+
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        Self::Composite {
+            lhs: Box::new(self),
+            op: BooleanOp::Xor,
+            rhs: Box::new(rhs),
+        }
+    }
+}
+
 impl Not for FieldMatcher {
     type Output = Self;
 
@@ -484,18 +1965,26 @@ impl Not for FieldMatcher {
 
 /// Parse field matcher singleton expression.
 #[inline]
-fn parse_field_matcher_singleton(
-    i: &[u8],
-) -> ParseResult<FieldMatcher> {
-    map(parse_singleton_matcher, FieldMatcher::Singleton)(i)
+fn parse_field_matcher_singleton<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, FieldMatcher> {
+    map(
+        |i| parse_singleton_matcher(i, defs),
+        FieldMatcher::Singleton,
+    )(i)
 }
 
 /// Parse field matcher expression (curly bracket notation).
 #[inline]
-fn parse_field_matcher_singleton_bracket(
-    i: &[u8],
-) -> ParseResult<FieldMatcher> {
-    map(parse_singleton_matcher_bracket, FieldMatcher::Singleton)(i)
+fn parse_field_matcher_singleton_bracket<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, FieldMatcher> {
+    map(
+        |i| parse_singleton_matcher_bracket(i, defs),
+        FieldMatcher::Singleton,
+    )(i)
 }
 
 /// Parse field matcher exists expression.
@@ -517,6 +2006,7 @@ fn parse_field_matcher_exists(i: &[u8]) -> ParseResult<FieldMatcher> {
             |(t, o, s)| {
                 FieldMatcher::Singleton(SingletonMatcher::Subfields(
                     SubfieldsMatcher {
+                        quantifier: Quantifier::Any,
                         tag_matcher: t,
                         occurrence_matcher: o,
                         subfield_matcher: SubfieldMatcher::Singleton(
@@ -533,24 +2023,50 @@ fn parse_field_matcher_exists(i: &[u8]) -> ParseResult<FieldMatcher> {
 
 /// Parse field matcher cardinality expression.
 #[inline]
-fn parse_field_matcher_cardinality(
+fn parse_field_matcher_cardinality<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, FieldMatcher> {
+    map(
+        |i| parse_cardinality_matcher(i, defs),
+        FieldMatcher::Cardinality,
+    )(i)
+}
+
+/// Parse field matcher scope expression.
+#[inline]
+fn parse_field_matcher_scope<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, FieldMatcher> {
+    map(|i| parse_scope_matcher(i, defs), FieldMatcher::Scope)(i)
+}
+
+/// Parse field matcher predicate expression.
+#[inline]
+fn parse_field_matcher_predicate(
     i: &[u8],
 ) -> ParseResult<FieldMatcher> {
-    map(parse_cardinality_matcher, FieldMatcher::Cardinality)(i)
+    map(parse_predicate_matcher, FieldMatcher::Predicate)(i)
 }
 
 /// Parse a field matcher group expression.
-fn parse_field_matcher_group(i: &[u8]) -> ParseResult<FieldMatcher> {
+fn parse_field_matcher_group<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, FieldMatcher> {
     map(
         preceded(
             ws(char('(')),
             cut(terminated(
                 alt((
-                    parse_field_matcher_composite,
-                    parse_field_matcher_singleton,
-                    parse_field_matcher_not,
-                    parse_field_matcher_cardinality,
-                    parse_field_matcher_group,
+                    |i| parse_field_matcher_composite(i, defs),
+                    |i| parse_field_matcher_singleton(i, defs),
+                    |i| parse_field_matcher_not(i, defs),
+                    parse_field_matcher_predicate,
+                    |i| parse_field_matcher_scope(i, defs),
+                    |i| parse_field_matcher_cardinality(i, defs),
+                    |i| parse_field_matcher_group(i, defs),
                 )),
                 ws(char(')')),
             )),
@@ -559,41 +2075,74 @@ fn parse_field_matcher_group(i: &[u8]) -> ParseResult<FieldMatcher> {
     )(i)
 }
 
+/// Parse a reference to a named filter (`$name` or `%name`), which is
+/// a field matcher expression.
+fn parse_field_matcher_named<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, FieldMatcher> {
+    map(
+        |i| {
+            parse_reference(
+                i,
+                defs,
+                parse_field_matcher_with,
+                "field matcher",
+            )
+        },
+        |matcher| FieldMatcher::Group(Box::new(matcher)),
+    )(i)
+}
+
 /// Parse a field matcher not expression.
-fn parse_field_matcher_not(i: &[u8]) -> ParseResult<FieldMatcher> {
+fn parse_field_matcher_not<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, FieldMatcher> {
     map(
         preceded(
             ws(char('!')),
             cut(alt((
-                parse_field_matcher_group,
-                parse_field_matcher_singleton_bracket,
+                |i| parse_field_matcher_group(i, defs),
+                |i| parse_field_matcher_singleton_bracket(i, defs),
                 parse_field_matcher_exists,
-                parse_field_matcher_not,
+                parse_field_matcher_predicate,
+                |i| parse_field_matcher_scope(i, defs),
+                |i| parse_field_matcher_named(i, defs),
+                |i| parse_field_matcher_not(i, defs),
             ))),
         ),
         |matcher| FieldMatcher::Not(Box::new(matcher)),
     )(i)
 }
 
+/// Parse an operand of a field matcher composite expression.
+fn parse_field_matcher_operand<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, FieldMatcher> {
+    alt((
+        ws(|i| parse_field_matcher_group(i, defs)),
+        ws(parse_field_matcher_predicate),
+        ws(|i| parse_field_matcher_scope(i, defs)),
+        ws(|i| parse_field_matcher_cardinality(i, defs)),
+        ws(|i| parse_field_matcher_singleton(i, defs)),
+        ws(|i| parse_field_matcher_named(i, defs)),
+        ws(|i| parse_field_matcher_not(i, defs)),
+        ws(parse_field_matcher_exists),
+    ))(i)
+}
+
 /// Parse a field matcher and expression.
-fn parse_field_matcher_and(i: &[u8]) -> ParseResult<FieldMatcher> {
+fn parse_field_matcher_and<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, FieldMatcher> {
     let (i, (first, remainder)) = tuple((
-        alt((
-            ws(parse_field_matcher_group),
-            ws(parse_field_matcher_cardinality),
-            ws(parse_field_matcher_singleton),
-            ws(parse_field_matcher_not),
-            ws(parse_field_matcher_exists),
-        )),
-        many1(preceded(
+        |i| parse_field_matcher_operand(i, defs),
+        many0(preceded(
             ws(tag("&&")),
-            alt((
-                ws(parse_field_matcher_group),
-                ws(parse_field_matcher_cardinality),
-                ws(parse_field_matcher_singleton),
-                ws(parse_field_matcher_not),
-                ws(parse_field_matcher_exists),
-            )),
+            cut(|i| parse_field_matcher_operand(i, defs)),
         )),
     ))(i)?;
 
@@ -603,24 +2152,35 @@ fn parse_field_matcher_and(i: &[u8]) -> ParseResult<FieldMatcher> {
     ))
 }
 
-fn parse_field_matcher_or(i: &[u8]) -> ParseResult<FieldMatcher> {
+/// Parse a field matcher xor expression.
+fn parse_field_matcher_xor<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, FieldMatcher> {
     let (i, (first, remainder)) = tuple((
-        alt((
-            ws(parse_field_matcher_group),
-            ws(parse_field_matcher_and),
-            ws(parse_field_matcher_cardinality),
-            ws(parse_field_matcher_singleton),
-            ws(parse_field_matcher_exists),
+        |i| parse_field_matcher_and(i, defs),
+        many0(preceded(
+            ws(parse_xor_op),
+            cut(|i| parse_field_matcher_and(i, defs)),
         )),
-        many1(preceded(
+    ))(i)?;
+
+    Ok((
+        i,
+        remainder.into_iter().fold(first, |prev, next| prev ^ next),
+    ))
+}
+
+/// Parse a field matcher or expression.
+fn parse_field_matcher_or<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, FieldMatcher> {
+    let (i, (first, remainder)) = tuple((
+        |i| parse_field_matcher_xor(i, defs),
+        many0(preceded(
             ws(tag("||")),
-            cut(alt((
-                ws(parse_field_matcher_group),
-                ws(parse_field_matcher_and),
-                ws(parse_field_matcher_cardinality),
-                ws(parse_field_matcher_singleton),
-                ws(parse_field_matcher_exists),
-            ))),
+            cut(|i| parse_field_matcher_xor(i, defs)),
         )),
     ))(i)?;
 
@@ -630,24 +2190,64 @@ fn parse_field_matcher_or(i: &[u8]) -> ParseResult<FieldMatcher> {
     ))
 }
 
-/// Parse a field matcher composite expression.
-fn parse_field_matcher_composite(
-    i: &[u8],
-) -> ParseResult<FieldMatcher> {
-    alt((parse_field_matcher_or, parse_field_matcher_and))(i)
+/// Parse a field matcher implication, which is right-associative.
+fn parse_field_matcher_implies<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, FieldMatcher> {
+    let (i, (lhs, rhs)) = pair(
+        |i| parse_field_matcher_or(i, defs),
+        opt(preceded(
+            ws(parse_implies_op),
+            cut(|i| parse_field_matcher_implies(i, defs)),
+        )),
+    )(i)?;
+
+    Ok((
+        i,
+        match rhs {
+            Some(rhs) => FieldMatcher::Composite {
+                lhs: Box::new(lhs),
+                op: BooleanOp::Implies,
+                rhs: Box::new(rhs),
+            },
+            None => lhs,
+        },
+    ))
 }
 
-/// Parse a field matcher expression.
-pub fn parse_field_matcher(i: &[u8]) -> ParseResult<FieldMatcher> {
+/// Parse a field matcher composite expression. Operators bind (from
+/// strongest to weakest) in the order `&&`, `^`, `||` and `=>`.
+#[inline]
+fn parse_field_matcher_composite<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, FieldMatcher> {
+    parse_field_matcher_implies(i, defs)
+}
+
+/// Parse a field matcher expression, which may contain references to
+/// the given named filters.
+pub(crate) fn parse_field_matcher_with<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, FieldMatcher> {
     alt((
-        ws(parse_field_matcher_composite),
-        ws(parse_field_matcher_group),
-        ws(parse_field_matcher_not),
-        ws(parse_field_matcher_singleton),
-        ws(parse_field_matcher_cardinality),
+        ws(|i| parse_field_matcher_composite(i, defs)),
+        ws(|i| parse_field_matcher_group(i, defs)),
+        ws(|i| parse_field_matcher_not(i, defs)),
+        ws(|i| parse_field_matcher_singleton(i, defs)),
+        ws(parse_field_matcher_predicate),
+        ws(|i| parse_field_matcher_scope(i, defs)),
+        ws(|i| parse_field_matcher_cardinality(i, defs)),
     ))(i)
 }
 
+/// Parse a field matcher expression.
+pub fn parse_field_matcher(i: &[u8]) -> ParseResult<FieldMatcher> {
+    parse_field_matcher_with(i, &Definitions::default())
+}
+
 #[cfg(test)]
 mod tests {
     use nom_test_helpers::assert_finished_and_eq;
@@ -686,11 +2286,59 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parse_reference_matcher() -> anyhow::Result<()> {
+        assert_finished_and_eq!(
+            parse_reference_matcher(b"003@.0 == 007G.0"),
+            ReferenceMatcher {
+                lhs: FieldReference {
+                    tag_matcher: TagMatcher::new("003@")?,
+                    occurrence_matcher: OccurrenceMatcher::None,
+                    codes: vec!['0'],
+                },
+                op: RelationalOp::Eq,
+                quantifier: Quantifier::Any,
+                rhs: FieldReference {
+                    tag_matcher: TagMatcher::new("007G")?,
+                    occurrence_matcher: OccurrenceMatcher::None,
+                    codes: vec!['0'],
+                },
+            }
+        );
+
+        assert_finished_and_eq!(
+            parse_reference_matcher(b"060R.b >= ALL 060R/*.[ab]"),
+            ReferenceMatcher {
+                lhs: FieldReference {
+                    tag_matcher: TagMatcher::new("060R")?,
+                    occurrence_matcher: OccurrenceMatcher::None,
+                    codes: vec!['b'],
+                },
+                op: RelationalOp::Ge,
+                quantifier: Quantifier::All,
+                rhs: FieldReference {
+                    tag_matcher: TagMatcher::new("060R")?,
+                    occurrence_matcher: OccurrenceMatcher::Any,
+                    codes: vec!['a', 'b'],
+                },
+            }
+        );
+
+        assert!(parse_reference_matcher(b"003@.0 == '123'").is_err());
+        assert!(parse_reference_matcher(b"003@.0 =~ 007G.0").is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn test_parse_subfields_matcher() -> anyhow::Result<()> {
         assert_finished_and_eq!(
-            parse_subfields_matcher(b"003@.0?"),
+            parse_subfields_matcher(
+                b"003@.0?",
+                &Definitions::default()
+            ),
             SubfieldsMatcher {
+                quantifier: Quantifier::Any,
                 tag_matcher: TagMatcher::new("003@")?,
                 occurrence_matcher: OccurrenceMatcher::None,
                 subfield_matcher: SubfieldMatcher::new("0?")?,
@@ -698,8 +2346,12 @@ mod tests {
         );
 
         assert_finished_and_eq!(
-            parse_subfields_matcher(b"003@$0?"),
+            parse_subfields_matcher(
+                b"003@$0?",
+                &Definitions::default()
+            ),
             SubfieldsMatcher {
+                quantifier: Quantifier::Any,
                 tag_matcher: TagMatcher::new("003@")?,
                 occurrence_matcher: OccurrenceMatcher::None,
                 subfield_matcher: SubfieldMatcher::new("0?")?,
@@ -707,8 +2359,12 @@ mod tests {
         );
 
         assert_finished_and_eq!(
-            parse_subfields_matcher(b"003@ $0?"),
+            parse_subfields_matcher(
+                b"003@ $0?",
+                &Definitions::default()
+            ),
             SubfieldsMatcher {
+                quantifier: Quantifier::Any,
                 tag_matcher: TagMatcher::new("003@")?,
                 occurrence_matcher: OccurrenceMatcher::None,
                 subfield_matcher: SubfieldMatcher::new("0?")?,
@@ -716,8 +2372,12 @@ mod tests {
         );
 
         assert_finished_and_eq!(
-            parse_subfields_matcher(b"003@{ #0 == 1 && 0? }"),
+            parse_subfields_matcher(
+                b"003@{ #0 == 1 && 0? }",
+                &Definitions::default()
+            ),
             SubfieldsMatcher {
+                quantifier: Quantifier::Any,
                 tag_matcher: TagMatcher::new("003@")?,
                 occurrence_matcher: OccurrenceMatcher::None,
                 subfield_matcher: SubfieldMatcher::new(
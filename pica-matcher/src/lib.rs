@@ -2,24 +2,37 @@
 //! or subfields.
 
 mod common;
+mod compiled_matcher;
+mod context;
+mod definitions;
 mod error;
 pub mod field_matcher;
+mod list;
 mod occurrence_matcher;
 mod options;
 mod record_matcher;
+mod scope;
 pub mod subfield_matcher;
 mod tag_matcher;
+mod trace;
 
-pub use error::ParseMatcherError;
+pub use compiled_matcher::CompiledMatcher;
+pub use context::ParseContext;
+pub use error::{ParseMatcherError, SyntaxError};
 pub use field_matcher::FieldMatcher;
-pub use occurrence_matcher::OccurrenceMatcher;
-pub use options::MatcherOptions;
+pub use list::read_list;
+pub use occurrence_matcher::{OccurrenceComparison, OccurrenceMatcher};
+pub use options::{MatcherOptions, NormalizationForm, StrsimMetric};
 pub use record_matcher::RecordMatcher;
+pub use scope::Scope;
 pub use subfield_matcher::SubfieldMatcher;
 pub use tag_matcher::TagMatcher;
+pub use trace::Trace;
 
 /// Parsers recognizing matcher for PICA+ primitves.
 pub mod parser {
+    pub use super::common::{parse_subfield_code, ParseResult};
+    pub use super::error::{ParserError, SyntaxError};
     pub use super::field_matcher::parse_field_matcher;
     pub use super::occurrence_matcher::parse_occurrence_matcher;
     pub use super::subfield_matcher::parse_subfield_matcher;
@@ -0,0 +1,114 @@
+//! Lists of values, which are read from external files.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+use bstr::BString;
+
+use crate::{ParseContext, ParseMatcherError};
+
+/// Reads a list of values from a file.
+///
+/// If the file has the extension `csv`, the values are taken from the
+/// first column (the first row is treated as header); otherwise each
+/// non-empty line of the file is a value. Relative paths are resolved
+/// against the current working directory.
+pub fn read_list<P: AsRef<Path>>(
+    path: P,
+) -> io::Result<HashSet<BString>> {
+    let path = path.as_ref();
+    let mut values = HashSet::new();
+
+    if path.extension() == Some(OsStr::new("csv")) {
+        let mut reader =
+            csv::ReaderBuilder::new().flexible(true).from_path(path)?;
+
+        for result in reader.byte_records() {
+            let row = result?;
+            if let Some(value) = row.get(0) {
+                values.insert(BString::from(value));
+            }
+        }
+    } else {
+        let reader = BufReader::new(File::open(path)?);
+        for line in reader.split(b'\n') {
+            let mut line = line?;
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+
+            if !line.is_empty() {
+                values.insert(BString::from(line));
+            }
+        }
+    }
+
+    Ok(values)
+}
+
+/// The lists of a matcher expression, which are read once per path
+/// after the expression has been parsed. Relative paths are resolved
+/// against the base directory of the context.
+#[derive(Debug)]
+pub(crate) struct ListCache<'a> {
+    ctx: &'a ParseContext,
+    lists: HashMap<String, HashSet<BString>>,
+}
+
+impl<'a> ListCache<'a> {
+    /// Creates an empty cache for the lists of the given context.
+    pub(crate) fn new(ctx: &'a ParseContext) -> Self {
+        Self {
+            ctx,
+            lists: HashMap::new(),
+        }
+    }
+
+    /// Returns the values of the list file, which is read on the
+    /// first call for a path.
+    pub(crate) fn get(
+        &mut self,
+        path: &str,
+    ) -> Result<&HashSet<BString>, ParseMatcherError> {
+        match self.lists.entry(path.to_string()) {
+            Entry::Occupied(entry) => Ok(entry.into_mut()),
+            Entry::Vacant(entry) => {
+                let values = read_list(self.ctx.resolve_path(path))
+                    .map_err(|e| {
+                        ParseMatcherError::InvalidListFile(
+                            path.to_string(),
+                            e.to_string(),
+                        )
+                    })?;
+
+                Ok(entry.insert(values))
+            }
+        }
+    }
+}
+
+/// Matchers, which may contain references to list files
+/// (`0 in @codes.txt`). The parsers only keep the paths of the files;
+/// the lists are read afterwards by [LoadLists::load_lists].
+pub(crate) trait LoadLists: Sized {
+    /// Reads the lists of all list file references of the matcher.
+    fn load_lists(
+        &mut self,
+        lists: &mut ListCache,
+    ) -> Result<(), ParseMatcherError>;
+
+    /// Reads the lists of the matcher, whereby relative paths are
+    /// resolved against the base directory of the context, and
+    /// returns the matcher.
+    fn with_lists(
+        mut self,
+        ctx: &ParseContext,
+    ) -> Result<Self, ParseMatcherError> {
+        self.load_lists(&mut ListCache::new(ctx))?;
+        Ok(self)
+    }
+}
@@ -1,17 +1,24 @@
-use std::fmt::Display;
+use std::fmt::{self, Display};
+use std::ops::Deref;
 
 use bstr::BStr;
 use nom::branch::alt;
 use nom::bytes::complete::tag;
-use nom::character::complete::char;
+use nom::character::complete::{char, multispace0};
 use nom::combinator::{
     all_consuming, cut, map, success, value, verify,
 };
-use nom::sequence::{preceded, separated_pair};
+use nom::multi::separated_list1;
+use nom::sequence::{delimited, pair, preceded, separated_pair};
 use nom::Finish;
-use pica_record::parser::{parse_occurrence_digits, ParseResult};
 use pica_record::{Occurrence, OccurrenceMut};
+#[cfg(feature = "serde")]
+use serde::{Serialize, Serializer};
 
+use crate::common::{
+    parse_occurrence_digits, parse_relational_op_usize, ParseResult,
+    RelationalOp,
+};
 use crate::ParseMatcherError;
 
 /// A matcher that matches against PICA+
@@ -21,9 +28,52 @@ pub enum OccurrenceMatcher {
     Any,
     Some(OccurrenceMut),
     Range(OccurrenceMut, OccurrenceMut),
+    List(Vec<OccurrenceMut>),
+    Comparison(OccurrenceComparison),
+    NonZero,
     None,
 }
 
+/// A numeric comparison of an occurrence with a value (e.g. `/>=50`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OccurrenceComparison {
+    op: RelationalOp,
+    value: OccurrenceMut,
+}
+
+impl OccurrenceComparison {
+    /// Returns `true` if the numeric value of the occurrence satisfies
+    /// the comparison.
+    fn is_match<T: AsRef<[u8]>>(
+        &self,
+        occurrence: &Occurrence<T>,
+    ) -> bool {
+        let (Some(lhs), Some(rhs)) =
+            (numeric_value(occurrence), numeric_value(&self.value))
+        else {
+            return false;
+        };
+
+        match self.op {
+            RelationalOp::Ne => lhs != rhs,
+            RelationalOp::Gt => lhs > rhs,
+            RelationalOp::Ge => lhs >= rhs,
+            RelationalOp::Lt => lhs < rhs,
+            RelationalOp::Le => lhs <= rhs,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Returns the numeric value of an occurrence (`"05"` is `5`).
+fn numeric_value<T: AsRef<[u8]>>(
+    occurrence: &Occurrence<T>,
+) -> Option<u32> {
+    std::str::from_utf8(occurrence.deref().as_ref())
+        .ok()
+        .and_then(|value| value.parse().ok())
+}
+
 impl OccurrenceMatcher {
     /// Create a new tag matcher.
     ///
@@ -71,6 +121,19 @@ impl OccurrenceMatcher {
     ///     assert!(matcher.is_match(&OccurrenceRef::new("02")));
     ///     assert!(!matcher.is_match(&OccurrenceRef::new("04")));
     ///
+    ///     let matcher = OccurrenceMatcher::new("/>=50")?;
+    ///     assert!(matcher.is_match(&OccurrenceRef::new("50")));
+    ///     assert!(matcher.is_match(&OccurrenceRef::new("100")));
+    ///     assert!(!matcher.is_match(&OccurrenceRef::new("09")));
+    ///
+    ///     let matcher = OccurrenceMatcher::new("/[01,05,09]")?;
+    ///     assert!(matcher.is_match(&OccurrenceRef::new("05")));
+    ///     assert!(!matcher.is_match(&OccurrenceRef::new("02")));
+    ///
+    ///     let matcher = OccurrenceMatcher::new("/+")?;
+    ///     assert!(matcher.is_match(&OccurrenceRef::new("01")));
+    ///     assert!(!matcher.is_match(&OccurrenceRef::new("00")));
+    ///
     ///     Ok(())
     /// }
     /// ```
@@ -85,6 +148,59 @@ impl OccurrenceMatcher {
             Self::Range(min, max) => {
                 (occurrence >= min) && (occurrence <= max)
             }
+            Self::List(values) => {
+                values.iter().any(|value| occurrence == value)
+            }
+            Self::Comparison(comparison) => {
+                comparison.is_match(occurrence)
+            }
+            Self::NonZero => numeric_value(occurrence) != Some(0),
+        }
+    }
+}
+
+impl Display for OccurrenceMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Any => write!(f, "/*"),
+            Self::None => Ok(()),
+            Self::Some(occurrence) => write!(f, "/{occurrence}"),
+            Self::Range(min, max) => write!(f, "/{min}-{max}"),
+            Self::List(values) => {
+                write!(f, "/[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                write!(f, "]")
+            }
+            Self::Comparison(OccurrenceComparison { op, value }) => {
+                write!(f, "/{op}{value}")
+            }
+            Self::NonZero => write!(f, "/+"),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for OccurrenceMatcher {
+    /// Serializes the matcher without the leading slash (`"01"`,
+    /// `"01-03"`, `"[01,05]"`, `">=50"`, `"+"` or `"*"`); a matcher
+    /// without an occurrence is serialized as `null`.
+    fn serialize<S: Serializer>(
+        &self,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Any => s.serialize_str("*"),
+            Self::None => s.serialize_none(),
+            Self::Some(occurrence) => s.collect_str(occurrence),
+            Self::Range(min, max) => {
+                s.collect_str(&format_args!("{min}-{max}"))
+            }
+            matcher => s.collect_str(&matcher.to_string()[1..]),
         }
     }
 }
@@ -101,7 +217,14 @@ impl<T: AsRef<[u8]>> PartialEq<Option<&Occurrence<T>>>
     fn eq(&self, other: &Option<&Occurrence<T>>) -> bool {
         match other {
             Some(occurrence) => self.is_match(occurrence),
-            None => matches!(self, Self::Any | Self::None),
+            None => match self {
+                Self::Any | Self::None => true,
+                // A field without an occurrence has the occurrence `00`.
+                Self::List(_) | Self::Comparison(_) => {
+                    self.is_match(&OccurrenceMut::new("00"))
+                }
+                _ => false,
+            },
         }
     }
 }
@@ -138,6 +261,54 @@ fn parse_occurrence_range(i: &[u8]) -> ParseResult<OccurrenceMatcher> {
     )(i)
 }
 
+#[inline]
+fn parse_occurrence_list(i: &[u8]) -> ParseResult<OccurrenceMatcher> {
+    map(
+        delimited(
+            char('['),
+            cut(separated_list1(
+                char(','),
+                delimited(
+                    multispace0,
+                    parse_occurrence_digits,
+                    multispace0,
+                ),
+            )),
+            cut(char(']')),
+        ),
+        |values| {
+            OccurrenceMatcher::List(
+                values
+                    .into_iter()
+                    .map(OccurrenceMut::from_unchecked)
+                    .collect(),
+            )
+        },
+    )(i)
+}
+
+#[inline]
+fn parse_occurrence_comparison(
+    i: &[u8],
+) -> ParseResult<OccurrenceMatcher> {
+    map(
+        pair(parse_relational_op_usize, cut(parse_occurrence_digits)),
+        |(op, value)| {
+            let value = OccurrenceMut::from_unchecked(value);
+            if op == RelationalOp::Eq && value == "00" {
+                OccurrenceMatcher::None
+            } else if op == RelationalOp::Eq {
+                OccurrenceMatcher::Some(value)
+            } else {
+                OccurrenceMatcher::Comparison(OccurrenceComparison {
+                    op,
+                    value,
+                })
+            }
+        },
+    )(i)
+}
+
 #[inline]
 fn parse_occurrence_exact(i: &[u8]) -> ParseResult<OccurrenceMatcher> {
     map(
@@ -153,6 +324,9 @@ pub fn parse_occurrence_matcher(
         preceded(
             char('/'),
             cut(alt((
+                parse_occurrence_comparison,
+                parse_occurrence_list,
+                value(OccurrenceMatcher::NonZero, char('+')),
                 parse_occurrence_range,
                 parse_occurrence_exact,
                 value(OccurrenceMatcher::None, tag("00")),
@@ -195,6 +369,33 @@ mod tests {
             )
         );
 
+        assert_done_and_eq!(
+            parse_occurrence_matcher(b"/[01, 05,09]"),
+            OccurrenceMatcher::List(vec![
+                OccurrenceMut::new("01"),
+                OccurrenceMut::new("05"),
+                OccurrenceMut::new("09"),
+            ])
+        );
+
+        assert_done_and_eq!(
+            parse_occurrence_matcher(b"/>=50"),
+            OccurrenceMatcher::Comparison(OccurrenceComparison {
+                op: RelationalOp::Ge,
+                value: OccurrenceMut::new("50"),
+            })
+        );
+
+        assert_done_and_eq!(
+            parse_occurrence_matcher(b"/==01"),
+            OccurrenceMatcher::Some(OccurrenceMut::new("01"))
+        );
+
+        assert_done_and_eq!(
+            parse_occurrence_matcher(b"/+"),
+            OccurrenceMatcher::NonZero
+        );
+
         assert_done_and_eq!(
             parse_occurrence_matcher(b""),
             OccurrenceMatcher::None,
@@ -202,6 +403,10 @@ mod tests {
 
         assert_error!(parse_occurrence_matcher(b"/0A"));
         assert_error!(parse_occurrence_matcher(b"/A"));
+        assert_error!(parse_occurrence_matcher(b"/[]"));
+        assert_error!(parse_occurrence_matcher(b"/[01,02"));
+        assert_error!(parse_occurrence_matcher(b"/>"));
+        assert_error!(parse_occurrence_matcher(b"/=~01"));
 
         Ok(())
     }
@@ -227,6 +432,52 @@ mod tests {
         assert!(matcher.is_match(&OccurrenceRef::new("00")));
         assert!(!matcher.is_match(&OccurrenceRef::new("01")));
 
+        let matcher = OccurrenceMatcher::new("/[01,05,09]")?;
+        assert!(matcher.is_match(&OccurrenceRef::new("05")));
+        assert!(!matcher.is_match(&OccurrenceRef::new("02")));
+        assert!(!matcher.is_match(&OccurrenceRef::new("005")));
+
+        let matcher = OccurrenceMatcher::new("/>=50")?;
+        assert!(matcher.is_match(&OccurrenceRef::new("50")));
+        assert!(matcher.is_match(&OccurrenceRef::new("99")));
+        assert!(matcher.is_match(&OccurrenceRef::new("100")));
+        assert!(!matcher.is_match(&OccurrenceRef::new("49")));
+
+        let matcher = OccurrenceMatcher::new("/<05")?;
+        assert!(matcher.is_match(&OccurrenceRef::new("00")));
+        assert!(matcher.is_match(&OccurrenceRef::new("004")));
+        assert!(!matcher.is_match(&OccurrenceRef::new("05")));
+
+        let matcher = OccurrenceMatcher::new("/!=01")?;
+        assert!(matcher.is_match(&OccurrenceRef::new("02")));
+        assert!(!matcher.is_match(&OccurrenceRef::new("001")));
+
+        let matcher = OccurrenceMatcher::new("/+")?;
+        assert!(matcher.is_match(&OccurrenceRef::new("01")));
+        assert!(matcher.is_match(&OccurrenceRef::new("100")));
+        assert!(!matcher.is_match(&OccurrenceRef::new("00")));
+        assert!(!matcher.is_match(&OccurrenceRef::new("000")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_string() -> anyhow::Result<()> {
+        for (expr, expected) in [
+            ("/*", "/*"),
+            ("/01-03", "/01-03"),
+            ("/[01, 05,09]", "/[01,05,09]"),
+            ("/>=50", "/>=50"),
+            ("/==05", "/05"),
+            ("/==00", ""),
+            ("/!=01", "/!=01"),
+            ("/+", "/+"),
+        ] {
+            let matcher = OccurrenceMatcher::new(expr)?;
+            assert_eq!(matcher.to_string(), expected);
+            assert_eq!(OccurrenceMatcher::new(expected)?, matcher);
+        }
+
         Ok(())
     }
 
@@ -257,6 +508,15 @@ mod tests {
         assert_ne!(matcher, OccurrenceRef::new("01"));
         assert_eq!(matcher, Option::<OccurrenceRef>::None.as_ref());
 
+        let matcher = OccurrenceMatcher::new("/<05")?;
+        assert_eq!(matcher, Option::<OccurrenceRef>::None.as_ref());
+
+        let matcher = OccurrenceMatcher::new("/[00,01]")?;
+        assert_eq!(matcher, Option::<OccurrenceRef>::None.as_ref());
+
+        let matcher = OccurrenceMatcher::new("/+")?;
+        assert_ne!(matcher, Option::<OccurrenceRef>::None.as_ref());
+
         Ok(())
     }
 }
@@ -1,15 +1,241 @@
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use bstr::ByteSlice;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize, Serializer};
+use strsim::{
+    jaro_winkler, normalized_damerau_levenshtein,
+    normalized_levenshtein,
+};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::ParseMatcherError;
+
+/// Unicode normalization forms, which can be applied to values before
+/// they are compared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NormalizationForm {
+    Nfc,
+    Nfkc,
+    Nfd,
+    Nfkd,
+}
+
+impl NormalizationForm {
+    /// Returns `true` if the normalization form is a composed form.
+    fn is_composed(&self) -> bool {
+        matches!(self, Self::Nfc | Self::Nfkc)
+    }
+}
+
+impl FromStr for NormalizationForm {
+    type Err = ParseMatcherError;
+
+    /// Parses a normalization form from a string slice (`nfc`, `nfkc`,
+    /// `nfd` or `nfkd`).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use pica_matcher::NormalizationForm;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> anyhow::Result<()> {
+    ///     let nf = "NFD".parse::<NormalizationForm>()?;
+    ///     assert_eq!(nf, NormalizationForm::Nfd);
+    ///
+    ///     assert!("nfx".parse::<NormalizationForm>().is_err());
+    ///     Ok(())
+    /// }
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "nfc" => Ok(Self::Nfc),
+            "nfkc" => Ok(Self::Nfkc),
+            "nfd" => Ok(Self::Nfd),
+            "nfkd" => Ok(Self::Nfkd),
+            _ => Err(ParseMatcherError::InvalidNormalizationForm(
+                s.to_string(),
+            )),
+        }
+    }
+}
+
+impl Display for NormalizationForm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Nfc => write!(f, "nfc"),
+            Self::Nfkc => write!(f, "nfkc"),
+            Self::Nfd => write!(f, "nfd"),
+            Self::Nfkd => write!(f, "nfkd"),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for NormalizationForm {
+    fn serialize<S: Serializer>(
+        &self,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for NormalizationForm {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Self::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// String similarity metrics, which are used by the similar operator
+/// (`=*`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StrsimMetric {
+    /// The normalized Levenshtein distance.
+    #[default]
+    Levenshtein,
+    /// The normalized Damerau-Levenshtein distance, which counts
+    /// transpositions of adjacent characters as a single edit.
+    DamerauLevenshtein,
+    /// The Jaro-Winkler similarity, which favors strings with a common
+    /// prefix.
+    JaroWinkler,
+    /// The token set ratio, which compares the (sorted) sets of words
+    /// of both strings and ignores duplicate words and word order. If
+    /// the words of one string are a subset of the words of the other
+    /// string, the score is 1.0.
+    TokenSet,
+}
+
+impl StrsimMetric {
+    /// Returns the similarity score (between 0.0 and 1.0) of both
+    /// strings.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use pica_matcher::StrsimMetric;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> anyhow::Result<()> {
+    ///     let metric = StrsimMetric::TokenSet;
+    ///     assert_eq!(metric.score("Lovelace, Ada", "Ada Lovelace,"), 1.0);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn score(&self, lhs: &str, rhs: &str) -> f64 {
+        match self {
+            Self::Levenshtein => normalized_levenshtein(lhs, rhs),
+            Self::DamerauLevenshtein => {
+                normalized_damerau_levenshtein(lhs, rhs)
+            }
+            Self::JaroWinkler => jaro_winkler(lhs, rhs),
+            Self::TokenSet => token_set_ratio(lhs, rhs),
+        }
+    }
+}
+
+/// Computes the token set ratio of two strings. The common words of
+/// both strings (`t0`) are compared against the common words followed
+/// by the remaining words of each string (`t1` and `t2`). The maximum
+/// of the normalized Levenshtein distances is returned. A string
+/// without any words isn't similar to any other string.
+fn token_set_ratio(lhs: &str, rhs: &str) -> f64 {
+    fn tokens(value: &str) -> BTreeSet<&str> {
+        value
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|token| !token.is_empty())
+            .collect()
+    }
+
+    let lhs = tokens(lhs);
+    let rhs = tokens(rhs);
+    if lhs.is_empty() || rhs.is_empty() {
+        return 0.0;
+    }
+
+    let common = || lhs.intersection(&rhs).copied();
+
+    let t0 = common().collect::<Vec<_>>().join(" ");
+    let t1 = common()
+        .chain(lhs.difference(&rhs).copied())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let t2 = common()
+        .chain(rhs.difference(&lhs).copied())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    [
+        normalized_levenshtein(&t0, &t1),
+        normalized_levenshtein(&t0, &t2),
+        normalized_levenshtein(&t1, &t2),
+    ]
+    .into_iter()
+    .fold(0.0, f64::max)
+}
+
+impl FromStr for StrsimMetric {
+    type Err = ParseMatcherError;
+
+    /// Parses a similarity metric from a string slice (`levenshtein`,
+    /// `damerau-levenshtein`, `jaro-winkler` or `token-set`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "levenshtein" => Ok(Self::Levenshtein),
+            "damerau-levenshtein" => Ok(Self::DamerauLevenshtein),
+            "jaro-winkler" => Ok(Self::JaroWinkler),
+            "token-set" => Ok(Self::TokenSet),
+            _ => Err(ParseMatcherError::InvalidStrsimMetric(
+                s.to_string(),
+            )),
+        }
+    }
+}
+
+impl Display for StrsimMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Levenshtein => write!(f, "levenshtein"),
+            Self::DamerauLevenshtein => {
+                write!(f, "damerau-levenshtein")
+            }
+            Self::JaroWinkler => write!(f, "jaro-winkler"),
+            Self::TokenSet => write!(f, "token-set"),
+        }
+    }
+}
+
 /// Options and flags which can be used to configure a matcher.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct MatcherOptions {
     pub case_ignore: bool,
+    pub case_fold: bool,
+    pub diacritic_ignore: bool,
+    pub normalization: Option<NormalizationForm>,
     pub strsim_threshold: f64,
+    pub strsim_metric: StrsimMetric,
 }
 
 impl Default for MatcherOptions {
     fn default() -> Self {
         Self {
             case_ignore: false,
+            case_fold: false,
+            diacritic_ignore: false,
+            normalization: None,
             strsim_threshold: 0.8,
+            strsim_metric: StrsimMetric::default(),
         }
     }
 }
@@ -26,9 +252,150 @@ impl MatcherOptions {
         self
     }
 
+    /// Whether to fold the case when comparing strings or not. In
+    /// contrast to `case_ignore`, characters like `ß` and `SS` are
+    /// considered equal.
+    ///
+    /// The case is folded by converting a value to uppercase and then
+    /// to lowercase, which approximates the full Unicode case folding
+    /// (`CaseFolding.txt`), but may differ for some characters (e.g.
+    /// the Cherokee letters, which are folded to uppercase).
+    pub fn case_fold(mut self, yes: bool) -> Self {
+        self.case_fold = yes;
+        self
+    }
+
+    /// Whether to ignore diacritics (combining marks) when comparing
+    /// strings or not (e.g. `Müller` and `Muller` are considered
+    /// equal).
+    pub fn diacritic_ignore(mut self, yes: bool) -> Self {
+        self.diacritic_ignore = yes;
+        self
+    }
+
+    /// Set the Unicode normalization form, which is applied to both
+    /// values before they are compared.
+    pub fn normalization(
+        mut self,
+        normalization: Option<NormalizationForm>,
+    ) -> Self {
+        self.normalization = normalization;
+        self
+    }
+
     /// Set the similarity threshold for the similar operator (`=*`).
     pub fn strsim_threshold(mut self, threshold: f64) -> Self {
         self.strsim_threshold = threshold;
         self
     }
+
+    /// Set the similarity metric for the similar operator (`=*`).
+    pub fn strsim_metric(mut self, metric: StrsimMetric) -> Self {
+        self.strsim_metric = metric;
+        self
+    }
+
+    /// Returns `true` if a value is transformed by Unicode
+    /// normalization, case folding or the removal of diacritics
+    /// before it is compared.
+    pub fn is_unicode_aware(&self) -> bool {
+        self.case_fold
+            || self.diacritic_ignore
+            || self.normalization.is_some()
+    }
+
+    /// Returns `true` if regular expressions should be matched case
+    /// insensitive.
+    pub fn is_case_insensitive(&self) -> bool {
+        self.case_ignore || self.case_fold
+    }
+
+    /// Transforms a value into the form in which it is compared to
+    /// other values. The value is normalized, diacritics are removed
+    /// and the case is folded (or lowercased) according to the
+    /// options. Values, which aren't valid UTF-8, are converted
+    /// lossy, if one of the Unicode options is set.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use pica_matcher::{MatcherOptions, NormalizationForm};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> anyhow::Result<()> {
+    ///     let options = MatcherOptions::new()
+    ///         .normalization(Some(NormalizationForm::Nfc))
+    ///         .diacritic_ignore(true)
+    ///         .case_fold(true);
+    ///
+    ///     assert_eq!(
+    ///         options.normalize("Mu\u{0308}ller".as_bytes()),
+    ///         options.normalize("MÜLLER".as_bytes()),
+    ///     );
+    ///     assert_eq!(
+    ///         options.normalize("Straße".as_bytes()).as_ref(),
+    ///         b"strasse"
+    ///     );
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn normalize<'a>(&self, value: &'a [u8]) -> Cow<'a, [u8]> {
+        if !self.is_unicode_aware() {
+            return if self.case_ignore {
+                Cow::Owned(value.to_lowercase())
+            } else {
+                Cow::Borrowed(value)
+            };
+        }
+
+        let value = self.normalize_unicode(value);
+        let value = value.to_str_lossy();
+
+        // An approximation of the full Unicode case folding (see
+        // `MatcherOptions::case_fold`).
+        let value = if self.case_fold {
+            value.to_uppercase().to_lowercase()
+        } else if self.case_ignore {
+            value.to_lowercase()
+        } else {
+            value.into_owned()
+        };
+
+        Cow::Owned(value.into_bytes())
+    }
+
+    /// Applies the Unicode normalization and the removal of
+    /// diacritics to a value, without changing its case. This is used
+    /// for regular expressions, which handle the case by themselves.
+    pub fn normalize_unicode<'a>(
+        &self,
+        value: &'a [u8],
+    ) -> Cow<'a, [u8]> {
+        if !self.diacritic_ignore && self.normalization.is_none() {
+            return Cow::Borrowed(value);
+        }
+
+        let value = value.to_str_lossy();
+        let value: String = match self.normalization {
+            Some(NormalizationForm::Nfc) => value.nfc().collect(),
+            Some(NormalizationForm::Nfkc) => value.nfkc().collect(),
+            Some(NormalizationForm::Nfd) => value.nfd().collect(),
+            Some(NormalizationForm::Nfkd) => value.nfkd().collect(),
+            None => value.into_owned(),
+        };
+
+        if !self.diacritic_ignore {
+            return Cow::Owned(value.into_bytes());
+        }
+
+        // Diacritics are removed from the decomposed value. The result
+        // is composed again, if a composed normalization form is used.
+        let stripped = value.nfd().filter(|c| !is_combining_mark(*c));
+        let value: String = match self.normalization {
+            Some(nf) if nf.is_composed() => stripped.nfc().collect(),
+            _ => stripped.collect(),
+        };
+
+        Cow::Owned(value.into_bytes())
+    }
 }
@@ -1,22 +1,26 @@
 use std::fmt::{self, Display};
-use std::ops::{BitAnd, BitOr, Not};
+use std::ops::{BitAnd, BitOr, BitXor, Not};
 use std::str::FromStr;
 
 use nom::combinator::all_consuming;
 use nom::Finish;
 use pica_record::Record;
 #[cfg(feature = "serde")]
-use serde::Deserialize;
+use serde::{Deserialize, Serialize, Serializer};
 
 use crate::common::BooleanOp;
-use crate::field_matcher::parse_field_matcher;
-use crate::{FieldMatcher, MatcherOptions, ParseMatcherError};
+use crate::definitions::parse_definitions;
+use crate::field_matcher::parse_field_matcher_with;
+use crate::list::{ListCache, LoadLists};
+use crate::{
+    CompiledMatcher, FieldMatcher, MatcherOptions, ParseContext,
+    ParseMatcherError, SyntaxError, Trace,
+};
 
 /// A Matcher that works on PICA+ [Records](pica_record::Record).
 #[derive(Debug, PartialEq, Eq)]
 pub struct RecordMatcher {
     pub(crate) field_matcher: FieldMatcher,
-    pub(crate) matcher_str: String,
 }
 
 impl RecordMatcher {
@@ -39,15 +43,145 @@ impl RecordMatcher {
     /// }
     /// ```
     pub fn new(data: &str) -> Result<Self, ParseMatcherError> {
-        all_consuming(parse_field_matcher)(data.as_bytes())
-            .finish()
-            .map_err(|_| {
-                ParseMatcherError::InvalidRecordMatcher(data.into())
-            })
-            .map(|(_, matcher)| Self {
-                field_matcher: matcher,
-                matcher_str: data.into(),
-            })
+        Self::with_context(data, &ParseContext::default())
+    }
+
+    /// Create a new field matcher from a string slice, which may
+    /// reference the named filters of the given context (`%name`).
+    /// Relative paths of list file references are resolved against the
+    /// base directory of the context.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::collections::BTreeMap;
+    ///
+    /// use pica_matcher::{ParseContext, RecordMatcher};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> anyhow::Result<()> {
+    ///     let ctx = ParseContext::new().definitions(BTreeMap::from([(
+    ///         "persons".to_string(),
+    ///         "002@.0 =^ 'Tp'".to_string(),
+    ///     )]));
+    ///
+    ///     let matcher = RecordMatcher::with_context(
+    ///         "let lovelace = 028A.a == 'Lovelace'; \
+    ///          %persons && $lovelace",
+    ///         &ctx,
+    ///     )?;
+    ///
+    ///     assert_eq!(
+    ///         matcher.to_string(),
+    ///         "002@.0 =^ 'Tp' && 028A.a == 'Lovelace'"
+    ///     );
+    ///
+    ///     assert!(RecordMatcher::new("%persons && 028A?").is_err());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_context(
+        data: &str,
+        ctx: &ParseContext,
+    ) -> Result<Self, ParseMatcherError> {
+        Self::parse_with_context(data, ctx)?.with_lists(ctx)
+    }
+
+    /// Parses the expression without reading the lists of list file
+    /// references (`0 in @codes.txt`), which is sufficient to format
+    /// or inspect an expression. A list, which hasn't been read, doesn't
+    /// contain any values; use [RecordMatcher::new] to evaluate the
+    /// expression.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use pica_matcher::RecordMatcher;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> anyhow::Result<()> {
+    ///     let expr = "002@.0 in @missing.txt";
+    ///     let matcher = RecordMatcher::parse(expr)?;
+    ///     assert_eq!(matcher.to_string(), expr);
+    ///     assert!(RecordMatcher::new(expr).is_err());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn parse(data: &str) -> Result<Self, ParseMatcherError> {
+        Self::parse_with_context(data, &ParseContext::default())
+    }
+
+    /// Parses the expression with the named filters of the given
+    /// context, but without reading the lists of list file references
+    /// (see [RecordMatcher::parse]).
+    pub fn parse_with_context(
+        data: &str,
+        ctx: &ParseContext,
+    ) -> Result<Self, ParseMatcherError> {
+        all_consuming(|i| {
+            parse_definitions(
+                i,
+                &ctx.definitions,
+                parse_field_matcher_with,
+            )
+        })(data.as_bytes())
+        .finish()
+        .map_err(|e| {
+            ParseMatcherError::InvalidRecordMatcher(SyntaxError::new(
+                data, e,
+            ))
+        })
+        .map(|(_, matcher)| Self {
+            field_matcher: matcher,
+        })
+    }
+
+    /// Reads the lists of all list file references (`0 in @codes.txt`)
+    /// of a matcher, which has been created by [RecordMatcher::parse],
+    /// whereby relative paths are resolved against the base directory
+    /// of the context.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use pica_matcher::{ParseContext, RecordMatcher};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> anyhow::Result<()> {
+    ///     let ctx = ParseContext::new().base_dir("tests/data");
+    ///     let mut matcher = RecordMatcher::parse("002@.0 in @bbg.txt")?;
+    ///     matcher.read_lists(&ctx)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn read_lists(
+        &mut self,
+        ctx: &ParseContext,
+    ) -> Result<(), ParseMatcherError> {
+        self.load_lists(&mut ListCache::new(ctx))
+    }
+
+    /// Returns the underlying field matcher, which is the abstract
+    /// syntax tree of the expression. If the `serde` feature is
+    /// enabled, the tree can be serialized (e.g. to JSON).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use pica_matcher::{FieldMatcher, RecordMatcher};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> anyhow::Result<()> {
+    ///     let matcher = RecordMatcher::new("003@? && (002@?)")?;
+    ///     assert_eq!(
+    ///         matcher.field_matcher().to_string(),
+    ///         "003@? && 002@?"
+    ///     );
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn field_matcher(&self) -> &FieldMatcher {
+        &self.field_matcher
     }
 
     /// Returns `true` if the given record matches against the record
@@ -59,11 +193,83 @@ impl RecordMatcher {
     ) -> bool {
         self.field_matcher.is_match(record.iter(), options)
     }
+
+    /// Compiles the matcher with the given options for a fast
+    /// evaluation against many records (see [CompiledMatcher]).
+    pub fn compile(
+        &self,
+        options: &MatcherOptions,
+    ) -> CompiledMatcher<'_> {
+        CompiledMatcher::new(self, options)
+    }
+
+    /// Evaluates the matcher against the given record and returns the
+    /// trace of the evaluation, which explains why the record matched
+    /// or failed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use pica_matcher::RecordMatcher;
+    /// use pica_record::RecordRef;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> anyhow::Result<()> {
+    ///     let matcher =
+    ///         RecordMatcher::new("003@? && 012A{a == 'x' || b?}")?;
+    ///     let record = RecordRef::new(vec![
+    ///         ("003@", None, vec![('0', "abc")]),
+    ///         ("012A", None, vec![('a', "y")]),
+    ///     ]);
+    ///
+    ///     let trace = matcher.trace(&record, &Default::default());
+    ///     assert!(!trace.result());
+    ///     assert_eq!(trace.expr(), "&&");
+    ///
+    ///     let children = trace.children();
+    ///     assert_eq!(children[0].expr(), "003@?");
+    ///     assert!(children[0].result());
+    ///     assert_eq!(children[1].expr(), "012A{a == 'x' || b?}");
+    ///     assert!(!children[1].result());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn trace<T: AsRef<[u8]>>(
+        &self,
+        record: &Record<T>,
+        options: &MatcherOptions,
+    ) -> Trace {
+        self.field_matcher.trace(record.iter(), options)
+    }
+}
+
+impl LoadLists for RecordMatcher {
+    fn load_lists(
+        &mut self,
+        lists: &mut ListCache,
+    ) -> Result<(), ParseMatcherError> {
+        self.field_matcher.load_lists(lists)
+    }
 }
 
 impl Display for RecordMatcher {
+    /// Formats the matcher in a canonical form (see [FieldMatcher]).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use pica_matcher::RecordMatcher;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> anyhow::Result<()> {
+    ///     let matcher = RecordMatcher::new("003@?")?
+    ///         & !RecordMatcher::new("002@ $0 == \"Tp1\"")?;
+    ///     assert_eq!(matcher.to_string(), "003@? && !(002@.0 == 'Tp1')");
+    ///     Ok(())
+    /// }
+    /// ```
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.matcher_str)
+        write!(f, "{}", self.field_matcher)
     }
 }
 
@@ -78,16 +284,12 @@ impl FromStr for RecordMatcher {
 impl BitAnd for RecordMatcher {
     type Output = Self;
     fn bitand(self, rhs: Self) -> Self::Output {
-        let matcher_str =
-            format!("({}) && ({})", self.matcher_str, rhs.matcher_str);
-
         RecordMatcher {
             field_matcher: FieldMatcher::Composite {
                 lhs: Box::new(self.field_matcher),
                 op: BooleanOp::And,
                 rhs: Box::new(rhs.field_matcher),
             },
-            matcher_str,
         }
     }
 }
@@ -95,16 +297,25 @@ impl BitAnd for RecordMatcher {
 impl BitOr for RecordMatcher {
     type Output = Self;
     fn bitor(self, rhs: Self) -> Self::Output {
-        let matcher_str =
-            format!("({}) || ({})", self.matcher_str, rhs.matcher_str);
-
         RecordMatcher {
             field_matcher: FieldMatcher::Composite {
                 lhs: Box::new(self.field_matcher),
                 op: BooleanOp::Or,
                 rhs: Box::new(rhs.field_matcher),
             },
-            matcher_str,
+        }
+    }
+}
+
+impl BitXor for RecordMatcher {
+    type Output = Self;
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        RecordMatcher {
+            field_matcher: FieldMatcher::Composite {
+                lhs: Box::new(self.field_matcher),
+                op: BooleanOp::Xor,
+                rhs: Box::new(rhs.field_matcher),
+            },
         }
     }
 }
@@ -117,11 +328,22 @@ impl Not for RecordMatcher {
             field_matcher: FieldMatcher::Not(Box::new(
                 self.field_matcher,
             )),
-            matcher_str: format!("!({})", self.matcher_str),
         }
     }
 }
 
+#[cfg(feature = "serde")]
+impl Serialize for RecordMatcher {
+    /// Serializes the matcher as a (canonical) expression string. Use
+    /// [RecordMatcher::field_matcher] to serialize the syntax tree.
+    fn serialize<S: Serializer>(
+        &self,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
 #[cfg(feature = "serde")]
 impl<'de> Deserialize<'de> for RecordMatcher {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -0,0 +1,180 @@
+//! Holding blocks (local blocks and copies) of a PICA+ record.
+
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use pica_record::Field;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::ParseMatcherError;
+
+/// The scope of a matcher or a selection within the holdings of a
+/// record.
+///
+/// A local block (level 1) begins with the field `101@` (or with a
+/// level 1 field following a field of another level) and contains the
+/// level 2 fields that follow it. A copy consists of the level 1
+/// fields of a local block and the level 2 fields of the block with
+/// the same occurrence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    Local,
+    Copy,
+}
+
+impl Scope {
+    /// Splits the fields of a record into the blocks of the scope. The
+    /// `key` function returns the tag and the occurrence of a field.
+    /// Fields of level 0 don't belong to any block.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use pica_matcher::Scope;
+    /// use pica_record::{FieldRef, RecordRef};
+    ///
+    /// fn key<'a>(field: &'a FieldRef) -> (&'a [u8], Option<&'a [u8]>) {
+    ///     (field.tag().as_ref(), field.occurrence().map(|o| o.as_ref()))
+    /// }
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> anyhow::Result<()> {
+    ///     let record = RecordRef::new(vec![
+    ///         ("003@", None, vec![('0', "123456789X")]),
+    ///         ("101@", None, vec![('a', "1")]),
+    ///         ("209A", Some("01"), vec![('a', "X")]),
+    ///         ("209A", Some("02"), vec![('a', "Y")]),
+    ///         ("101@", None, vec![('a', "2")]),
+    ///         ("209A", Some("01"), vec![('a', "Z")]),
+    ///     ]);
+    ///
+    ///     assert_eq!(Scope::Local.blocks(record.iter(), key).len(), 2);
+    ///     assert_eq!(Scope::Copy.blocks(record.iter(), key).len(), 3);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn blocks<'a, F>(
+        &self,
+        fields: impl IntoIterator<Item = &'a F>,
+        key: impl Fn(&F) -> (&[u8], Option<&[u8]>),
+    ) -> Vec<Vec<&'a F>> {
+        let level = |field: &F| key(field).0.first().copied();
+        let mut blocks: Vec<Vec<&F>> = vec![];
+        let mut prev_level = None;
+
+        for field in fields {
+            let (tag, _) = key(field);
+            let current = level(field);
+
+            match current {
+                Some(b'1')
+                    if prev_level != current || tag == b"101@" =>
+                {
+                    blocks.push(vec![field])
+                }
+                Some(b'1' | b'2') => match blocks.last_mut() {
+                    Some(block) => block.push(field),
+                    None => blocks.push(vec![field]),
+                },
+                _ => (),
+            }
+
+            prev_level = current;
+        }
+
+        if *self == Self::Local {
+            return blocks;
+        }
+
+        let mut copies = vec![];
+        for block in blocks {
+            let (local, fields): (Vec<&F>, Vec<&F>) = block
+                .into_iter()
+                .partition(|field| level(field) == Some(b'1'));
+
+            let mut block: Vec<(Option<&[u8]>, Vec<&F>)> = vec![];
+            for field in fields {
+                let (_, occurrence) = key(field);
+                match block.iter_mut().find(|(o, _)| *o == occurrence) {
+                    Some((_, copy)) => copy.push(field),
+                    None => {
+                        let mut copy = local.clone();
+                        copy.push(field);
+                        block.push((occurrence, copy));
+                    }
+                }
+            }
+
+            copies.extend(block.into_iter().map(|(_, copy)| copy));
+        }
+
+        copies
+    }
+}
+
+/// Returns the tag and the occurrence of a field (see [Scope::blocks]).
+pub(crate) fn field_key<T: AsRef<[u8]>>(
+    field: &Field<T>,
+) -> (&[u8], Option<&[u8]>) {
+    (
+        field.tag().as_ref(),
+        field.occurrence().map(|occurrence| occurrence.as_ref()),
+    )
+}
+
+impl FromStr for Scope {
+    type Err = ParseMatcherError;
+
+    /// Parses a scope from a string slice (`local` or `copy`).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use pica_matcher::Scope;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> anyhow::Result<()> {
+    ///     assert_eq!("copy".parse::<Scope>()?, Scope::Copy);
+    ///     assert!("title".parse::<Scope>().is_err());
+    ///     Ok(())
+    /// }
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "local" => Ok(Self::Local),
+            "copy" => Ok(Self::Copy),
+            _ => Err(ParseMatcherError::InvalidScope(s.to_string())),
+        }
+    }
+}
+
+impl Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Local => write!(f, "local"),
+            Self::Copy => write!(f, "copy"),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Scope {
+    fn serialize<S: Serializer>(
+        &self,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Scope {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Self::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
@@ -1,39 +1,80 @@
 //! Matcher that works on PICA+ [Subfields](pica_record::Subfield).
 
-use std::ops::{BitAnd, BitOr};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt::{self, Display, Write};
+use std::iter;
+use std::ops::{BitAnd, BitOr, BitXor};
+use std::sync::RwLock;
 
 use bstr::{BString, ByteSlice};
+use chrono::format::{Item, StrftimeItems};
+use chrono::NaiveDate;
 use nom::branch::alt;
-use nom::bytes::complete::tag;
+use nom::bytes::complete::{is_not, tag};
 use nom::character::complete::{char, digit1};
 use nom::combinator::{
-    all_consuming, cut, map, map_res, opt, value, verify,
+    all_consuming, cut, map, map_opt, map_res, opt, recognize, success,
+    value, verify,
 };
-use nom::multi::{many1, separated_list1};
-use nom::sequence::{delimited, preceded, terminated, tuple};
+use nom::error::context;
+use nom::multi::{many0, many1, separated_list1};
+use nom::sequence::{delimited, pair, preceded, terminated, tuple};
 use nom::Finish;
-use pica_record::parser::{parse_subfield_code, ParseResult};
 use pica_record::Subfield;
-use regex::bytes::RegexBuilder;
-use regex::Regex;
-use strsim::normalized_levenshtein;
+use regex::bytes::{Regex, RegexBuilder};
+#[cfg(feature = "serde")]
+use serde::ser::{Serialize, SerializeMap, Serializer};
 
 use crate::common::{
-    parse_relational_op_str, parse_relational_op_usize, parse_string,
-    ws, BooleanOp, RelationalOp,
+    parse_implies_op, parse_quantifier, parse_relational_op_str,
+    parse_relational_op_usize, parse_string, parse_subfield_code,
+    parse_usize, parse_xor_op, ws, BooleanOp, ParseResult, Quantifier,
+    Quoted, RelationalOp,
+};
+use crate::definitions::{parse_reference, Definitions};
+use crate::list::{ListCache, LoadLists};
+use crate::trace::{fmt_subfield, Trace};
+use crate::{
+    MatcherOptions, NormalizationForm, ParseContext, ParseMatcherError,
+    SyntaxError,
 };
-use crate::{MatcherOptions, ParseMatcherError};
 
 const SUBFIELD_CODES: &str =
     "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
 
 /// Parse a list of subfield codes
-fn parse_subfield_codes(i: &[u8]) -> ParseResult<Vec<char>> {
-    alt((
-        delimited(char('['), many1(parse_subfield_code), char(']')),
-        map(parse_subfield_code, |code| vec![code]),
-        value(SUBFIELD_CODES.chars().collect(), char('*')),
-    ))(i)
+pub(crate) fn parse_subfield_codes(i: &[u8]) -> ParseResult<Vec<char>> {
+    context(
+        "subfield code",
+        alt((
+            delimited(char('['), many1(parse_subfield_code), char(']')),
+            map(parse_subfield_code, |code| vec![code]),
+            value(SUBFIELD_CODES.chars().collect(), char('*')),
+        )),
+    )(i)
+}
+
+/// A list of subfield codes, which is formatted as a single code (`a`),
+/// a list of codes (`[ab]`) or as a wildcard (`*`).
+pub(crate) struct SubfieldCodes<'a>(pub(crate) &'a [char]);
+
+impl Display for SubfieldCodes<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            [code] => write!(f, "{code}"),
+            codes
+                if SUBFIELD_CODES
+                    .chars()
+                    .all(|code| codes.contains(&code)) =>
+            {
+                write!(f, "*")
+            }
+            codes => {
+                write!(f, "[{}]", codes.iter().collect::<String>())
+            }
+        }
+    }
 }
 
 /// A trait that provides the basic matcher API.
@@ -47,6 +88,45 @@ pub trait Matcher {
     ) -> bool;
 }
 
+/// Parse an optional quantifier (`ALL` or `ANY`), which precedes the
+/// subfield codes of a matcher. The default quantifier is `ANY`.
+fn parse_optional_quantifier(i: &[u8]) -> ParseResult<Quantifier> {
+    map(opt(ws(parse_quantifier)), Option::unwrap_or_default)(i)
+}
+
+/// Writes the quantifier of a matcher, unless it's the default
+/// quantifier (`ANY`) or the alternate flag (`{:#}`) is set. The
+/// alternate form is used by the dot notation of a field matcher,
+/// which writes the quantifier in front of the field.
+fn fmt_quantifier(
+    quantifier: &Quantifier,
+    f: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    if *quantifier == Quantifier::All && !f.alternate() {
+        write!(f, "{quantifier} ")?;
+    }
+
+    Ok(())
+}
+
+/// Returns `true` if the predicate holds for at least one value (`ANY`)
+/// or for all values (`ALL`) of the subfields with one of the given
+/// codes. If there is no such subfield, the result is `false` for both
+/// quantifiers.
+fn quantify<'a, T: AsRef<[u8]> + 'a>(
+    quantifier: &Quantifier,
+    codes: &[char],
+    subfields: impl IntoIterator<Item = &'a Subfield<T>>,
+    predicate: impl FnMut(&[u8]) -> bool,
+) -> bool {
+    let values = subfields
+        .into_iter()
+        .filter(|subfield| codes.contains(&subfield.code()))
+        .map(|subfield| subfield.value().as_ref());
+
+    quantifier.eval(values, predicate)
+}
+
 /// A matcher that checks if a subfield exists.
 ///
 /// This matcher can be used to determine if a single subfield or a list
@@ -107,8 +187,10 @@ impl ExistsMatcher {
     pub fn new(data: &str) -> Result<Self, ParseMatcherError> {
         all_consuming(parse_exists_matcher)(data.as_bytes())
             .finish()
-            .map_err(|_| {
-                ParseMatcherError::InvalidSubfieldMatcher(data.into())
+            .map_err(|e| {
+                ParseMatcherError::InvalidSubfieldMatcher(
+                    SyntaxError::new(data, e),
+                )
             })
             .map(|(_, matcher)| matcher)
     }
@@ -128,36 +210,340 @@ impl Matcher for ExistsMatcher {
     }
 }
 
-/// A matcher that checks relations between (string) values.
-///
-/// This matcher provides basic relational operations between string
-/// values; the following operators
+impl Display for ExistsMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}?", SubfieldCodes(&self.codes))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for ExistsMatcher {
+    fn serialize<S: Serializer>(
+        &self,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut map = s.serialize_map(Some(2))?;
+        map.serialize_entry("type", "exists")?;
+        map.serialize_entry("codes", &self.codes)?;
+        map.end()
+    }
+}
+
+/// A matcher that checks relations between values.
 ///
-/// This matcher provides the following relational operators:
+/// This matcher provides the following relational operators for
+/// string values:
 /// * Equal (`==`)
 /// * Not Equal (`!=`)
 /// * StartsWith (`=^`)
 /// * EndsWith (`=$`)
 /// * Similar (`=*`)
+/// * Contains (`=?`)
+/// * ContainsWord (`=%`)
+///
+/// If the right-hand side is an (unquoted) integer, the subfield value
+/// is parsed as an integer and compared numerically by one of the
+/// operators `==`, `!=`, `>=`, `>`, `<=` or `<`. The same operators
+/// can be used to compare dates: the subfield value is parsed using
+/// the format of the `date` function (e.g. `date(0, '%d-%m-%y')`) and
+/// compared against a date in the format `yyyy-mm-dd`. The format must
+/// describe a complete date (e.g. `%Y` alone is rejected). Subfield
+/// values that can't be parsed are unequal to the right-hand side: they
+/// match the `!=` operator and none of the others. The `len` function
+/// compares the number of characters of a subfield value (e.g.
+/// `len(a) > 200`).
 #[derive(Debug, PartialEq, Eq)]
 pub struct RelationMatcher {
+    quantifier: Quantifier,
     codes: Vec<char>,
-    op: RelationalOp,
-    value: BString,
+    pub(crate) op: RelationalOp,
+    value: Value,
 }
 
-/// Parse a relational expression
-fn parse_relation_matcher(i: &[u8]) -> ParseResult<RelationMatcher> {
+/// The right-hand side of a relational expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Value {
+    String(BString),
+    Integer(i64),
+    Date { format: String, date: NaiveDate },
+    Length(usize),
+}
+
+/// Parse a signed integer.
+fn parse_integer(i: &[u8]) -> ParseResult<i64> {
+    map_res(recognize(pair(opt(char('-')), digit1)), |s| {
+        std::str::from_utf8(s).unwrap().parse::<i64>()
+    })(i)
+}
+
+/// Returns `true` if a complete date (year, month and day) can be
+/// parsed with the given format. The check formats a sample date and
+/// parses it back, which rejects invalid format specifiers as well as
+/// formats like `%Y` that never yield a date.
+fn is_date_format(format: &str) -> bool {
+    let sample = NaiveDate::from_ymd_opt(2021, 12, 31)
+        .and_then(|date| date.and_hms_opt(23, 59, 59))
+        .unwrap()
+        .and_utc();
+
+    if StrftimeItems::new(format).any(|item| item == Item::Error) {
+        return false;
+    }
+
+    let mut value = String::new();
+    write!(value, "{}", sample.format(format)).is_ok()
+        && NaiveDate::parse_from_str(&value, format)
+            == Ok(sample.date_naive())
+}
+
+/// Parse a date format string. The format must not contain invalid
+/// format specifiers and must describe a complete date.
+fn parse_date_format(i: &[u8]) -> ParseResult<String> {
+    context(
+        "date format",
+        verify(parse_string, |format: &str| is_date_format(format)),
+    )(i)
+}
+
+/// Parse a date in the format `yyyy-mm-dd`.
+fn parse_date(i: &[u8]) -> ParseResult<NaiveDate> {
+    context(
+        "date (yyyy-mm-dd)",
+        map_opt(parse_string, |s| {
+            NaiveDate::parse_from_str(&s, "%Y-%m-%d").ok()
+        }),
+    )(i)
+}
+
+/// Parse a date relation, which starts with a call of the `date`
+/// function. The given parser is used to parse the first argument of
+/// the function, which precedes the subfield code(s).
+pub(crate) fn parse_date_relation<'a, O, F>(
+    prefix: F,
+) -> impl FnMut(&'a [u8]) -> ParseResult<'a, (O, RelationMatcher)>
+where
+    F: FnMut(&'a [u8]) -> ParseResult<'a, O>,
+{
+    map(
+        tuple((
+            preceded(ws(tag("date(")), prefix),
+            ws(parse_subfield_codes),
+            preceded(char(','), ws(parse_date_format)),
+            ws(char(')')),
+            ws(parse_relational_op_usize),
+            ws(parse_date),
+        )),
+        |(o, codes, format, _, op, date)| {
+            (
+                o,
+                RelationMatcher {
+                    quantifier: Quantifier::Any,
+                    codes,
+                    op,
+                    value: Value::Date { format, date },
+                },
+            )
+        },
+    )
+}
+
+/// Parse a length relation, which starts with a call of the `len`
+/// function. The given parser is used to parse the first argument of
+/// the function, which precedes the subfield code(s).
+pub(crate) fn parse_length_relation<'a, O, F>(
+    prefix: F,
+) -> impl FnMut(&'a [u8]) -> ParseResult<'a, (O, RelationMatcher)>
+where
+    F: FnMut(&'a [u8]) -> ParseResult<'a, O>,
+{
     map(
         tuple((
+            preceded(ws(tag("len(")), prefix),
             ws(parse_subfield_codes),
-            ws(parse_relational_op_str),
-            map(ws(parse_string), BString::from),
+            ws(char(')')),
+            ws(parse_relational_op_usize),
+            ws(parse_usize),
         )),
-        |(codes, op, value)| RelationMatcher { codes, op, value },
+        |(o, codes, _, op, value)| {
+            (
+                o,
+                RelationMatcher {
+                    quantifier: Quantifier::Any,
+                    codes,
+                    op,
+                    value: Value::Length(value),
+                },
+            )
+        },
+    )
+}
+
+/// Parse a relational expression
+fn parse_relation_matcher(i: &[u8]) -> ParseResult<RelationMatcher> {
+    map(
+        pair(
+            parse_optional_quantifier,
+            alt((
+                map(
+                    tuple((
+                        ws(parse_subfield_codes),
+                        ws(parse_relational_op_str),
+                        map(ws(parse_string), |s| {
+                            Value::String(s.into())
+                        }),
+                    )),
+                    |(codes, op, value)| RelationMatcher {
+                        quantifier: Quantifier::Any,
+                        codes,
+                        op,
+                        value,
+                    },
+                ),
+                map(
+                    tuple((
+                        ws(parse_subfield_codes),
+                        ws(parse_relational_op_usize),
+                        map(ws(parse_integer), Value::Integer),
+                    )),
+                    |(codes, op, value)| RelationMatcher {
+                        quantifier: Quantifier::Any,
+                        codes,
+                        op,
+                        value,
+                    },
+                ),
+                map(
+                    parse_date_relation(success(())),
+                    |(_, matcher)| matcher,
+                ),
+                map(
+                    parse_length_relation(success(())),
+                    |(_, matcher)| matcher,
+                ),
+            )),
+        ),
+        |(quantifier, matcher)| RelationMatcher {
+            quantifier,
+            ..matcher
+        },
     )(i)
 }
 
+/// Returns `true` if the relation defined by the operator exists
+/// between both (ordered) values.
+fn compare_ord<T: Ord>(op: &RelationalOp, lhs: T, rhs: T) -> bool {
+    match op {
+        RelationalOp::Eq => lhs == rhs,
+        RelationalOp::Ne => lhs != rhs,
+        RelationalOp::Ge => lhs >= rhs,
+        RelationalOp::Gt => lhs > rhs,
+        RelationalOp::Le => lhs <= rhs,
+        RelationalOp::Lt => lhs < rhs,
+        _ => unreachable!(),
+    }
+}
+
+/// Returns `true` if both values are equal. Both values are
+/// normalized according to the options first (see
+/// [MatcherOptions::normalize]).
+fn compare(lhs: &[u8], rhs: &[u8], options: &MatcherOptions) -> bool {
+    options.normalize(lhs) == options.normalize(rhs)
+}
+
+/// Returns `true` if the right-hand side is a prefix of the left-hand
+/// side, otherwise `false`. Both values are normalized according to
+/// the options first.
+fn starts_with(
+    lhs: &[u8],
+    rhs: &[u8],
+    options: &MatcherOptions,
+) -> bool {
+    options.normalize(lhs).starts_with(&options.normalize(rhs))
+}
+
+/// Returns `true` if the right-hand side is a suffix of the left-hand
+/// side, otherwise `false`. Both values are normalized according to
+/// the options first.
+fn ends_with(lhs: &[u8], rhs: &[u8], options: &MatcherOptions) -> bool {
+    options.normalize(lhs).ends_with(&options.normalize(rhs))
+}
+
+/// Returns `true` if both values are similar. The similarity score is
+/// determined by the similarity metric of the options (the normalized
+/// levenshtein distance by default). Both values are normalized
+/// according to the options first.
+fn is_similar(
+    lhs: &[u8],
+    rhs: &[u8],
+    options: &MatcherOptions,
+) -> bool {
+    let score = options.strsim_metric.score(
+        &options.normalize(rhs).to_str_lossy(),
+        &options.normalize(lhs).to_str_lossy(),
+    );
+
+    score > options.strsim_threshold
+}
+
+/// Returns `true` if the right-hand side is a substring of the
+/// left-hand side, otherwise `false`. Both values are normalized
+/// according to the options first.
+fn contains(lhs: &[u8], rhs: &[u8], options: &MatcherOptions) -> bool {
+    options.normalize(lhs).contains_str(options.normalize(rhs))
+}
+
+/// Returns `true` if the words of the right-hand side occur as a
+/// contiguous sequence of words in the left-hand side (e.g. `Ada
+/// Lovelace` is contained in `Augusta Ada Lovelace`, but `Love` isn't).
+/// Both values are normalized according to the options first.
+fn contains_word(
+    lhs: &[u8],
+    rhs: &[u8],
+    options: &MatcherOptions,
+) -> bool {
+    let lhs = options.normalize(lhs);
+    let rhs = options.normalize(rhs);
+
+    let haystack: Vec<&str> = lhs.words().collect();
+    let needle: Vec<&str> = rhs.words().collect();
+
+    !needle.is_empty()
+        && haystack
+            .windows(needle.len())
+            .any(|window| window == needle.as_slice())
+}
+
+/// Returns `true` if the relation defined by the operator exists
+/// between two values. The values are compared as integers by an
+/// ordering operator (`>=`, `>`, `<=` or `<`), if both values are
+/// integers; otherwise they are compared as strings.
+pub(crate) fn relate(
+    op: &RelationalOp,
+    lhs: &[u8],
+    rhs: &[u8],
+    options: &MatcherOptions,
+) -> bool {
+    match op {
+        RelationalOp::Eq => compare(lhs, rhs, options),
+        RelationalOp::Ne => !compare(lhs, rhs, options),
+        RelationalOp::StartsWith => starts_with(lhs, rhs, options),
+        RelationalOp::EndsWith => ends_with(lhs, rhs, options),
+        RelationalOp::Similar => is_similar(lhs, rhs, options),
+        RelationalOp::Contains => contains(lhs, rhs, options),
+        RelationalOp::ContainsWord => contains_word(lhs, rhs, options),
+        _ => {
+            let parse = |value: &[u8]| {
+                value.to_str().ok().and_then(|s| s.parse::<i64>().ok())
+            };
+
+            match (parse(lhs), parse(rhs)) {
+                (Some(lhs), Some(rhs)) => compare_ord(op, lhs, rhs),
+                _ => compare_ord(op, lhs, rhs),
+            }
+        }
+    }
+}
+
 impl RelationMatcher {
     /// Create a new relation matcher from a string slice.
     ///
@@ -181,140 +567,231 @@ impl RelationMatcher {
     ///         &Default::default()
     ///     ));
     ///
+    ///     let matcher = RelationMatcher::new("a > 999")?;
+    ///     assert!(matcher.is_match(
+    ///         &SubfieldRef::new('a', "1000"),
+    ///         &Default::default()
+    ///     ));
+    ///
+    ///     let matcher =
+    ///         RelationMatcher::new("date(0, '%d-%m-%y') >= '2020-01-01'")?;
+    ///     assert!(matcher.is_match(
+    ///         &SubfieldRef::new('0', "01-07-21"),
+    ///         &Default::default()
+    ///     ));
+    ///
     ///     Ok(())
     /// }
     /// ```
     pub fn new(data: &str) -> Result<Self, ParseMatcherError> {
         all_consuming(parse_relation_matcher)(data.as_bytes())
             .finish()
-            .map_err(|_| {
-                ParseMatcherError::InvalidSubfieldMatcher(data.into())
+            .map_err(|e| {
+                ParseMatcherError::InvalidSubfieldMatcher(
+                    SyntaxError::new(data, e),
+                )
             })
             .map(|(_, matcher)| matcher)
     }
 
-    /// Returns `true` if the given value is equal to the matcher's
-    /// value. If the `case_ignore` flag is set, both strings will be
-    /// converted to lowercase first.
-    fn compare(&self, value: &[u8], options: &MatcherOptions) -> bool {
-        if options.case_ignore {
-            self.value.to_lowercase() == value.to_lowercase()
-        } else {
-            self.value == value
-        }
-    }
-
-    /// Returns `true` if the given values is a prefix of the matcher's
-    /// value, otherwise `false`. If the `case_ignore` flag ist set,
-    /// both strings will be converted to lowercase first.
-    fn starts_with(
+    /// Returns `true` if the value and the matcher's value are
+    /// related.
+    fn is_related(
         &self,
         value: &[u8],
         options: &MatcherOptions,
     ) -> bool {
-        if options.case_ignore {
-            value.to_lowercase().starts_with(&self.value.to_lowercase())
-        } else {
-            value.starts_with(&self.value)
+        match self.value {
+            Value::String(ref rhs) => {
+                relate(&self.op, value, rhs, options)
+            }
+            Value::Integer(rhs) => value
+                .to_str()
+                .ok()
+                .and_then(|s| s.parse::<i64>().ok())
+                .map_or(self.op == RelationalOp::Ne, |lhs| {
+                    compare_ord(&self.op, lhs, rhs)
+                }),
+            Value::Date {
+                ref format,
+                date: rhs,
+            } => value
+                .to_str()
+                .ok()
+                .and_then(|s| NaiveDate::parse_from_str(s, format).ok())
+                .map_or(self.op == RelationalOp::Ne, |lhs| {
+                    compare_ord(&self.op, lhs, rhs)
+                }),
+            Value::Length(rhs) => {
+                let lhs =
+                    options.normalize_unicode(value).chars().count();
+                compare_ord(&self.op, lhs, rhs)
+            }
         }
     }
+}
 
-    /// Returns `true` if the given values is a suffix of the matcher's
-    /// value, otherwise `false`. If the `case_ignore` flag ist set,
-    /// both strings will be converted to lowercase first.
-    fn ends_with(
+impl Matcher for RelationMatcher {
+    /// Returns true if at least one subfield (or all subfields, if the
+    /// quantifier is `ALL`) is found, when the subfield's value and the
+    /// matcher value are related. The two values are related iff the
+    /// relation defined by the operator exists.
+    fn is_match<'a, T: AsRef<[u8]> + 'a>(
         &self,
-        value: &[u8],
+        subfields: impl IntoIterator<Item = &'a Subfield<T>> + Clone,
         options: &MatcherOptions,
     ) -> bool {
-        if options.case_ignore {
-            value.to_lowercase().ends_with(&self.value.to_lowercase())
-        } else {
-            value.ends_with(&self.value)
+        quantify(&self.quantifier, &self.codes, subfields, |value| {
+            self.is_related(value, options)
+        })
+    }
+}
+
+impl Display for RelationMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_quantifier(&self.quantifier, f)?;
+        let codes = SubfieldCodes(&self.codes);
+        let op = &self.op;
+
+        match self.value {
+            Value::String(ref value) => {
+                let value = value.to_str_lossy();
+                write!(f, "{codes} {op} {}", Quoted(&value))
+            }
+            Value::Integer(value) => write!(f, "{codes} {op} {value}"),
+            Value::Date {
+                ref format,
+                ref date,
+            } => {
+                let date = date.format("%Y-%m-%d").to_string();
+                write!(
+                    f,
+                    "date({codes}, {}) {op} {}",
+                    Quoted(format),
+                    Quoted(&date)
+                )
+            }
+            Value::Length(value) => {
+                write!(f, "len({codes}) {op} {value}")
+            }
         }
     }
+}
 
-    /// Returns `true` if the given value is similar to the matcher's
-    /// value. The similarity score is determined by calculating the
-    /// normalized levenshtein distance between both strings. If the
-    /// `case_ignore` flag is set, both strings will be converted to
-    /// lowercase first.
-    fn is_similar(
+#[cfg(feature = "serde")]
+impl Serialize for RelationMatcher {
+    /// Serializes the matcher. The value of a date comparison is
+    /// serialized as `yyyy-mm-dd` together with the `format` of the
+    /// subfield values.
+    fn serialize<S: Serializer>(
         &self,
-        value: &[u8],
-        options: &MatcherOptions,
-    ) -> bool {
-        let score = if options.case_ignore {
-            normalized_levenshtein(
-                &self.value.to_string().to_lowercase(),
-                &value.to_str_lossy().to_lowercase(),
-            )
-        } else {
-            normalized_levenshtein(
-                &self.value.to_string(),
-                &value.to_str_lossy(),
-            )
-        };
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut map = s.serialize_map(None)?;
+        map.serialize_entry("type", "relation")?;
+        map.serialize_entry("quantifier", &self.quantifier)?;
+        map.serialize_entry("codes", &self.codes)?;
+        map.serialize_entry("op", &self.op)?;
+
+        match self.value {
+            Value::String(ref value) => {
+                map.serialize_entry("value", &value.to_str_lossy())?
+            }
+            Value::Integer(value) => {
+                map.serialize_entry("value", &value)?
+            }
+            Value::Date {
+                ref format,
+                ref date,
+            } => {
+                let date = date.format("%Y-%m-%d").to_string();
+                map.serialize_entry("value", &date)?;
+                map.serialize_entry("format", format)?;
+            }
+            Value::Length(value) => {
+                map.serialize_entry("value", &value)?;
+                map.serialize_entry("function", "len")?;
+            }
+        }
 
-        score > options.strsim_threshold
+        map.end()
     }
 }
 
-impl Matcher for RelationMatcher {
-    /// Returns true if at least one subfield is found, when the
-    /// subfield's value and the matcher value are related. The two
-    /// values are related iff the relation defined by the operator
-    /// exists.
-    fn is_match<'a, T: AsRef<[u8]> + 'a>(
-        &self,
-        subfields: impl IntoIterator<Item = &'a Subfield<T>> + Clone,
-        options: &MatcherOptions,
-    ) -> bool {
-        subfields
-            .into_iter()
-            .filter(|s| self.codes.contains(&s.code()))
-            .any(|subfield| {
-                let value = subfield.value().as_ref();
-                match self.op {
-                    RelationalOp::Eq => self.compare(value, options),
-                    RelationalOp::Ne => !self.compare(value, options),
-                    RelationalOp::StartsWith => {
-                        self.starts_with(value, options)
-                    }
-                    RelationalOp::EndsWith => {
-                        self.ends_with(value, options)
-                    }
-                    RelationalOp::Similar => {
-                        self.is_similar(value, options)
-                    }
-                    _ => unreachable!(),
-                }
-            })
+/// A regular expression, which is built once for case-sensitive and
+/// once for case-insensitive matching.
+#[derive(Debug)]
+pub(crate) struct CompiledRegex {
+    sensitive: Regex,
+    insensitive: Regex,
+}
+
+impl CompiledRegex {
+    pub(crate) fn new(pattern: &str) -> Result<Self, regex::Error> {
+        Ok(Self {
+            sensitive: Regex::new(pattern)?,
+            insensitive: RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()?,
+        })
+    }
+
+    /// Returns the regular expression for the case sensitivity of the
+    /// given options.
+    pub(crate) fn get(&self, options: &MatcherOptions) -> &Regex {
+        if options.is_case_insensitive() {
+            &self.insensitive
+        } else {
+            &self.sensitive
+        }
+    }
+}
+
+impl PartialEq for CompiledRegex {
+    fn eq(&self, other: &Self) -> bool {
+        self.sensitive.as_str() == other.sensitive.as_str()
     }
 }
 
+impl Eq for CompiledRegex {}
+
 /// A matcher that checks a subfield value against a regex.
+///
+/// The regular expression is built once, when the matcher is parsed.
+/// Unicode options (normalization, diacritics) are applied to the
+/// subfield values only, never to the pattern.
 #[derive(Debug, PartialEq, Eq)]
 pub struct RegexMatcher {
-    codes: Vec<char>,
-    pattern: String,
-    invert: bool,
+    pub(crate) quantifier: Quantifier,
+    pub(crate) codes: Vec<char>,
+    pub(crate) pattern: String,
+    pub(crate) regex: CompiledRegex,
+    pub(crate) invert: bool,
 }
 
 /// Parse a regex matcher expression
 fn parse_regex_matcher(i: &[u8]) -> ParseResult<RegexMatcher> {
     map(
         tuple((
+            parse_optional_quantifier,
             parse_subfield_codes,
             alt((
                 value(false, ws(tag("=~"))),
                 value(true, ws(tag("!~"))),
             )),
-            verify(parse_string, |x| Regex::new(x).is_ok()),
+            context(
+                "regular expression",
+                map_res(parse_string, |pattern| {
+                    CompiledRegex::new(&pattern)
+                        .map(|regex| (pattern, regex))
+                }),
+            ),
         )),
-        |(codes, invert, pattern)| RegexMatcher {
+        |(quantifier, codes, invert, (pattern, regex))| RegexMatcher {
+            quantifier,
             codes,
             pattern,
+            regex,
             invert,
         },
     )(i)
@@ -349,67 +826,193 @@ impl RegexMatcher {
     pub fn new(data: &str) -> Result<Self, ParseMatcherError> {
         all_consuming(parse_regex_matcher)(data.as_bytes())
             .finish()
-            .map_err(|_| {
-                ParseMatcherError::InvalidSubfieldMatcher(data.into())
+            .map_err(|e| {
+                ParseMatcherError::InvalidSubfieldMatcher(
+                    SyntaxError::new(data, e),
+                )
             })
             .map(|(_, matcher)| matcher)
     }
 }
 
 impl Matcher for RegexMatcher {
-    /// Returns true if at least one subfield value is found, that
-    /// matches against the regular expression.
+    /// Returns true if at least one subfield value (or all values, if
+    /// the quantifier is `ALL`) is found, that matches against the
+    /// regular expression.
     fn is_match<'a, T: AsRef<[u8]> + 'a>(
         &self,
         subfields: impl IntoIterator<Item = &'a Subfield<T>> + Clone,
         options: &MatcherOptions,
     ) -> bool {
-        let re = RegexBuilder::new(&self.pattern)
-            .case_insensitive(options.case_ignore)
-            .build()
-            .unwrap();
+        let re = self.regex.get(options);
 
-        subfields
-            .into_iter()
-            .filter(|s| self.codes.contains(&s.code()))
-            .any(|subfield| {
-                let mut result = re.is_match(subfield.value().as_ref());
-                if self.invert {
-                    result = !result;
-                }
+        quantify(&self.quantifier, &self.codes, subfields, |value| {
+            let value = options.normalize_unicode(value);
+            re.is_match(&value) != self.invert
+        })
+    }
+}
 
-                result
-            })
+impl Display for RegexMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_quantifier(&self.quantifier, f)?;
+        let op = if self.invert { "!~" } else { "=~" };
+        write!(
+            f,
+            "{} {op} {}",
+            SubfieldCodes(&self.codes),
+            Quoted(&self.pattern)
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for RegexMatcher {
+    fn serialize<S: Serializer>(
+        &self,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut map = s.serialize_map(Some(5))?;
+        map.serialize_entry("type", "regex")?;
+        map.serialize_entry("quantifier", &self.quantifier)?;
+        map.serialize_entry("codes", &self.codes)?;
+        map.serialize_entry("pattern", &self.pattern)?;
+        map.serialize_entry("invert", &self.invert)?;
+        map.end()
     }
 }
 
 /// A matcher that checks if a subfield value is in a predefined list.
+///
+/// The list is either given inline (`0 in ['abc', 'def']`) or read
+/// from a file (`0 in @codes.txt`, see [read_list](crate::read_list)).
+/// The parser only keeps the path of the file; the list is read once
+/// the whole expression has been parsed.
 #[derive(Debug, PartialEq, Eq)]
 pub struct InMatcher {
+    quantifier: Quantifier,
     codes: Vec<char>,
-    values: Vec<BString>,
+    values: HashSet<BString>,
+    folded: HashSet<BString>,
+    normalized: NormalizedValues,
+    file: Option<String>,
     invert: bool,
 }
 
+/// Characters, which can't be part of an unquoted path of a list file.
+const LIST_FILE_DELIMITERS: &str = " \t\r\n()[]{}&|!,";
+
+/// Parse the path of a list file (`@codes.txt` or `@'my codes.txt'`).
+fn parse_list_file(
+    i: &[u8],
+) -> ParseResult<(Option<String>, HashSet<BString>)> {
+    map(
+        preceded(
+            char('@'),
+            alt((
+                parse_string,
+                map_res(is_not(LIST_FILE_DELIMITERS), |s: &[u8]| {
+                    std::str::from_utf8(s).map(String::from)
+                }),
+            )),
+        ),
+        |path| (Some(path), HashSet::new()),
+    )(i)
+}
+
+/// Returns the lowercased values of a list.
+fn fold_values(values: &HashSet<BString>) -> HashSet<BString> {
+    values.iter().map(|v| v.to_lowercase().into()).collect()
+}
+
+/// The options, which determine the normalized form of a value.
+type NormalizationKey = (bool, bool, bool, Option<NormalizationForm>);
+
+/// The normalized values of a list and the options they belong to.
+type Normalized = Box<(NormalizationKey, HashSet<BString>)>;
+
+/// The values of a list in the normalized form of the options, which
+/// were used last. The values are normalized once per options instead
+/// of on every comparison.
+#[derive(Debug, Default)]
+struct NormalizedValues(RwLock<Option<Normalized>>);
+
+impl NormalizedValues {
+    /// Returns `true` if the list contains the (normalized) value.
+    fn contains(
+        &self,
+        values: &HashSet<BString>,
+        value: &[u8],
+        options: &MatcherOptions,
+    ) -> bool {
+        let key = (
+            options.case_ignore,
+            options.case_fold,
+            options.diacritic_ignore,
+            options.normalization,
+        );
+
+        if let Some(cached) = &*self.0.read().unwrap() {
+            if cached.0 == key {
+                return cached.1.contains(value);
+            }
+        }
+
+        let normalized: HashSet<BString> = values
+            .iter()
+            .map(|v| options.normalize(v).into_owned().into())
+            .collect();
+
+        let result = normalized.contains(value);
+        *self.0.write().unwrap() = Some(Box::new((key, normalized)));
+        result
+    }
+
+    /// Discards the normalized values.
+    fn clear(&mut self) {
+        *self.0.get_mut().unwrap() = None;
+    }
+}
+
+/// The normalized values are a cache, which doesn't affect equality.
+impl PartialEq for NormalizedValues {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for NormalizedValues {}
+
 /// Parse a in matcher expression.
 fn parse_in_matcher(i: &[u8]) -> ParseResult<InMatcher> {
     map(
         tuple((
+            parse_optional_quantifier,
             parse_subfield_codes,
             map(opt(ws(tag("not"))), |not| not.is_some()),
             ws(tag("in")),
-            delimited(
-                ws(char('[')),
-                separated_list1(
-                    ws(char(',')),
-                    map(parse_string, BString::from),
+            alt((
+                delimited(
+                    ws(char('[')),
+                    map(
+                        separated_list1(
+                            ws(char(',')),
+                            map(parse_string, BString::from),
+                        ),
+                        |values| (None, HashSet::from_iter(values)),
+                    ),
+                    ws(char(']')),
                 ),
-                ws(char(']')),
-            ),
+                ws(parse_list_file),
+            )),
         )),
-        |(codes, invert, _, values)| InMatcher {
+        |(quantifier, codes, invert, _, (file, values))| InMatcher {
+            quantifier,
             codes,
+            folded: fold_values(&values),
+            normalized: NormalizedValues::default(),
             values,
+            file,
             invert,
         },
     )(i)
@@ -438,56 +1041,165 @@ impl InMatcher {
     ///         &Default::default()
     ///     ));
     ///
+    ///     let matcher = InMatcher::new("0 in @tests/data/bbg.txt")?;
+    ///     assert!(matcher.is_match(
+    ///         &SubfieldRef::new('0', "Tp1"),
+    ///         &Default::default()
+    ///     ));
+    ///
     ///     Ok(())
     /// }
     /// ```
     pub fn new(data: &str) -> Result<Self, ParseMatcherError> {
         all_consuming(parse_in_matcher)(data.as_bytes())
             .finish()
-            .map_err(|_| {
-                ParseMatcherError::InvalidSubfieldMatcher(data.into())
+            .map_err(|e| {
+                ParseMatcherError::InvalidSubfieldMatcher(
+                    SyntaxError::new(data, e),
+                )
+            })
+            .and_then(|(_, matcher)| {
+                matcher.with_lists(&ParseContext::default())
             })
-            .map(|(_, matcher)| matcher)
+    }
+}
+
+impl LoadLists for InMatcher {
+    fn load_lists(
+        &mut self,
+        lists: &mut ListCache,
+    ) -> Result<(), ParseMatcherError> {
+        if let Some(ref path) = self.file {
+            self.values = lists.get(path)?.clone();
+            self.folded = fold_values(&self.values);
+            self.normalized.clear();
+        }
+
+        Ok(())
     }
 }
 
 impl Matcher for InMatcher {
-    /// Returns `true` if at least one subfield is found, where the
-    /// value is contained in the matcher list.
+    /// Returns `true` if at least one subfield (or all subfields, if
+    /// the quantifier is `ALL`) is found, where the value is contained
+    /// in the matcher list.
     fn is_match<'a, T: AsRef<[u8]> + 'a>(
         &self,
         subfields: impl IntoIterator<Item = &'a Subfield<T>> + Clone,
         options: &MatcherOptions,
     ) -> bool {
-        subfields
-            .into_iter()
-            .filter(|s| self.codes.contains(&s.code()))
-            .any(|subfield| {
-                let mut result =
-                    self.values.iter().any(|value: &BString| {
-                        if options.case_ignore {
-                            subfield.value().as_ref().to_lowercase()
-                                == value.to_lowercase()
-                        } else {
-                            subfield.value().as_ref() == value
-                        }
-                    });
+        quantify(&self.quantifier, &self.codes, subfields, |value| {
+            let result = if options.is_unicode_aware() {
+                let value = options.normalize(value);
+                self.normalized.contains(&self.values, &value, options)
+            } else if options.case_ignore {
+                self.folded.contains(value.to_lowercase().as_slice())
+            } else {
+                self.values.contains(value)
+            };
+
+            result != self.invert
+        })
+    }
+}
 
-                if self.invert {
-                    result = !result;
-                }
+impl InMatcher {
+    /// Returns the values of the list in sorted order.
+    fn sorted_values(&self) -> Vec<Cow<'_, str>> {
+        let mut values = self
+            .values
+            .iter()
+            .map(|value| value.to_str_lossy())
+            .collect::<Vec<_>>();
+        values.sort_unstable();
+        values
+    }
+}
 
-                result
-            })
+impl Display for InMatcher {
+    /// Formats the matcher with a reference to the list file, if the
+    /// list was read from a file, and with an inline list of the
+    /// (sorted) values otherwise.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_quantifier(&self.quantifier, f)?;
+        write!(f, "{}", SubfieldCodes(&self.codes))?;
+        if self.invert {
+            write!(f, " not")?;
+        }
+
+        if let Some(ref path) = self.file {
+            return if path.is_empty()
+                || path.contains(|c| LIST_FILE_DELIMITERS.contains(c))
+                || path.starts_with(['\'', '"'])
+            {
+                write!(f, " in @{}", Quoted(path))
+            } else {
+                write!(f, " in @{path}")
+            };
+        }
+
+        let values = self.sorted_values();
+        write!(f, " in [")?;
+        for (i, value) in values.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", Quoted(value))?;
+        }
+        write!(f, "]")
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for InMatcher {
+    /// Serializes the matcher with the (sorted) values of the list. If
+    /// the list was read from a file, the path of the file is included
+    /// too; the values are omitted, if the file hasn't been read (see
+    /// [RecordMatcher::parse](crate::RecordMatcher::parse)).
+    fn serialize<S: Serializer>(
+        &self,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut map = s.serialize_map(None)?;
+        map.serialize_entry("type", "in")?;
+        map.serialize_entry("quantifier", &self.quantifier)?;
+        map.serialize_entry("codes", &self.codes)?;
+        if self.file.is_none() || !self.values.is_empty() {
+            map.serialize_entry("values", &self.sorted_values())?;
+        }
+        if let Some(ref path) = self.file {
+            map.serialize_entry("file", path)?;
+        }
+        map.serialize_entry("invert", &self.invert)?;
+        map.end()
+    }
+}
+
+/// The right-hand side of a cardinality matcher, which is either a
+/// number or the number of other subfields (`#a > #[bc]`).
+#[derive(Debug, PartialEq, Eq)]
+enum Cardinality {
+    Value(usize),
+    Count(Vec<char>),
+}
+
+impl Display for Cardinality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Value(value) => write!(f, "{value}"),
+            Self::Count(codes) => {
+                write!(f, "#{}", SubfieldCodes(codes))
+            }
+        }
     }
 }
 
 /// A matcher that checks the number of occurrences of a subfield.
 #[derive(Debug, PartialEq, Eq)]
 pub struct CardinalityMatcher {
-    code: char,
+    codes: Vec<char>,
     op: RelationalOp,
-    value: usize,
+    rhs: Cardinality,
 }
 
 /// Parse a cardinality matcher expression.
@@ -498,14 +1210,18 @@ fn parse_cardinality_matcher(
         preceded(
             ws(char('#')),
             cut(tuple((
-                ws(parse_subfield_code),
+                ws(parse_subfield_codes),
                 ws(parse_relational_op_usize),
-                map_res(digit1, |s| {
-                    std::str::from_utf8(s).unwrap().parse::<usize>()
-                }),
+                alt((
+                    map(parse_usize, Cardinality::Value),
+                    map(
+                        preceded(ws(char('#')), parse_subfield_codes),
+                        Cardinality::Count,
+                    ),
+                )),
             ))),
         ),
-        |(code, op, value)| CardinalityMatcher { code, op, value },
+        |(codes, op, rhs)| CardinalityMatcher { codes, op, rhs },
     )(i)
 }
 
@@ -535,45 +1251,114 @@ impl CardinalityMatcher {
     ///         &Default::default()
     ///     ));
     ///
+    ///     let matcher = CardinalityMatcher::new("#[ab] == #0")?;
+    ///     assert!(matcher.is_match(
+    ///         vec![
+    ///             &SubfieldRef::new('a', "abc"),
+    ///             &SubfieldRef::new('0', "def"),
+    ///         ],
+    ///         &Default::default()
+    ///     ));
+    ///
     ///     Ok(())
     /// }
     /// ```
     pub fn new(data: &str) -> Result<Self, ParseMatcherError> {
         all_consuming(parse_cardinality_matcher)(data.as_bytes())
             .finish()
-            .map_err(|_| {
-                ParseMatcherError::InvalidSubfieldMatcher(data.into())
+            .map_err(|e| {
+                ParseMatcherError::InvalidSubfieldMatcher(
+                    SyntaxError::new(data, e),
+                )
             })
             .map(|(_, matcher)| matcher)
     }
+
+    /// Returns `true` if the subfield is counted by the matcher (on
+    /// either side).
+    fn is_counted<T: AsRef<[u8]>>(
+        &self,
+        subfield: &Subfield<T>,
+    ) -> bool {
+        let code = subfield.code();
+        self.codes.contains(&code)
+            || match self.rhs {
+                Cardinality::Count(ref codes) => codes.contains(&code),
+                Cardinality::Value(_) => false,
+            }
+    }
 }
 
 impl Matcher for CardinalityMatcher {
-    /// Returns true of number of fields with a code equal to the
-    /// matcher's code is `==`, `!=`, `>=`, `>`, `<=`, or `<` than the
-    /// matcher's value.
+    /// Returns true of number of subfields with a code equal to one
+    /// of the matcher's codes is `==`, `!=`, `>=`, `>`, `<=`, or `<`
+    /// than the matcher's value or the number of subfields with
+    /// another code.
     fn is_match<'a, T: AsRef<[u8]> + 'a>(
         &self,
         subfields: impl IntoIterator<Item = &'a Subfield<T>> + Clone,
         _options: &MatcherOptions,
     ) -> bool {
-        let count = subfields
-            .into_iter()
-            .filter(|&s| self.code == s.code())
-            .count();
+        let count = |codes: &[char]| {
+            subfields
+                .clone()
+                .into_iter()
+                .filter(|&s| codes.contains(&s.code()))
+                .count()
+        };
+
+        let lhs = count(&self.codes);
+        let rhs = match self.rhs {
+            Cardinality::Value(value) => value,
+            Cardinality::Count(ref codes) => count(codes),
+        };
 
         match self.op {
-            RelationalOp::Eq => count == self.value,
-            RelationalOp::Ne => count != self.value,
-            RelationalOp::Ge => count >= self.value,
-            RelationalOp::Gt => count > self.value,
-            RelationalOp::Le => count <= self.value,
-            RelationalOp::Lt => count < self.value,
+            RelationalOp::Eq => lhs == rhs,
+            RelationalOp::Ne => lhs != rhs,
+            RelationalOp::Ge => lhs >= rhs,
+            RelationalOp::Gt => lhs > rhs,
+            RelationalOp::Le => lhs <= rhs,
+            RelationalOp::Lt => lhs < rhs,
             _ => unreachable!(),
         }
     }
 }
 
+impl Display for CardinalityMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#{} {} {}",
+            SubfieldCodes(&self.codes),
+            self.op,
+            self.rhs
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for CardinalityMatcher {
+    fn serialize<S: Serializer>(
+        &self,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut map = s.serialize_map(Some(4))?;
+        map.serialize_entry("type", "cardinality")?;
+        map.serialize_entry("codes", &self.codes)?;
+        map.serialize_entry("op", &self.op)?;
+        match self.rhs {
+            Cardinality::Value(ref value) => {
+                map.serialize_entry("value", value)?
+            }
+            Cardinality::Count(ref codes) => {
+                map.serialize_entry("rhs", codes)?
+            }
+        }
+        map.end()
+    }
+}
+
 /// A matcher that checks for the singleton matcher.
 ///
 /// This matcher combines all atomic, singleton matcher into a new
@@ -622,10 +1407,67 @@ impl SingletonMatcher {
     pub fn new(data: &str) -> Result<Self, ParseMatcherError> {
         all_consuming(parse_singleton_matcher)(data.as_bytes())
             .finish()
-            .map_err(|_| {
-                ParseMatcherError::InvalidSubfieldMatcher(data.into())
+            .map_err(|e| {
+                ParseMatcherError::InvalidSubfieldMatcher(
+                    SyntaxError::new(data, e),
+                )
             })
-            .map(|(_, matcher)| matcher)
+            .and_then(|(_, matcher)| {
+                matcher.with_lists(&ParseContext::default())
+            })
+    }
+
+    /// Returns `true` if the matcher compares the result of a function
+    /// (`date` or `len`) of the subfield values (see [RelationMatcher]).
+    pub(crate) fn is_function_relation(&self) -> bool {
+        matches!(
+            self,
+            Self::Relation(RelationMatcher {
+                value: Value::Date { .. } | Value::Length(_),
+                ..
+            })
+        )
+    }
+
+    /// Returns the quantifier of the matcher. Matchers without a
+    /// quantifier (existence and cardinality) return `ANY`.
+    pub(crate) fn quantifier(&self) -> Quantifier {
+        match self {
+            Self::In(m) => m.quantifier.clone(),
+            Self::Regex(m) => m.quantifier.clone(),
+            Self::Relation(m) => m.quantifier.clone(),
+            Self::Cardinality(_) | Self::Exists(_) => Quantifier::Any,
+        }
+    }
+
+    /// Sets the quantifier of the matcher, if the matcher supports
+    /// quantifiers.
+    pub(crate) fn set_quantifier(&mut self, quantifier: Quantifier) {
+        match self {
+            Self::In(m) => m.quantifier = quantifier,
+            Self::Regex(m) => m.quantifier = quantifier,
+            Self::Relation(m) => m.quantifier = quantifier,
+            Self::Cardinality(_) | Self::Exists(_) => (),
+        }
+    }
+
+    /// Returns the evaluation trace of the matcher.
+    pub(crate) fn trace<'a, T: AsRef<[u8]> + 'a>(
+        &self,
+        subfields: impl IntoIterator<Item = &'a Subfield<T>> + Clone,
+        options: &MatcherOptions,
+    ) -> Trace {
+        let result = self.is_match(subfields.clone(), options);
+        let matches = subfields
+            .into_iter()
+            .filter(|subfield| match self {
+                Self::Cardinality(m) => m.is_counted(subfield),
+                _ => self.is_match(iter::once(*subfield), options),
+            })
+            .map(fmt_subfield)
+            .collect();
+
+        Trace::new(self.to_string(), result).with_matches(matches)
     }
 }
 
@@ -646,6 +1488,36 @@ impl Matcher for SingletonMatcher {
     }
 }
 
+impl Display for SingletonMatcher {
+    /// Formats the matcher. The alternate form (`{:#}`) omits the
+    /// quantifier.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cardinality(m) => m.fmt(f),
+            Self::Exists(m) => m.fmt(f),
+            Self::In(m) => m.fmt(f),
+            Self::Regex(m) => m.fmt(f),
+            Self::Relation(m) => m.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for SingletonMatcher {
+    fn serialize<S: Serializer>(
+        &self,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Cardinality(m) => m.serialize(s),
+            Self::Exists(m) => m.serialize(s),
+            Self::In(m) => m.serialize(s),
+            Self::Regex(m) => m.serialize(s),
+            Self::Relation(m) => m.serialize(s),
+        }
+    }
+}
+
 /// A matcher that allows grouping, negation and connecting of singleton
 /// matcher.
 #[derive(Debug, PartialEq, Eq)]
@@ -660,6 +1532,34 @@ pub enum SubfieldMatcher {
     },
 }
 
+impl LoadLists for SingletonMatcher {
+    fn load_lists(
+        &mut self,
+        lists: &mut ListCache,
+    ) -> Result<(), ParseMatcherError> {
+        match self {
+            Self::In(m) => m.load_lists(lists),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl LoadLists for SubfieldMatcher {
+    fn load_lists(
+        &mut self,
+        lists: &mut ListCache,
+    ) -> Result<(), ParseMatcherError> {
+        match self {
+            Self::Singleton(m) => m.load_lists(lists),
+            Self::Group(m) | Self::Not(m) => m.load_lists(lists),
+            Self::Composite { lhs, rhs, .. } => {
+                lhs.load_lists(lists)?;
+                rhs.load_lists(lists)
+            }
+        }
+    }
+}
+
 impl BitAnd for SubfieldMatcher {
     type Output = Self;
 
@@ -684,6 +1584,86 @@ impl BitOr for SubfieldMatcher {
     }
 }
 
+impl BitXor for SubfieldMatcher {
+    type Output = Self;
+
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        Self::Composite {
+            lhs: Box::new(self),
+            op: BooleanOp::Xor,
+            rhs: Box::new(rhs),
+        }
+    }
+}
+
+impl Display for SubfieldMatcher {
+    /// Formats the matcher in a canonical form. Redundant parentheses
+    /// are omitted; a composite operand is enclosed in parentheses
+    /// only if its operator binds weaker than the enclosing one (or
+    /// if it's the left operand of an implication).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Singleton(m) => write!(f, "{m}"),
+            Self::Group(m) => write!(f, "{m}"),
+            Self::Not(m) => match m.ungrouped() {
+                m @ (Self::Singleton(SingletonMatcher::Exists(_))
+                | Self::Not(_)) => write!(f, "!{m}"),
+                m => write!(f, "!({m})"),
+            },
+            Self::Composite { lhs, op, rhs } => {
+                let fmt_operand = |f: &mut fmt::Formatter<'_>,
+                                   m: &Self,
+                                   left: bool|
+                 -> fmt::Result {
+                    match m.ungrouped() {
+                        m @ Self::Composite { op: inner, .. }
+                            if inner.precedence() < op.precedence()
+                                || (left
+                                    && *inner
+                                        == BooleanOp::Implies) =>
+                        {
+                            write!(f, "({m})")
+                        }
+                        m => write!(f, "{m}"),
+                    }
+                };
+
+                fmt_operand(f, lhs, true)?;
+                write!(f, " {op} ")?;
+                fmt_operand(f, rhs, false)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for SubfieldMatcher {
+    /// Serializes the abstract syntax tree of the matcher. Groups are
+    /// omitted and chains of the same connective are flattened (e.g.
+    /// `{"type": "and", "operands": [...]}`).
+    fn serialize<S: Serializer>(
+        &self,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Singleton(m) => m.serialize(s),
+            Self::Group(m) => m.serialize(s),
+            Self::Not(m) => {
+                let mut map = s.serialize_map(Some(2))?;
+                map.serialize_entry("type", "not")?;
+                map.serialize_entry("operand", m)?;
+                map.end()
+            }
+            Self::Composite { op, .. } => {
+                let mut map = s.serialize_map(Some(2))?;
+                map.serialize_entry("type", op.name())?;
+                map.serialize_entry("operands", &self.operands(op))?;
+                map.end()
+            }
+        }
+    }
+}
+
 #[inline]
 fn parse_subfield_exists_matcher(
     i: &[u8],
@@ -700,30 +1680,37 @@ pub(crate) fn parse_subfield_singleton_matcher(
     map(parse_singleton_matcher, SubfieldMatcher::Singleton)(i)
 }
 
-fn parse_not_matcher(i: &[u8]) -> ParseResult<SubfieldMatcher> {
+fn parse_not_matcher<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, SubfieldMatcher> {
     map(
         preceded(
             ws(char('!')),
             cut(alt((
-                parse_group_matcher,
+                |i| parse_group_matcher(i, defs),
                 parse_subfield_exists_matcher,
-                parse_not_matcher,
+                |i| parse_named_matcher(i, defs),
+                |i| parse_not_matcher(i, defs),
             ))),
         ),
         |matcher| SubfieldMatcher::Not(Box::new(matcher)),
     )(i)
 }
 
-fn parse_group_matcher(i: &[u8]) -> ParseResult<SubfieldMatcher> {
+fn parse_group_matcher<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, SubfieldMatcher> {
     map(
         preceded(
             ws(char('(')),
             cut(terminated(
                 alt((
-                    parse_composite_matcher,
+                    |i| parse_composite_matcher(i, defs),
                     parse_subfield_singleton_matcher,
-                    parse_not_matcher,
-                    parse_group_matcher,
+                    |i| parse_not_matcher(i, defs),
+                    |i| parse_group_matcher(i, defs),
                 )),
                 ws(char(')')),
             )),
@@ -732,22 +1719,80 @@ fn parse_group_matcher(i: &[u8]) -> ParseResult<SubfieldMatcher> {
     )(i)
 }
 
-fn parse_or_matcher(i: &[u8]) -> ParseResult<SubfieldMatcher> {
+/// Parse a reference to a named filter (`$name` or `%name`), which is
+/// a subfield matcher expression.
+fn parse_named_matcher<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, SubfieldMatcher> {
+    map(
+        |i| {
+            parse_reference(
+                i,
+                defs,
+                parse_subfield_matcher_with,
+                "subfield matcher",
+            )
+        },
+        |matcher| SubfieldMatcher::Group(Box::new(matcher)),
+    )(i)
+}
+
+/// Parse an operand of a composite matcher.
+fn parse_operand<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, SubfieldMatcher> {
+    alt((
+        ws(|i| parse_group_matcher(i, defs)),
+        map(ws(parse_singleton_matcher), SubfieldMatcher::Singleton),
+        ws(|i| parse_named_matcher(i, defs)),
+        ws(|i| parse_not_matcher(i, defs)),
+    ))(i)
+}
+
+fn parse_and_matcher<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, SubfieldMatcher> {
     let (i, (first, remainder)) = tuple((
-        alt((
-            ws(parse_group_matcher),
-            ws(parse_and_matcher),
-            ws(parse_subfield_singleton_matcher),
-            ws(parse_not_matcher),
+        |i| parse_operand(i, defs),
+        many0(preceded(ws(tag("&&")), cut(|i| parse_operand(i, defs)))),
+    ))(i)?;
+
+    Ok((
+        i,
+        remainder.into_iter().fold(first, |prev, next| prev & next),
+    ))
+}
+
+fn parse_xor_matcher<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, SubfieldMatcher> {
+    let (i, (first, remainder)) = tuple((
+        |i| parse_and_matcher(i, defs),
+        many0(preceded(
+            ws(parse_xor_op),
+            cut(|i| parse_and_matcher(i, defs)),
         )),
-        many1(preceded(
+    ))(i)?;
+
+    Ok((
+        i,
+        remainder.into_iter().fold(first, |prev, next| prev ^ next),
+    ))
+}
+
+fn parse_or_matcher<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, SubfieldMatcher> {
+    let (i, (first, remainder)) = tuple((
+        |i| parse_xor_matcher(i, defs),
+        many0(preceded(
             ws(tag("||")),
-            cut(alt((
-                ws(parse_group_matcher),
-                ws(parse_and_matcher),
-                ws(parse_subfield_singleton_matcher),
-                ws(parse_not_matcher),
-            ))),
+            cut(|i| parse_xor_matcher(i, defs)),
         )),
     ))(i)?;
 
@@ -757,60 +1802,166 @@ fn parse_or_matcher(i: &[u8]) -> ParseResult<SubfieldMatcher> {
     ))
 }
 
-fn parse_and_matcher(i: &[u8]) -> ParseResult<SubfieldMatcher> {
-    let (i, (first, remainder)) = tuple((
-        alt((
-            ws(parse_group_matcher),
-            map(
-                ws(parse_singleton_matcher),
-                SubfieldMatcher::Singleton,
-            ),
-            ws(parse_not_matcher),
-        )),
-        many1(preceded(
-            ws(tag("&&")),
-            alt((
-                ws(parse_group_matcher),
-                map(
-                    ws(parse_singleton_matcher),
-                    SubfieldMatcher::Singleton,
-                ),
-                ws(parse_not_matcher),
-            )),
+/// Parse an implication, which is right-associative (`a? => b? =>
+/// c?` is equivalent to `a? => (b? => c?)`).
+fn parse_implies_matcher<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, SubfieldMatcher> {
+    let (i, (lhs, rhs)) = pair(
+        |i| parse_or_matcher(i, defs),
+        opt(preceded(
+            ws(parse_implies_op),
+            cut(|i| parse_implies_matcher(i, defs)),
         )),
-    ))(i)?;
+    )(i)?;
 
     Ok((
         i,
-        remainder.into_iter().fold(first, |prev, next| prev & next),
+        match rhs {
+            Some(rhs) => SubfieldMatcher::Composite {
+                lhs: Box::new(lhs),
+                op: BooleanOp::Implies,
+                rhs: Box::new(rhs),
+            },
+            None => lhs,
+        },
     ))
 }
 
+/// Parse a composite matcher expression. Operators bind (from
+/// strongest to weakest) in the order `&&`, `^`, `||` and `=>`.
 #[inline]
-fn parse_composite_matcher(i: &[u8]) -> ParseResult<SubfieldMatcher> {
-    alt((parse_or_matcher, parse_and_matcher))(i)
+fn parse_composite_matcher<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, SubfieldMatcher> {
+    parse_implies_matcher(i, defs)
 }
 
-pub fn parse_subfield_matcher(
-    i: &[u8],
-) -> ParseResult<SubfieldMatcher> {
+/// Parse a subfield matcher expression, which may contain references
+/// to the given named filters.
+pub(crate) fn parse_subfield_matcher_with<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, SubfieldMatcher> {
     alt((
-        parse_composite_matcher,
-        parse_group_matcher,
-        parse_not_matcher,
+        |i| parse_composite_matcher(i, defs),
+        |i| parse_group_matcher(i, defs),
+        |i| parse_not_matcher(i, defs),
         map(parse_singleton_matcher, SubfieldMatcher::Singleton),
     ))(i)
 }
 
+pub fn parse_subfield_matcher(
+    i: &[u8],
+) -> ParseResult<SubfieldMatcher> {
+    parse_subfield_matcher_with(i, &Definitions::default())
+}
+
 impl SubfieldMatcher {
     /// Create a new matcher from a string slice.
     pub fn new(data: &str) -> Result<Self, ParseMatcherError> {
         all_consuming(parse_subfield_matcher)(data.as_bytes())
             .finish()
-            .map_err(|_| {
-                ParseMatcherError::InvalidSubfieldMatcher(data.into())
+            .map_err(|e| {
+                ParseMatcherError::InvalidSubfieldMatcher(
+                    SyntaxError::new(data, e),
+                )
             })
-            .map(|(_, matcher)| matcher)
+            .and_then(|(_, matcher)| {
+                matcher.with_lists(&ParseContext::default())
+            })
+    }
+
+    /// Reads the lists of all list file references (`0 in @codes.txt`),
+    /// whereby relative paths are resolved against the base directory
+    /// of the context. This is only needed for matchers, which are
+    /// created by
+    /// [parse_subfield_matcher](crate::parser::parse_subfield_matcher);
+    /// [SubfieldMatcher::new] reads the lists by itself.
+    pub fn read_lists(
+        &mut self,
+        ctx: &ParseContext,
+    ) -> Result<(), ParseMatcherError> {
+        self.load_lists(&mut ListCache::new(ctx))
+    }
+
+    /// Evaluates the matcher against the given subfield(s) and returns
+    /// the trace of the evaluation.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use pica_matcher::SubfieldMatcher;
+    /// use pica_record::SubfieldRef;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> anyhow::Result<()> {
+    ///     let matcher = SubfieldMatcher::new("a == 'x' && b?")?;
+    ///     let trace = matcher.trace(
+    ///         &[SubfieldRef::new('a', "x"), SubfieldRef::new('c', "y")],
+    ///         &Default::default(),
+    ///     );
+    ///
+    ///     assert!(!trace.result());
+    ///     assert_eq!(trace.expr(), "&&");
+    ///
+    ///     let children = trace.children();
+    ///     assert_eq!(children[0].expr(), "a == 'x'");
+    ///     assert_eq!(children[0].matches(), ["$a x"]);
+    ///     assert!(!children[1].result());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn trace<'a, T: AsRef<[u8]> + 'a>(
+        &self,
+        subfields: impl IntoIterator<Item = &'a Subfield<T>> + Clone,
+        options: &MatcherOptions,
+    ) -> Trace {
+        match self {
+            Self::Singleton(m) => m.trace(subfields, options),
+            Self::Group(m) => m.trace(subfields, options),
+            Self::Not(m) => {
+                let trace = m.trace(subfields, options);
+                Trace::connective("!", !trace.result(), [trace])
+            }
+            Self::Composite { lhs, op, rhs } => {
+                let lhs = lhs.trace(subfields.clone(), options);
+                let rhs = rhs.trace(subfields, options);
+                let result = op.eval(lhs.result(), || rhs.result());
+
+                Trace::connective(op.to_string(), result, [lhs, rhs])
+            }
+        }
+    }
+
+    /// Returns the matcher without enclosing groups.
+    fn ungrouped(&self) -> &Self {
+        match self {
+            Self::Group(m) => m.ungrouped(),
+            m => m,
+        }
+    }
+
+    /// Returns the operands of a chain of the connective `op`. Chains
+    /// of implications aren't flattened, because `=>` isn't
+    /// associative.
+    #[cfg(feature = "serde")]
+    fn operands(&self, op: &BooleanOp) -> Vec<&Self> {
+        match self.ungrouped() {
+            Self::Composite { lhs, op: o, rhs }
+                if o == op && *op == BooleanOp::Implies =>
+            {
+                vec![lhs, rhs]
+            }
+            Self::Composite { lhs, op: o, rhs } if o == op => {
+                let mut operands = lhs.operands(op);
+                operands.extend(rhs.operands(op));
+                operands
+            }
+            m => vec![m],
+        }
     }
 }
 
@@ -824,15 +1975,10 @@ impl Matcher for SubfieldMatcher {
             Self::Singleton(m) => m.is_match(subfields, options),
             Self::Group(m) => m.is_match(subfields, options),
             Self::Not(m) => !m.is_match(subfields, options),
-            Self::Composite { lhs, op, rhs } => {
-                if *op == BooleanOp::And {
-                    lhs.is_match(subfields.clone(), options)
-                        && rhs.is_match(subfields, options)
-                } else {
-                    lhs.is_match(subfields.clone(), options)
-                        || rhs.is_match(subfields, options)
-                }
-            }
+            Self::Composite { lhs, op, rhs } => op
+                .eval(lhs.is_match(subfields.clone(), options), || {
+                    rhs.is_match(subfields, options)
+                }),
         }
     }
 }
@@ -864,33 +2010,99 @@ mod tests {
         assert_finished_and_eq!(
             parse_relation_matcher(b"0 == 'abc'"),
             RelationMatcher {
+                quantifier: Quantifier::Any,
                 codes: vec!['0'],
                 op: RelationalOp::Eq,
-                value: "abc".into()
+                value: Value::String("abc".into())
             }
         );
         assert_finished_and_eq!(
             parse_relation_matcher(b"[012] =^ 'abc'"),
             RelationMatcher {
+                quantifier: Quantifier::Any,
                 codes: vec!['0', '1', '2'],
                 op: RelationalOp::StartsWith,
-                value: "abc".into()
+                value: Value::String("abc".into())
             }
         );
         assert_finished_and_eq!(
             parse_relation_matcher(b"0 =$ 'abc'"),
             RelationMatcher {
+                quantifier: Quantifier::Any,
                 codes: vec!['0'],
                 op: RelationalOp::EndsWith,
-                value: "abc".into()
+                value: Value::String("abc".into())
             }
         );
         assert_finished_and_eq!(
             parse_relation_matcher(b"0 =* 'abc'"),
             RelationMatcher {
+                quantifier: Quantifier::Any,
                 codes: vec!['0'],
                 op: RelationalOp::Similar,
-                value: "abc".into()
+                value: Value::String("abc".into())
+            }
+        );
+
+        assert_finished_and_eq!(
+            parse_relation_matcher(b"0 =? 'abc'"),
+            RelationMatcher {
+                quantifier: Quantifier::Any,
+                codes: vec!['0'],
+                op: RelationalOp::Contains,
+                value: Value::String("abc".into())
+            }
+        );
+
+        assert_finished_and_eq!(
+            parse_relation_matcher(b"0 =% 'abc'"),
+            RelationMatcher {
+                quantifier: Quantifier::Any,
+                codes: vec!['0'],
+                op: RelationalOp::ContainsWord,
+                value: Value::String("abc".into())
+            }
+        );
+
+        assert_finished_and_eq!(
+            parse_relation_matcher(b"0 > 1990"),
+            RelationMatcher {
+                quantifier: Quantifier::Any,
+                codes: vec!['0'],
+                op: RelationalOp::Gt,
+                value: Value::Integer(1990)
+            }
+        );
+        assert_finished_and_eq!(
+            parse_relation_matcher(b"0 != -1"),
+            RelationMatcher {
+                quantifier: Quantifier::Any,
+                codes: vec!['0'],
+                op: RelationalOp::Ne,
+                value: Value::Integer(-1)
+            }
+        );
+        assert_finished_and_eq!(
+            parse_relation_matcher(
+                b"date(0, '%d-%m-%y') >= '2020-01-01'"
+            ),
+            RelationMatcher {
+                quantifier: Quantifier::Any,
+                codes: vec!['0'],
+                op: RelationalOp::Ge,
+                value: Value::Date {
+                    format: "%d-%m-%y".into(),
+                    date: NaiveDate::from_ymd_opt(2020, 1, 1).unwrap(),
+                }
+            }
+        );
+        assert_finished_and_eq!(
+            parse_relation_matcher(b"len([ab]) <= 200"),
+            RelationMatcher {
+                quantifier: Quantifier::Any,
+                codes: vec!['a', 'b'],
+                op: RelationalOp::Le,
+                value: Value::Length(200),
             }
         );
 
@@ -898,6 +2110,19 @@ mod tests {
         assert_error!(parse_relation_matcher(b"0 > 'abc'"));
         assert_error!(parse_relation_matcher(b"0 <= 'abc'"));
         assert_error!(parse_relation_matcher(b"0 < 'abc'"));
+        assert_error!(parse_relation_matcher(b"0 =^ 1990"));
+        assert_error!(parse_relation_matcher(
+            b"date(0, '%Q') > '2020-01-01'"
+        ));
+        assert_error!(parse_relation_matcher(
+            b"date(0, '%Y%m%d') > '01.01.2020'"
+        ));
+        assert_error!(parse_relation_matcher(
+            b"date(0, '%Y%m%d') =^ '2020-01-01'"
+        ));
+        assert_error!(parse_relation_matcher(b"len(0) > 'abc'"));
+        assert_error!(parse_relation_matcher(b"len(0) =? 1"));
+        assert_error!(parse_relation_matcher(b"0 =? 1"));
     }
 
     #[test]
@@ -905,16 +2130,20 @@ mod tests {
         assert_finished_and_eq!(
             parse_regex_matcher(b"0 =~ '^a.*c$'"),
             RegexMatcher {
+                quantifier: Quantifier::Any,
                 codes: vec!['0'],
                 pattern: "^a.*c$".into(),
+                regex: CompiledRegex::new("^a.*c$").unwrap(),
                 invert: false,
             }
         );
         assert_finished_and_eq!(
             parse_regex_matcher(b"0 !~ '^a.*c$'"),
             RegexMatcher {
+                quantifier: Quantifier::Any,
                 codes: vec!['0'],
                 pattern: "^a.*c$".into(),
+                regex: CompiledRegex::new("^a.*c$").unwrap(),
                 invert: true,
             }
         );
@@ -928,8 +2157,12 @@ mod tests {
         assert_finished_and_eq!(
             parse_in_matcher(b"0 in ['abc', 'bcd']"),
             InMatcher {
+                quantifier: Quantifier::Any,
                 codes: vec!['0'],
-                values: vec!["abc".into(), "bcd".into()],
+                values: HashSet::from(["abc".into(), "bcd".into()]),
+                folded: HashSet::from(["abc".into(), "bcd".into()]),
+                normalized: NormalizedValues::default(),
+                file: None,
                 invert: false
             }
         );
@@ -937,13 +2170,31 @@ mod tests {
         assert_finished_and_eq!(
             parse_in_matcher(b"[09] not in ['abc', 'bcd']"),
             InMatcher {
+                quantifier: Quantifier::Any,
                 codes: vec!['0', '9'],
-                values: vec!["abc".into(), "bcd".into()],
+                values: HashSet::from(["abc".into(), "bcd".into()]),
+                folded: HashSet::from(["abc".into(), "bcd".into()]),
+                normalized: NormalizedValues::default(),
+                file: None,
                 invert: true,
             }
         );
 
         assert_error!(parse_in_matcher(b"0 in []"));
+
+        // List files are read after parsing.
+        assert_finished_and_eq!(
+            parse_in_matcher(b"0 in @missing.txt"),
+            InMatcher {
+                quantifier: Quantifier::Any,
+                codes: vec!['0'],
+                values: HashSet::new(),
+                folded: HashSet::new(),
+                normalized: NormalizedValues::default(),
+                file: Some("missing.txt".into()),
+                invert: false,
+            }
+        );
     }
 
     #[test]
@@ -951,46 +2202,63 @@ mod tests {
         assert_finished_and_eq!(
             parse_cardinality_matcher(b"#0 == 1"),
             CardinalityMatcher {
-                code: '0',
+                codes: vec!['0'],
                 op: RelationalOp::Eq,
-                value: 1,
+                rhs: Cardinality::Value(1),
             }
         );
         assert_finished_and_eq!(
             parse_cardinality_matcher(b"#0 >= 1"),
             CardinalityMatcher {
-                code: '0',
+                codes: vec!['0'],
                 op: RelationalOp::Ge,
-                value: 1,
+                rhs: Cardinality::Value(1),
             }
         );
         assert_finished_and_eq!(
             parse_cardinality_matcher(b"#0 > 1"),
             CardinalityMatcher {
-                code: '0',
+                codes: vec!['0'],
                 op: RelationalOp::Gt,
-                value: 1,
+                rhs: Cardinality::Value(1),
             }
         );
         assert_finished_and_eq!(
             parse_cardinality_matcher(b"#0 <= 1"),
             CardinalityMatcher {
-                code: '0',
+                codes: vec!['0'],
                 op: RelationalOp::Le,
-                value: 1,
+                rhs: Cardinality::Value(1),
             }
         );
         assert_finished_and_eq!(
             parse_cardinality_matcher(b"#0 < 1"),
             CardinalityMatcher {
-                code: '0',
+                codes: vec!['0'],
                 op: RelationalOp::Lt,
-                value: 1,
+                rhs: Cardinality::Value(1),
+            }
+        );
+
+        assert_finished_and_eq!(
+            parse_cardinality_matcher(b"#[ab] > 0"),
+            CardinalityMatcher {
+                codes: vec!['a', 'b'],
+                op: RelationalOp::Gt,
+                rhs: Cardinality::Value(0),
+            }
+        );
+        assert_finished_and_eq!(
+            parse_cardinality_matcher(b"#a != #[bc]"),
+            CardinalityMatcher {
+                codes: vec!['a'],
+                op: RelationalOp::Ne,
+                rhs: Cardinality::Count(vec!['b', 'c']),
             }
         );
 
         assert_error!(parse_cardinality_matcher(b"#a =~ '^abc'"));
-        assert_error!(parse_cardinality_matcher(b"#[ab] > 0"));
+        assert_error!(parse_cardinality_matcher(b"#a > #"));
         assert_error!(parse_cardinality_matcher(b"#a > -1"));
     }
 }
@@ -1,4 +1,4 @@
-use std::fmt::Display;
+use std::fmt::{self, Display};
 
 use nom::branch::alt;
 use nom::character::complete::{char, one_of};
@@ -6,11 +6,21 @@ use nom::combinator::{all_consuming, map, value};
 use nom::multi::fold_many1;
 use nom::sequence::{delimited, separated_pair};
 use nom::Finish;
-use pica_record::parser::{parse_tag, ParseResult};
 use pica_record::{Tag, TagMut};
+#[cfg(feature = "serde")]
+use serde::{Serialize, Serializer};
 
+use crate::common::{parse_tag, ParseResult, ParserError};
 use crate::ParseMatcherError;
 
+/// The allowed characters at each position of a tag.
+const ALLOWED: [&str; 4] = [
+    "012",
+    "0123456789",
+    "0123456789",
+    "ABCDEFGHIJKLMNOPQRSTUVWXYZ@",
+];
+
 /// A matcher that matches against PICA+ [Tags](`pica_record::Tag`).
 #[derive(Debug, PartialEq, Eq)]
 pub enum TagMatcher {
@@ -76,6 +86,75 @@ impl TagMatcher {
     }
 }
 
+impl Display for TagMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Simple(tag) => write!(f, "{tag}"),
+            Self::Pattern(pattern) => {
+                for (fragment, allowed) in pattern.iter().zip(ALLOWED) {
+                    fmt_fragment(f, fragment, allowed)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for TagMatcher {
+    /// Serializes the matcher as a tag (pattern), e.g. `"01[2-4]A"`.
+    fn serialize<S: Serializer>(
+        &self,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+/// Formats a fragment of a tag pattern. Runs of three or more
+/// consecutive characters are written as a range (e.g. `[0-3]`).
+fn fmt_fragment(
+    f: &mut fmt::Formatter<'_>,
+    fragment: &[char],
+    allowed: &str,
+) -> fmt::Result {
+    let mut chars = fragment.to_vec();
+    chars.sort_unstable();
+    chars.dedup();
+
+    if chars.len() == allowed.len() {
+        return write!(f, ".");
+    }
+
+    if let [c] = chars[..] {
+        return write!(f, "{c}");
+    }
+
+    write!(f, "[")?;
+    let mut i = 0;
+    while i < chars.len() {
+        let mut j = i;
+        while j + 1 < chars.len()
+            && chars[j + 1] as u32 == chars[j] as u32 + 1
+        {
+            j += 1;
+        }
+
+        if j - i >= 2 {
+            write!(f, "{}-{}", chars[i], chars[j])?;
+        } else {
+            for c in &chars[i..=j] {
+                write!(f, "{c}")?;
+            }
+        }
+
+        i = j + 1;
+    }
+
+    write!(f, "]")
+}
+
 impl<T: AsRef<[u8]>> PartialEq<TagMatcher> for Tag<T> {
     #[inline]
     fn eq(&self, matcher: &TagMatcher) -> bool {
@@ -132,10 +211,10 @@ fn parse_fragment<'a>(
 
 #[inline]
 fn parse_pattern(i: &[u8]) -> ParseResult<TagMatcher> {
-    let (i, p0) = parse_fragment("012", i)?;
-    let (i, p1) = parse_fragment("0123456789", i)?;
-    let (i, p2) = parse_fragment("0123456789", i)?;
-    let (i, p3) = parse_fragment("ABCDEFGHIJKLMNOPQRSTUVWXYZ@", i)?;
+    let (i, p0) = parse_fragment(ALLOWED[0], i)?;
+    let (i, p1) = parse_fragment(ALLOWED[1], i)?;
+    let (i, p2) = parse_fragment(ALLOWED[2], i)?;
+    let (i, p3) = parse_fragment(ALLOWED[3], i)?;
 
     Ok((i, TagMatcher::Pattern([p0, p1, p2, p3])))
 }
@@ -149,6 +228,7 @@ fn parse_simple(i: &[u8]) -> ParseResult<TagMatcher> {
 
 pub fn parse_tag_matcher(i: &[u8]) -> ParseResult<TagMatcher> {
     alt((parse_simple, parse_pattern))(i)
+        .map_err(|e| e.map(|_| ParserError::expected(i, "tag")))
 }
 
 #[cfg(test)]
@@ -0,0 +1,125 @@
+//! Evaluation traces, which explain why a matcher matched or failed.
+
+use bstr::ByteSlice;
+use pica_record::{Field, Subfield};
+
+/// The evaluation of a (sub-)expression of a matcher.
+///
+/// A trace contains the (formatted) expression, the result of its
+/// evaluation, the fields or subfields that satisfied the expression
+/// (or were counted by a cardinality matcher) and the traces of its
+/// sub-expressions. Connectives are labeled with their operator (`&&`,
+/// `||` or `!`); chains of the same connective are flattened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace {
+    expr: String,
+    result: bool,
+    matches: Vec<String>,
+    children: Vec<Trace>,
+}
+
+impl Trace {
+    /// Creates a new trace of an expression.
+    pub(crate) fn new(expr: impl Into<String>, result: bool) -> Self {
+        Self {
+            expr: expr.into(),
+            result,
+            matches: vec![],
+            children: vec![],
+        }
+    }
+
+    /// Sets the fields or subfields that satisfied the expression.
+    pub(crate) fn with_matches(mut self, matches: Vec<String>) -> Self {
+        self.matches = matches;
+        self
+    }
+
+    /// Sets the traces of the sub-expressions.
+    pub(crate) fn with_children(
+        mut self,
+        children: Vec<Trace>,
+    ) -> Self {
+        self.children = children;
+        self
+    }
+
+    /// Creates the trace of a connective. Operands, which are
+    /// connected by the same operator, are merged into the new trace.
+    pub(crate) fn connective(
+        op: impl Into<String>,
+        result: bool,
+        operands: impl IntoIterator<Item = Trace>,
+    ) -> Self {
+        let op = op.into();
+        let mut children = vec![];
+
+        for operand in operands {
+            if operand.expr == op && op != "!" {
+                children.extend(operand.children);
+            } else {
+                children.push(operand);
+            }
+        }
+
+        Self::new(op, result).with_children(children)
+    }
+
+    /// Returns the (formatted) expression.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use pica_matcher::RecordMatcher;
+    /// use pica_record::RecordRef;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> anyhow::Result<()> {
+    ///     let matcher = RecordMatcher::new("003@.0 == 'abc'")?;
+    ///     let record =
+    ///         RecordRef::new(vec![("003@", None, vec![('0', "abc")])]);
+    ///
+    ///     let trace = matcher.trace(&record, &Default::default());
+    ///     assert_eq!(trace.expr(), "003@.0 == 'abc'");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn expr(&self) -> &str {
+        &self.expr
+    }
+
+    /// Returns the result of the evaluation.
+    pub fn result(&self) -> bool {
+        self.result
+    }
+
+    /// Returns the fields (`012A/01`) or subfields (`$a abc`), which
+    /// satisfied the expression.
+    pub fn matches(&self) -> &[String] {
+        &self.matches
+    }
+
+    /// Returns the traces of the sub-expressions.
+    pub fn children(&self) -> &[Trace] {
+        &self.children
+    }
+}
+
+/// Formats a field by its tag and occurrence (e.g. `012A/01`).
+pub(crate) fn fmt_field<T: AsRef<[u8]>>(field: &Field<T>) -> String {
+    match field.occurrence() {
+        Some(occurrence) => format!("{}/{occurrence}", field.tag()),
+        None => field.tag().to_string(),
+    }
+}
+
+/// Formats a subfield by its code and value (e.g. `$a abc`).
+pub(crate) fn fmt_subfield<T: AsRef<[u8]>>(
+    subfield: &Subfield<T>,
+) -> String {
+    format!(
+        "${} {}",
+        subfield.code(),
+        subfield.value().as_ref().to_str_lossy()
+    )
+}
@@ -0,0 +1,234 @@
+use std::path::Path;
+use std::{env, fs};
+
+use once_cell::sync::OnceCell;
+use pica_matcher::{MatcherOptions, NormalizationForm, RecordMatcher};
+use pica_record::{Record, RecordMut, RecordRef};
+
+fn ada_lovelace() -> &'static [u8] {
+    static DATA: OnceCell<Vec<u8>> = OnceCell::new();
+    DATA.get_or_init(|| {
+        let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let path = Path::new(&manifest_dir)
+            .join("../tests/data/119232022.dat");
+        fs::read_to_string(path).unwrap().as_bytes().to_vec()
+    })
+}
+
+/// Asserts that the compiled matcher returns the same result as the
+/// matcher itself.
+fn assert_equivalent<T: AsRef<[u8]>>(
+    expr: &str,
+    record: &Record<T>,
+    options: &MatcherOptions,
+) -> anyhow::Result<()> {
+    let matcher = RecordMatcher::new(expr)?;
+    assert_eq!(
+        matcher.compile(options).is_match(record),
+        matcher.is_match(record, options),
+        "{expr}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn compiled_matcher_is_match() -> anyhow::Result<()> {
+    let record = RecordMut::from_bytes(ada_lovelace())?;
+    let options = MatcherOptions::default();
+
+    for expr in [
+        "003@?",
+        "!003@?",
+        "004B? && 028A.a == 'Lovelace'",
+        "028A.a == 'Byron' || 028A.d =~ '^Ada'",
+        "028A.a =~ '^By' || 028A.a =~ 'ce$' || 028A.a =~ '^X'",
+        "028A.a =~ '^By' || 028A.a =~ '^X' || 028A.d =~ 'Ada'",
+        "028A.a !~ '^Love' || 028A.a =~ '^X'",
+        "028@{a =~ '^X' || a =~ '^Y'} || 028A{d =~ '^X' || a =~ 'e$'}",
+        "028A{d =^ 'Ada' && a == 'Lovelace'} && 028@?",
+        "#028[A@] > 3 && 028[A@].a =~ 'Lovelace' && !065R?",
+        "0[01]2[@A].0 =~ '^Tp' || 003@.0 == '1'",
+        "003@? && 002@? && 028A? && 028@? && 060R? && !012A?",
+        "(003@? || 012A?) && (028A.a =~ '^X' || 028A.a =~ 'e$')",
+        "!(028A.a =~ '^X' || 028A.a =~ '^Y') && 003@?",
+        "028A.a =* 'Lovelace' && 028A.a =~ 'L'",
+        "060R.a >= ALL 060R.b || 002@.0 in ['Tp1', 'Tpz']",
+        "ALL 028A.a =~ 'e$' || 028A.a =~ '^X' || 028A.a =~ '^Y'",
+        "028A{ALL [ad] =~ '^[A-Z]'} && ALL 060R{a? || b?} && 003@?",
+        "ALL 028[A@]{d =~ '^Ada'} || ALL 065R.9 in ['X']",
+        "#fields > 20 && 003@? && !duplicates? && !invalid_utf8?",
+        "before(003@, 028A)? && 028A.a == 'Lovelace' && #bytes > 100",
+        "@copy{003@?} || 028A.a == 'Lovelace' && !ALL @local{028A?}",
+        "028A.a =? 'love' || 028A.d =% 'Ada' && len(028A.a) > 5",
+    ] {
+        assert_equivalent(expr, &record, &options)?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn compiled_matcher_options() -> anyhow::Result<()> {
+    let record = RecordMut::from_bytes(ada_lovelace())?;
+    let options = MatcherOptions::new().case_ignore(true);
+
+    let matcher =
+        RecordMatcher::new("028A.a =~ '^LOVE' || 028A.a =~ '^X'")?;
+    assert!(matcher.compile(&options).is_match(&record));
+    assert!(!matcher.compile(&Default::default()).is_match(&record));
+
+    let options = MatcherOptions::new()
+        .normalization(Some(NormalizationForm::Nfd))
+        .diacritic_ignore(true)
+        .case_fold(true);
+
+    for expr in [
+        "028A.a =~ '^LOVE' || 028A.a =~ '^X'",
+        "028A.a =~ '^LOVE' || 028A.d =~ '^ADA'",
+        "028A.a == 'LÖVELACE' && 028A.d =^ 'ÁDA'",
+    ] {
+        assert_equivalent(expr, &record, &options)?;
+        assert!(RecordMatcher::new(expr)?.is_match(&record, &options));
+    }
+
+    // Regular expressions aren't normalized, only the values.
+    for expr in [
+        "028A.a =~ '^LÖVE' || 028A.a =~ '^X'",
+        "028A.a =~ '^L[̈O]VE' || 028A.a =~ '^X'",
+    ] {
+        assert_equivalent(expr, &record, &options)?;
+    }
+    assert!(!RecordMatcher::new(
+        "028A.a =~ '^LÖVE' || 028A.a =~ '^X'"
+    )?
+    .is_match(&record, &options));
+
+    Ok(())
+}
+
+#[test]
+fn compiled_matcher_indexed() -> anyhow::Result<()> {
+    let record = RecordRef::new(vec![
+        ("003@", None, vec![('0', "123456789X")]),
+        ("012A", Some("01"), vec![('a', "abc")]),
+        ("012A", None, vec![('a', "def"), ('b', "1")]),
+        ("013A", None, vec![('a', "ghi")]),
+    ]);
+    let options = MatcherOptions::default();
+
+    for (expr, expected) in [
+        ("003@? && 012A? && 013A? && !014A?", true),
+        ("003@? && 012A/01.a == 'abc' && 013A? && 012A.b?", true),
+        ("003@? && 012A.a == 'abc' && 013A? && 012A.b?", false),
+        ("003@? && #012A == 1 && 013A? && 012A.b?", true),
+        ("003@? && #012A/* == 2 && 01[23]A.a == 'ghi' && 012A?", true),
+        ("003@? && #012A/* > #013A && 013A? && 012A?", true),
+        ("003@? && #013A >= #012A/* && 013A? && 012A?", false),
+        ("014A? || 015A? || 016A? || 01[23]A.a =~ '^gh'", true),
+        ("003@? ^ 012A? && 013A?", false),
+        ("014A? ^ 012A{a == 'def' ^ b?}", false),
+        ("012A{a == 'abc' => b?} => 014A?", false),
+        ("012A/01{a == 'abc' => b?} => 014A?", true),
+    ] {
+        let matcher = RecordMatcher::new(expr)?;
+        assert_eq!(
+            matcher.compile(&options).is_match(&record),
+            expected
+        );
+        assert_eq!(matcher.is_match(&record, &options), expected);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn compiled_matcher_differential() -> anyhow::Result<()> {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR")?;
+    let data = [
+        "000008672",
+        "000009229",
+        "000016586",
+        "1004916019",
+        "118515551",
+        "119232022",
+    ]
+    .into_iter()
+    .map(|idn| {
+        fs::read(
+            Path::new(&manifest_dir)
+                .join(format!("../tests/data/{idn}.dat")),
+        )
+    })
+    .collect::<Result<Vec<_>, _>>()?;
+
+    let records = data
+        .iter()
+        .map(|data| RecordMut::from_bytes(data))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // A record with two local blocks and copies.
+    let holdings = RecordRef::new(vec![
+        ("003@", None, vec![('0', "123456789X")]),
+        ("028A", None, vec![('a', "Lovelace"), ('d', "Ada")]),
+        ("101@", None, vec![('a', "1")]),
+        ("203@", Some("01"), vec![('0', "123")]),
+        ("209A", Some("01"), vec![('f', "ab"), ('x', "00")]),
+        ("101@", None, vec![('a', "2")]),
+        ("203@", Some("01"), vec![('0', "456")]),
+        ("209A", Some("01"), vec![('f', "cd")]),
+        ("209A", Some("02"), vec![('f', "ef"), ('x', "01")]),
+    ]);
+
+    let options = [
+        MatcherOptions::default(),
+        MatcherOptions::new().case_ignore(true),
+        MatcherOptions::new()
+            .normalization(Some(NormalizationForm::Nfc))
+            .diacritic_ignore(true)
+            .case_fold(true),
+    ];
+
+    let exprs = [
+        // quantifiers
+        "ALL 028[A@].a =~ '^[A-Z]' || 003@.0 =~ '^0'",
+        "ALL 028[A@]{a =~ 'e$' || a =~ '^X'} && 003@?",
+        "028A{ALL [ad] =~ '^[A-Z]' || a =~ '^x'} || ALL 209A.f?",
+        "ALL 060R.a =~ '^1' || 060R.a =~ '^X' || 060R.a =~ '^Y'",
+        "ALL 041[AP]{a =~ '^[A-Z]' && ALL 9 =~ '^[0-9]'}",
+        // exclusive or
+        "003@.0 =~ '^0' ^ 028A.a =~ 'e$'",
+        "028A.a =~ '^L' ^ 028A.a =~ '^B' || 028@.a =~ '^X'",
+        "028A{a =~ '^L' ^ d =~ '^A'} xor 002@.0 =^ 'Tp'",
+        "(003@? ^ 028A?) ^ (060R? ^ 002@.0 == 'Tb1')",
+        "!(028A.a =~ 'e$' ^ 028A.a =~ '^X') && 003@?",
+        // implication
+        "002@.0 =^ 'Tp' => 028A.a =~ '^[A-Z]'",
+        "028A.a =~ '^X' => 028A.a =~ '^Y' || 028A.d =~ '^A'",
+        "028A{a =~ '^L' => d =~ '^A' || d =~ '^X'} implies 003@?",
+        "(028A? => 028@?) && (060R? => ALL 060R.a =~ '^1')",
+        "!(002@.0 == 'Tp1' => 028A.a =~ 'e$') || 003@?",
+        // scopes
+        "@copy{203@/01? && 209A/01.f == 'cd'}",
+        "@copy{203@.0 =~ '^1' ^ 209A.x?} || 028A.a =~ '^X'",
+        "ALL @copy{209A.f =~ '^[a-z]'} => 003@?",
+        "@local{101@.a == '2' && 209A.x =~ '^0'} || 028A.a =~ 'e$'",
+        "!ALL @local{209A.x?} && @copy{203@.0 == '456' => 209A?}",
+    ];
+
+    for record in records.iter() {
+        for options in options.iter() {
+            for expr in exprs {
+                assert_equivalent(expr, record, options)?;
+            }
+        }
+    }
+
+    for options in options.iter() {
+        for expr in exprs {
+            assert_equivalent(expr, &holdings, options)?;
+        }
+    }
+
+    Ok(())
+}
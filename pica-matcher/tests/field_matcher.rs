@@ -1,5 +1,5 @@
 use pica_matcher::{FieldMatcher, MatcherOptions};
-use pica_record::FieldMut;
+use pica_record::{FieldMut, FieldRef};
 
 #[test]
 fn field_matcher_exists() -> anyhow::Result<()> {
@@ -78,6 +78,259 @@ fn field_matcher_subfields() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn field_matcher_reference() -> anyhow::Result<()> {
+    let options = MatcherOptions::default();
+    let fields = vec![
+        FieldMut::new("003@", None, vec![('0', "123456789X")]),
+        FieldMut::new("007G", None, vec![('0', "123456789X")]),
+        FieldMut::new("060R", None, vec![('a', "1990"), ('b', "2005")]),
+        FieldMut::new("060R", None, vec![('a', "950"), ('b', "1100")]),
+    ];
+
+    let matcher = FieldMatcher::new("003@.0 == 007G.0")?;
+    assert!(matcher.is_match(&fields, &options));
+
+    let matcher = FieldMatcher::new("003@.0 != 007G.0")?;
+    assert!(!matcher.is_match(&fields, &options));
+
+    let matcher = FieldMatcher::new("060R.b >= 060R.a")?;
+    assert!(matcher.is_match(&fields, &options));
+
+    // compared as integers, not as strings
+    let matcher = FieldMatcher::new("060R.b < 060R.a")?;
+    assert!(!matcher.is_match(&fields[3..], &options));
+
+    let matcher = FieldMatcher::new("060R.b >= ALL 060R.a")?;
+    assert!(matcher.is_match(&fields, &options));
+
+    let matcher = FieldMatcher::new("060R.a >= ALL 060R.a")?;
+    assert!(matcher.is_match(&fields, &options));
+
+    let matcher = FieldMatcher::new("060R.a > ALL 060R.a")?;
+    assert!(!matcher.is_match(&fields, &options));
+
+    let matcher = FieldMatcher::new("060R.a < ANY 060R.b")?;
+    assert!(matcher.is_match(&fields, &options));
+
+    // missing values on the right-hand side
+    let matcher = FieldMatcher::new("003@.0 != 039D.0")?;
+    assert!(!matcher.is_match(&fields, &options));
+
+    let matcher =
+        FieldMatcher::new("003@.0 == 007G.0 && 060R.b > 060R.a")?;
+    assert!(matcher.is_match(&fields, &options));
+
+    // references to the same fields are compared per field
+    let fields = vec![
+        FieldMut::new("060R", None, vec![('a', "1900"), ('b', "1950")]),
+        FieldMut::new("060R", None, vec![('a', "2000"), ('b', "2010")]),
+        FieldMut::new("060R", Some("01"), vec![('a', "2020")]),
+    ];
+
+    let matcher = FieldMatcher::new("060R.b < 060R.a")?;
+    assert!(!matcher.is_match(&fields, &options));
+
+    let matcher = FieldMatcher::new("060R.b < ALL 060R.a")?;
+    assert!(!matcher.is_match(&fields, &options));
+
+    let matcher = FieldMatcher::new("060R.b >= 060R.a")?;
+    assert!(matcher.is_match(&fields, &options));
+
+    // different occurrence matchers compare the values of all fields
+    let matcher = FieldMatcher::new("060R.b < 060R/*.a")?;
+    assert!(matcher.is_match(&fields, &options));
+
+    Ok(())
+}
+
+#[test]
+fn field_matcher_quantifier() -> anyhow::Result<()> {
+    let fields = [
+        FieldMut::new("044H", None, vec![('a', "X"), ('9', "x")]),
+        FieldMut::new("044H", None, vec![('a', "Y"), ('a', "z")]),
+        FieldMut::new("044H", None, vec![('a', "Z"), ('9', "z")]),
+    ];
+    let options = MatcherOptions::default();
+
+    for (expr, expected) in [
+        ("044H{9?}", true),
+        ("ALL 044H{9?}", false),
+        ("ALL 044H{a?}", true),
+        ("044H{ALL a =~ '^[A-Z]'}", true),
+        ("ALL 044H{a =~ '^[A-Z]'}", true),
+        ("ALL 044H.a =~ '^[A-Z]'", false),
+        ("ALL 044H{ALL a =~ '^[A-Z]'}", false),
+        ("ALL 044H{ANY a in ['X', 'Y', 'Z']}", true),
+        ("ALL 044H{ALL a in ['X', 'Y', 'Z']}", false),
+        ("044H{ALL a != 'z' && 9?}", true),
+        ("ALL 044H{a =~ '^[A-Z]' && ALL 9 == 'z'}", false),
+    ] {
+        let matcher = FieldMatcher::new(expr)?;
+        assert_eq!(
+            matcher.is_match(&fields, &options),
+            expected,
+            "{expr}"
+        );
+    }
+
+    // Universal quantification over an empty set of fields or
+    // subfields doesn't match.
+    let matcher = FieldMatcher::new("ALL 045H{a?}")?;
+    assert!(!matcher.is_match(&fields, &options));
+
+    let matcher = FieldMatcher::new("044H{ALL b == 'x'}")?;
+    assert!(!matcher.is_match(&fields, &options));
+
+    let matcher = FieldMatcher::new("044H{!(ALL b == 'x')}")?;
+    assert!(matcher.is_match(&fields, &options));
+
+    Ok(())
+}
+
+#[test]
+fn field_matcher_predicate() -> anyhow::Result<()> {
+    let fields = [
+        FieldMut::new("003@", None, vec![('0', "123456789X")]),
+        FieldMut::new("021A", None, vec![('a', "abc")]),
+        FieldMut::new("028A", None, vec![('a', "def")]),
+        FieldMut::new("044H", None, vec![('a', "x"), ('9', "y")]),
+        FieldMut::new("044H", None, vec![('a', "x"), ('9', "y")]),
+        FieldMut::new("044H", Some("01"), vec![('a', "z")]),
+    ];
+    let options = MatcherOptions::default();
+
+    for (expr, expected) in [
+        ("#fields == 6", true),
+        ("#fields > 6", false),
+        ("# fields>=6", true),
+        ("#bytes == 77", true),
+        ("#bytes < 77", false),
+        ("invalid_utf8?", false),
+        ("!invalid_utf8?", true),
+        ("duplicates?", true),
+        ("duplicates(044H)?", true),
+        ("duplicates(044H/01)?", false),
+        ("duplicates(0[02]..)?", false),
+        ("before(021A, 028A)?", true),
+        ("before(028A, 021A)?", false),
+        ("before(021A, 044H/*)?", true),
+        ("before(044H, 044H/01)?", true),
+        ("before(021A, 012A)?", false),
+        ("003@? && #fields < 10 && !before(044H, 003@)?", true),
+    ] {
+        let matcher = FieldMatcher::new(expr)?;
+        assert_eq!(
+            matcher.is_match(&fields, &options),
+            expected,
+            "{expr}"
+        );
+    }
+
+    let field = FieldRef::from_bytes(b"044H \x1fa\xff\x1e")?;
+    let matcher = FieldMatcher::new("invalid_utf8?")?;
+    assert!(matcher.is_match(&field, &options));
+
+    Ok(())
+}
+
+#[test]
+fn field_matcher_scope() -> anyhow::Result<()> {
+    let fields = [
+        FieldMut::new("003@", None, vec![('0', "123456789X")]),
+        FieldMut::new("101@", None, vec![('a', "1")]),
+        FieldMut::new("201A", Some("01"), vec![('0', "01-01-20")]),
+        FieldMut::new("209A", Some("01"), vec![('a', "X")]),
+        FieldMut::new("209B", Some("02"), vec![('a', "Y")]),
+        FieldMut::new("101@", None, vec![('a', "2")]),
+        FieldMut::new("209A", Some("01"), vec![('a', "X")]),
+        FieldMut::new("209B", Some("01"), vec![('a', "Y")]),
+    ];
+    let options = MatcherOptions::default();
+
+    for (expr, expected) in [
+        ("@local{209A/*.a == 'X' && 209B/*.a == 'Y'}", true),
+        ("@copy{209A/*.a == 'X' && 209B/*.a == 'Y'}", true),
+        ("@copy{101@.a == '1' && 209A/*? && 209B/*?}", false),
+        ("@local{101@.a == '1' && 209A/*? && 209B/*?}", true),
+        ("@local{003@?}", false),
+        ("ALL @local{209A/*.a == 'X'}", true),
+        ("ALL @copy{209A/*.a == 'X'}", false),
+        ("ALL @copy{101@? && #209[AB]/* == 1}", false),
+        ("ANY @copy{ 201A/*? }", true),
+        ("003@? && !@copy{209A/*.a == 'Z'}", true),
+    ] {
+        let matcher = FieldMatcher::new(expr)?;
+        assert_eq!(
+            matcher.is_match(&fields, &options),
+            expected,
+            "{expr}"
+        );
+    }
+
+    let matcher = FieldMatcher::new("ALL @local{003@?}")?;
+    assert!(!matcher.is_match(&fields[..1], &options));
+
+    assert!(FieldMatcher::new("@title{003@?}").is_err());
+    assert!(FieldMatcher::new("@copy{003@?").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn field_matcher_subfields_length() -> anyhow::Result<()> {
+    let matcher = FieldMatcher::new("len(021A.a) > 10")?;
+    let options = MatcherOptions::default();
+
+    assert!(matcher.is_match(
+        &FieldMut::new("021A", None, vec![('a', "Lovelace, Ada")]),
+        &options
+    ));
+
+    assert!(!matcher.is_match(
+        &FieldMut::new("021A", None, vec![('a', "Lovelace")]),
+        &options
+    ));
+
+    assert!(!matcher.is_match(
+        &FieldMut::new("021B", None, vec![('a', "Lovelace, Ada")]),
+        &options
+    ));
+
+    let matcher =
+        FieldMatcher::new("021A.a =% 'Ada' && 021A.a =? 'lace'")?;
+    assert!(matcher.is_match(
+        &FieldMut::new("021A", None, vec![('a', "Lovelace, Ada")]),
+        &options
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn field_matcher_subfields_date() -> anyhow::Result<()> {
+    let matcher =
+        FieldMatcher::new("date(001A.0, '%d-%m-%y') >= '2020-01-01'")?;
+    let options = MatcherOptions::default();
+
+    assert!(matcher.is_match(
+        &FieldMut::new("001A", None, vec![('0', "15-06-21")]),
+        &options
+    ));
+
+    assert!(!matcher.is_match(
+        &FieldMut::new("001A", None, vec![('0', "15-06-19")]),
+        &options
+    ));
+
+    assert!(!matcher.is_match(
+        &FieldMut::new("001B", None, vec![('0', "15-06-21")]),
+        &options
+    ));
+
+    Ok(())
+}
+
 #[test]
 fn field_matcher_cardinality_eq() -> anyhow::Result<()> {
     let matcher = FieldMatcher::new("#012A == 1")?;
@@ -231,6 +484,43 @@ fn field_matcher_cardinality_lt() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn field_matcher_cardinality_count() -> anyhow::Result<()> {
+    let options = MatcherOptions::default();
+    let fields = vec![
+        FieldMut::new("041A", None, vec![('9', "123")]),
+        FieldMut::new("041H", Some("01"), vec![('9', "456")]),
+        FieldMut::new("044K", None, vec![('a', "abc")]),
+        FieldMut::new("044K", None, vec![('9', "789")]),
+    ];
+
+    let matcher = FieldMatcher::new("#041[AH]/* == 2")?;
+    assert!(matcher.is_match(&fields, &options));
+
+    let matcher = FieldMatcher::new("#041[AH] == 1")?;
+    assert!(matcher.is_match(&fields, &options));
+
+    let matcher = FieldMatcher::new("#[0-2]..@ == 0")?;
+    assert!(matcher.is_match(&fields, &options));
+
+    let matcher = FieldMatcher::new("#041[AH]/* >= #044K")?;
+    assert!(matcher.is_match(&fields, &options));
+
+    let matcher = FieldMatcher::new("#041[AH]/* > #044K")?;
+    assert!(!matcher.is_match(&fields, &options));
+
+    let matcher = FieldMatcher::new("#041[AH]/* > #044K{9?}")?;
+    assert!(matcher.is_match(&fields, &options));
+
+    let matcher = FieldMatcher::new("#044K{#9 == #a} == 0")?;
+    assert!(matcher.is_match(&fields, &options));
+
+    assert!(FieldMatcher::new("#041A > #").is_err());
+    assert!(FieldMatcher::new("#041A > #fields").is_err());
+
+    Ok(())
+}
+
 #[test]
 fn field_matcher_group() -> anyhow::Result<()> {
     // singleton
@@ -421,3 +711,153 @@ fn field_matcher_composite_or() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn field_matcher_composite_xor() -> anyhow::Result<()> {
+    let matcher = FieldMatcher::new("012A? ^ 013A.a == '1'")?;
+    let options = MatcherOptions::default();
+
+    assert!(matcher.is_match(
+        &FieldMut::new("012A", None, vec![('0', "abc")]),
+        &options
+    ));
+
+    assert!(!matcher.is_match(
+        vec![
+            &FieldMut::new("012A", None, vec![('0', "abc")]),
+            &FieldMut::new("013A", None, vec![('a', "1")]),
+        ],
+        &options
+    ));
+
+    assert!(!matcher.is_match(
+        &FieldMut::new("014A", None, vec![('0', "abc")]),
+        &options
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn field_matcher_composite_implies() -> anyhow::Result<()> {
+    let matcher = FieldMatcher::new("012A? => 013A? || 014A?")?;
+    let options = MatcherOptions::default();
+
+    assert!(matcher.is_match(
+        &FieldMut::new("013A", None, vec![('a', "1")]),
+        &options
+    ));
+
+    assert!(matcher.is_match(
+        vec![
+            &FieldMut::new("012A", None, vec![('0', "abc")]),
+            &FieldMut::new("014A", None, vec![('a', "1")]),
+        ],
+        &options
+    ));
+
+    assert!(!matcher.is_match(
+        &FieldMut::new("012A", None, vec![('0', "abc")]),
+        &options
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn field_matcher_display() -> anyhow::Result<()> {
+    for (expr, expected) in [
+        ("003@?", "003@?"),
+        ("0[12]2[AB]/*?", "0[12]2[AB]/*?"),
+        ("0..A/01-03?", "0..A/01-03?"),
+        ("003@.0 == \"123\"", "003@.0 == '123'"),
+        ("003@ $0 =^ \"O'Reilly\"", "003@.0 =^ \"O'Reilly\""),
+        (
+            "012A{ [ab] =~ '^\\\\d' && *? }",
+            "012A{[ab] =~ '^\\\\d' && *?}",
+        ),
+        ("012A/00{a? && (b? || !c?)}", "012A{a? && (b? || !c?)}"),
+        ("012A.a in ['y', 'x']", "012A.a in ['x', 'y']"),
+        ("012A.a not in ['x']", "012A.a not in ['x']"),
+        ("012A.#a >= 2", "012A.#a >= 2"),
+        ("012A.a > -10", "012A.a > -10"),
+        (
+            "date(010@.a, '%Y%m%d') >= '2020-01-01'",
+            "010@{date(a, '%Y%m%d') >= '2020-01-01'}",
+        ),
+        ("#012A{a?} > 1", "#012A{a?} > 1"),
+        ("012A? xor 013A?", "012A? ^ 013A?"),
+        ("012A? implies 013A?", "012A? => 013A?"),
+        ("(012A? ^ 013A?) && 014A?", "(012A? ^ 013A?) && 014A?"),
+        ("(012A? => 013A?) => 014A?", "(012A? => 013A?) => 014A?"),
+        ("!(012A? => 013A?)", "!(012A? => 013A?)"),
+        ("012A{a? ^ b? => c?}", "012A{a? ^ b? => c?}"),
+        ("012A{(a? => b?) || c?}", "012A{(a? => b?) || c?}"),
+        ("#041[AH]/*>#044K{ 9? }", "#041[AH]/* > #044K{9?}"),
+        ("012A{ #[ab]>=#c }", "012A.#[ab] >= #c"),
+        ("len(021A.a) >= 200", "021A{len(a) >= 200}"),
+        ("021A{ len([ab])<5 }", "021A{len([ab]) < 5}"),
+        ("021A.a =? 'abc'", "021A.a =? 'abc'"),
+        ("021A.a =% 'abc def'", "021A.a =% 'abc def'"),
+        ("060R.b >= ALL 060R.a", "060R.b >= ALL 060R.a"),
+        ("060R.b == ANY 060R.a", "060R.b == 060R.a"),
+        ("ALL 044H.a =~ '^[A-Z]'", "ALL 044H.a =~ '^[A-Z]'"),
+        ("ANY 044H.a == 'x'", "044H.a == 'x'"),
+        ("044H{ ALL a == 'x' }", "044H{ALL a == 'x'}"),
+        ("ALL 044H{ 9? }", "ALL 044H{9?}"),
+        ("ALL 044H{ANY a == 'x'}", "ALL 044H{a == 'x'}"),
+        ("044H.ALL a in ['x']", "044H{ALL a in ['x']}"),
+        ("# fields>500", "#fields > 500"),
+        ("#bytes <= 10000", "#bytes <= 10000"),
+        ("!invalid_utf8?", "!invalid_utf8?"),
+        ("!duplicates( 044H/* )?", "!duplicates(044H/*)?"),
+        ("before(021A,028A)?", "before(021A, 028A)?"),
+        ("@copy{ 209A/*? && 209B/*? }", "@copy{209A/*? && 209B/*?}"),
+        ("ALL @local{209A/*.a == 'X'}", "ALL @local{209A/*.a == 'X'}"),
+        ("ANY @local{209A/*?}", "@local{209A/*?}"),
+        ("!@copy{209A?}", "!@copy{209A?}"),
+        ("!(#fields > 1)", "!(#fields > 1)"),
+        ("!(003@? && 002@?)", "!(003@? && 002@?)"),
+        ("(003@? || 002@?) && 012A?", "(003@? || 002@?) && 012A?"),
+    ] {
+        let matcher = FieldMatcher::new(expr)?;
+        assert_eq!(matcher.to_string(), expected);
+        assert_eq!(FieldMatcher::new(expected)?, matcher);
+    }
+
+    // Redundant parentheses are removed and the canonical form is
+    // stable.
+    for (expr, expected) in [
+        ("((003@?))", "003@?"),
+        ("!(003@?)", "!003@?"),
+        ("(003@? && 002@?) && 012A?", "003@? && 002@? && 012A?"),
+        ("003@? || (002@? && 012A?)", "003@? || 002@? && 012A?"),
+        ("!((003@? || 002@?))", "!(003@? || 002@?)"),
+        ("012A{(a? || b?) && ((c?))}", "012A{(a? || b?) && c?}"),
+        ("!003@? || (002@? && 012A?)", "!003@? || 002@? && 012A?"),
+        (
+            "((003@? || 002@?) && !012A?) || 013A?",
+            "(003@? || 002@?) && !012A? || 013A?",
+        ),
+        (
+            "012A{(a? || b?) && c? || !d?}",
+            "012A{(a? || b?) && c? || !d?}",
+        ),
+        ("(012A? && 013A?) ^ 014A?", "012A? && 013A? ^ 014A?"),
+        ("012A? || (013A? ^ 014A?)", "012A? || 013A? ^ 014A?"),
+        ("012A? => (013A? => 014A?)", "012A? => 013A? => 014A?"),
+    ] {
+        let matcher = FieldMatcher::new(expr)?;
+        assert_eq!(matcher.to_string(), expected);
+        assert_eq!(FieldMatcher::new(expected)?.to_string(), expected);
+    }
+
+    let matcher = !FieldMatcher::new("003@.0 == 'a'")?
+        & (FieldMatcher::new("002@?")? | FieldMatcher::new("012A?")?);
+    assert_eq!(
+        matcher.to_string(),
+        "!(003@.0 == 'a') && (002@? || 012A?)"
+    );
+
+    Ok(())
+}
@@ -35,3 +35,34 @@ fn test_occurrence_matcher_any() -> anyhow::Result<()> {
     assert!(matcher.is_match(&OccurrenceMut::new("001")));
     Ok(())
 }
+
+#[test]
+fn test_occurrence_matcher_list() -> anyhow::Result<()> {
+    let matcher = OccurrenceMatcher::new("/[01,05,09]")?;
+    assert!(matcher.is_match(&OccurrenceMut::new("01")));
+    assert!(matcher.is_match(&OccurrenceMut::new("09")));
+    assert!(!matcher.is_match(&OccurrenceMut::new("02")));
+    Ok(())
+}
+
+#[test]
+fn test_occurrence_matcher_comparison() -> anyhow::Result<()> {
+    let matcher = OccurrenceMatcher::new("/>=50")?;
+    assert!(matcher.is_match(&OccurrenceMut::new("50")));
+    assert!(matcher.is_match(&OccurrenceMut::new("120")));
+    assert!(!matcher.is_match(&OccurrenceMut::new("01")));
+
+    let matcher = OccurrenceMatcher::new("/<10")?;
+    assert!(matcher.is_match(&OccurrenceMut::new("09")));
+    assert!(!matcher.is_match(&OccurrenceMut::new("10")));
+    Ok(())
+}
+
+#[test]
+fn test_occurrence_matcher_non_zero() -> anyhow::Result<()> {
+    let matcher = OccurrenceMatcher::new("/+")?;
+    assert!(matcher.is_match(&OccurrenceMut::new("01")));
+    assert!(matcher.is_match(&OccurrenceMut::new("001")));
+    assert!(!matcher.is_match(&OccurrenceMut::new("00")));
+    Ok(())
+}
@@ -1,8 +1,12 @@
+use std::collections::BTreeMap;
 use std::path::Path;
 use std::{env, fs};
 
 use once_cell::sync::OnceCell;
-use pica_matcher::RecordMatcher;
+use pica_matcher::{
+    MatcherOptions, NormalizationForm, ParseContext, ParseMatcherError,
+    RecordMatcher,
+};
 use pica_record::RecordMut;
 
 fn ada_lovelace() -> &'static [u8] {
@@ -127,3 +131,541 @@ fn record_matcher_or() -> anyhow::Result<()> {
     assert!(matcher.is_match(&record, &Default::default()));
     Ok(())
 }
+
+#[test]
+fn record_matcher_xor() -> anyhow::Result<()> {
+    let record = RecordMut::from_bytes(ada_lovelace())?;
+
+    let matcher = RecordMatcher::new("028A.a == 'Lovelace' ^ 003@?")?;
+    assert!(!matcher.is_match(&record, &Default::default()));
+
+    let matcher =
+        RecordMatcher::new("028A.d == 'Ada' xor 028A.d == 'Ada King'")?;
+    assert!(matcher.is_match(&record, &Default::default()));
+
+    let matcher =
+        RecordMatcher::new("003@?")? ^ RecordMatcher::new("012A?")?;
+    assert_eq!(matcher.to_string(), "003@? ^ 012A?");
+    assert!(matcher.is_match(&record, &Default::default()));
+
+    // `xor` is a keyword, which must be followed by a non-alphanumeric
+    // character.
+    assert!(RecordMatcher::new("028A.a? xor003@?").is_err());
+    assert!(RecordMatcher::new("028A{a? xord?}").is_err());
+    assert!(RecordMatcher::new("028A.a? xor(003@?)").is_ok());
+
+    Ok(())
+}
+
+#[test]
+fn record_matcher_implies() -> anyhow::Result<()> {
+    let record = RecordMut::from_bytes(ada_lovelace())?;
+
+    let matcher = RecordMatcher::new("002@.0 =^ 'Tp' => 028A?")?;
+    assert!(matcher.is_match(&record, &Default::default()));
+
+    let matcher = RecordMatcher::new("002@.0 =^ 'Tp' implies 012A?")?;
+    assert!(!matcher.is_match(&record, &Default::default()));
+    assert!(RecordMatcher::new("002@.0 =^ 'Tp' implies012A?").is_err());
+
+    let matcher = RecordMatcher::new("002@.0 =^ 'Ts' => 012A?")?;
+    assert!(matcher.is_match(&record, &Default::default()));
+
+    let trace = matcher.trace(&record, &Default::default());
+    assert_eq!(trace.expr(), "=>");
+    assert!(trace.result());
+    assert!(!trace.children()[0].result());
+
+    Ok(())
+}
+
+#[test]
+fn record_matcher_in_file() -> anyhow::Result<()> {
+    let record = RecordMut::from_bytes(ada_lovelace())?;
+
+    let ctx = ParseContext::new().base_dir("tests/data");
+    let matcher =
+        RecordMatcher::with_context("002@.0 in @bbg.txt", &ctx)?;
+    assert!(matcher.is_match(&record, &Default::default()));
+
+    let matcher =
+        RecordMatcher::with_context("042@.a in @lang.csv", &ctx)?;
+    assert!(!matcher.is_match(&record, &Default::default()));
+
+    assert_eq!(
+        RecordMatcher::new("002@.0 in @bbg.txt").unwrap_err(),
+        ParseMatcherError::InvalidListFile(
+            "bbg.txt".into(),
+            "No such file or directory (os error 2)".into()
+        )
+    );
+
+    // The same list is used in nested and repeated references.
+    let matcher = RecordMatcher::with_context(
+        "(002@{0 in @bbg.txt && (0 in @bbg.txt || 0 in @bbg.txt)}) \
+        && !(002@.0 not in @bbg.txt)",
+        &ctx,
+    )?;
+    assert!(matcher.is_match(&record, &Default::default()));
+
+    // Lists aren't read, if the expression is only parsed.
+    let matcher = RecordMatcher::parse("002@.0 in @missing.txt")?;
+    assert_eq!(matcher.to_string(), "002@.0 in @missing.txt");
+
+    Ok(())
+}
+
+#[test]
+fn record_matcher_definitions() -> anyhow::Result<()> {
+    let record = RecordMut::from_bytes(ada_lovelace())?;
+
+    let matcher = RecordMatcher::new(
+        "let persons = 002@.0 =^ 'Tp'; \
+         let lovelace = $persons && 028A.a == 'Lovelace'; \
+         $lovelace && !$persons",
+    )?;
+    assert!(!matcher.is_match(&record, &Default::default()));
+
+    let matcher = RecordMatcher::new(
+        "let name = 028A.a == 'Lovelace;'; \
+         $name || 003@ $0 == '119232022'",
+    )?;
+    assert!(matcher.is_match(&record, &Default::default()));
+
+    let ctx = ParseContext::new()
+        .definitions(BTreeMap::from([
+            ("persons".to_string(), "002@.0 =^ 'Tp'".to_string()),
+            (
+                "lovelace".to_string(),
+                "028A.a == 'Lovelace'".to_string(),
+            ),
+            ("ada".to_string(), "d =^ 'Ada'".to_string()),
+            ("cyclic".to_string(), "003@? && %cyclic".to_string()),
+        ]))
+        .base_dir("tests/data");
+
+    let matcher = RecordMatcher::with_context(
+        "%persons && (%lovelace || 028A.d == 'Ada')",
+        &ctx,
+    )?;
+    assert!(matcher.is_match(&record, &Default::default()));
+
+    let matcher = RecordMatcher::with_context(
+        "let ada = 028A.d =^ 'Ada'; %persons && $ada",
+        &ctx,
+    )?;
+    assert!(matcher.is_match(&record, &Default::default()));
+
+    let matcher = RecordMatcher::with_context(
+        "%persons && 002@.0 in @bbg.txt",
+        &ctx,
+    )?;
+    assert!(matcher.is_match(&record, &Default::default()));
+
+    let matcher = RecordMatcher::with_context(
+        "@copy{%persons} || 028A.a == '%lovelace'",
+        &ctx,
+    )?;
+    assert!(!matcher.is_match(&record, &Default::default()));
+
+    // Definitions of subfield matcher expressions can be used within
+    // curly brackets.
+    let matcher = RecordMatcher::with_context(
+        "let ada = d =^ 'Ada'; 028A{$ada && a == 'Lovelace'} \
+         && (%persons^%lovelace)",
+        &ctx,
+    )?;
+    assert!(!matcher.is_match(&record, &Default::default()));
+
+    let matcher =
+        RecordMatcher::with_context("028A{%ada && a?}", &ctx)?;
+    assert!(matcher.is_match(&record, &Default::default()));
+
+    // References are operands; they can't be used as arguments.
+    let Err(ParseMatcherError::InvalidRecordMatcher(_)) =
+        RecordMatcher::with_context("before(003@,%persons)?", &ctx)
+    else {
+        panic!("expected syntax error");
+    };
+
+    let matcher =
+        RecordMatcher::with_context("%persons && 00[23]@?", &ctx)?;
+    assert!(matcher.is_match(&record, &Default::default()));
+
+    assert!(RecordMatcher::new("%persons").is_err());
+    assert!(RecordMatcher::new("@persons && 003@?").is_err());
+    assert!(RecordMatcher::new("$persons && 003@?").is_err());
+    assert!(RecordMatcher::new("let a = 003@?; $a").is_err());
+    assert!(RecordMatcher::new("let p = 003@? $p").is_err());
+    assert!(RecordMatcher::new(
+        "let persons = 003@?; let persons = 028A?; $persons"
+    )
+    .is_err());
+    assert!(RecordMatcher::new(
+        "let persons = $lovelace; let lovelace = 028A?; $persons"
+    )
+    .is_err());
+    assert!(RecordMatcher::new("let ada = d?; $ada").is_err());
+    assert!(RecordMatcher::with_context("%cyclic", &ctx).is_err());
+    assert!(RecordMatcher::with_context("%ada", &ctx).is_err());
+    assert!(RecordMatcher::with_context("%unknown || %persons", &ctx)
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn record_matcher_syntax_error() {
+    let Err(ParseMatcherError::InvalidRecordMatcher(err)) =
+        RecordMatcher::new("003@.0 == 'a' &&\n012A/01.0 =~ '['")
+    else {
+        panic!("expected syntax error");
+    };
+
+    assert_eq!(err.offset(), 30);
+    assert_eq!((err.line(), err.column()), (2, 14));
+    assert_eq!(err.expected(), ["regular expression"]);
+    assert_eq!(
+        err.to_string(),
+        "unexpected input, expected regular expression at line 2, \
+        column 14\n  |\n2 | 012A/01.0 =~ '['\n  |              ^"
+    );
+
+    let Err(ParseMatcherError::InvalidRecordMatcher(err)) =
+        RecordMatcher::new("003@.0 == 'a' && 01XA.0 == 'b'")
+    else {
+        panic!("expected syntax error");
+    };
+
+    assert_eq!(err.column(), 18);
+    assert!(err.expected().contains(&"tag".to_string()));
+
+    let Err(ParseMatcherError::InvalidRecordMatcher(err)) =
+        RecordMatcher::new("003@{0 == 'a' && 1 == }")
+    else {
+        panic!("expected syntax error");
+    };
+
+    assert_eq!(err.column(), 23);
+    assert!(err.expected().contains(&"string literal".to_string()));
+}
+
+#[test]
+fn record_matcher_definitions_syntax_error() {
+    let Err(ParseMatcherError::InvalidRecordMatcher(err)) =
+        RecordMatcher::new(
+            "let persons = 002@.0 =^ 'Tp'; $persons && 012A.a ==",
+        )
+    else {
+        panic!("expected syntax error");
+    };
+
+    assert_eq!(err.offset(), 51);
+    assert_eq!((err.line(), err.column()), (1, 52));
+    assert!(err
+        .to_string()
+        .starts_with("unexpected end of expression"));
+
+    let Err(ParseMatcherError::InvalidRecordMatcher(err)) =
+        RecordMatcher::new(
+            "let persons =\n    002@.0 =~ '[';\n$persons && 028A?",
+        )
+    else {
+        panic!("expected syntax error");
+    };
+
+    assert_eq!((err.line(), err.column()), (2, 15));
+    assert_eq!(
+        err.to_string(),
+        "unexpected input, expected regular expression at line 2, \
+        column 15\n  |\n2 |     002@.0 =~ '[';\n  |               ^"
+    );
+
+    let ctx = ParseContext::new().definitions(BTreeMap::from([(
+        "persons".to_string(),
+        "002@.0 =^".to_string(),
+    )]));
+
+    let Err(ParseMatcherError::InvalidRecordMatcher(err)) =
+        RecordMatcher::with_context("003@? &&\n  %persons", &ctx)
+    else {
+        panic!("expected syntax error");
+    };
+
+    assert_eq!((err.line(), err.column()), (2, 3));
+    assert_eq!(
+        err.message(),
+        "invalid filter `%persons`: unexpected end of expression, \
+        expected tag or string literal"
+    );
+
+    let Err(ParseMatcherError::InvalidRecordMatcher(err)) =
+        RecordMatcher::new("let persons = 002@?;\n$person && 028A?")
+    else {
+        panic!("expected syntax error");
+    };
+
+    assert_eq!((err.line(), err.column()), (2, 1));
+    assert_eq!(err.message(), "`$person` is not defined");
+}
+
+#[test]
+fn record_matcher_trace() -> anyhow::Result<()> {
+    let record = RecordMut::from_bytes(ada_lovelace())?;
+    let options = Default::default();
+
+    let matcher = RecordMatcher::new(
+        "002@.0 =^ 'Tp' && 028A{d =^ 'Ada' && a == 'Lovelace'} \
+            && (#003@ > 1 || 050E.a =^ 'http') && !065R{4 == 'ortg'}",
+    )?;
+
+    let trace = matcher.trace(&record, &options);
+    assert_eq!(trace.result(), matcher.is_match(&record, &options));
+    assert!(!trace.result());
+    assert_eq!(trace.expr(), "&&");
+
+    let children = trace.children();
+    assert_eq!(children.len(), 4);
+
+    assert_eq!(children[0].expr(), "002@.0 =^ 'Tp'");
+    assert!(children[0].result());
+    assert_eq!(children[0].matches(), ["002@"]);
+    assert_eq!(children[0].children()[0].expr(), "002@");
+    assert_eq!(children[0].children()[0].matches(), ["$0 Tp1"]);
+
+    assert_eq!(
+        children[1].expr(),
+        "028A{d =^ 'Ada' && a == 'Lovelace'}"
+    );
+    assert!(children[1].result());
+
+    assert_eq!(children[2].expr(), "||");
+    assert!(children[2].result());
+    assert_eq!(children[2].children()[0].expr(), "#003@ > 1");
+    assert!(!children[2].children()[0].result());
+    assert_eq!(children[2].children()[0].matches(), ["003@"]);
+    assert_eq!(children[2].children()[1].matches(), ["050E"]);
+
+    assert_eq!(children[3].expr(), "!");
+    assert!(!children[3].result());
+    assert_eq!(children[3].children()[0].expr(), "065R.4 == 'ortg'");
+    assert!(children[3].children()[0].result());
+
+    Ok(())
+}
+
+/// Splits the arguments of a snapshot test (`args = "..."`) like a
+/// shell, which is sufficient for the quoting used by the snapshots.
+fn snapshot_args(config: &str) -> Vec<String> {
+    let Some(args) = config
+        .lines()
+        .find_map(|line| line.strip_prefix("args = \""))
+        .and_then(|args| args.strip_suffix('"'))
+    else {
+        return vec![];
+    };
+
+    let args = args.replace("\\\"", "\"").replace("\\\\", "\\");
+    let mut result = vec![];
+    let mut current = String::new();
+    let mut quote = None;
+
+    for c in args.chars() {
+        match (quote, c) {
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, ' ') => {
+                if !current.is_empty() {
+                    result.push(std::mem::take(&mut current));
+                }
+            }
+            (_, c) => current.push(c),
+        }
+    }
+
+    if !current.is_empty() {
+        result.push(current);
+    }
+
+    result
+}
+
+#[test]
+fn record_matcher_trace_consistency() -> anyhow::Result<()> {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR")?;
+    let snapshots =
+        Path::new(&manifest_dir).join("../tests/snapshot/filter");
+
+    let options = [
+        MatcherOptions::default(),
+        MatcherOptions::new().case_ignore(true),
+        MatcherOptions::new()
+            .normalization(Some(NormalizationForm::Nfd))
+            .diacritic_ignore(true)
+            .case_fold(true),
+    ];
+
+    let mut num_exprs = 0;
+    for entry in fs::read_dir(snapshots)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("toml")
+        {
+            continue;
+        }
+
+        // Snapshots without records on stdin read gzipped dumps, so
+        // their expressions are evaluated against a fixed record.
+        let data = fs::read(path.with_extension("stdin"))
+            .unwrap_or_else(|_| ada_lovelace().to_vec());
+        let records: Vec<RecordMut> = data
+            .split_inclusive(|b| *b == b'\n')
+            .filter_map(|line| RecordMut::from_bytes(line).ok())
+            .collect();
+
+        let config = fs::read_to_string(&path)?;
+        for arg in snapshot_args(&config) {
+            let Ok(matcher) = RecordMatcher::new(&arg) else {
+                continue;
+            };
+
+            for record in records.iter() {
+                for options in options.iter() {
+                    assert_eq!(
+                        matcher.trace(record, options).result(),
+                        matcher.is_match(record, options),
+                        "{}: {arg}",
+                        path.display()
+                    );
+                }
+            }
+
+            num_exprs += 1;
+        }
+    }
+
+    assert!(num_exprs > 100);
+    Ok(())
+}
+
+#[test]
+fn record_matcher_display() -> anyhow::Result<()> {
+    let matcher = RecordMatcher::new("003@ $0 == \"123\" && (002@?)")?;
+    assert_eq!(matcher.to_string(), "003@.0 == '123' && 002@?");
+
+    let matcher = (RecordMatcher::new("003@? || 002@?")?
+        & !RecordMatcher::new("012A?")?)
+        | RecordMatcher::new("013A?")?;
+    assert_eq!(
+        matcher.to_string(),
+        "(003@? || 002@?) && !012A? || 013A?"
+    );
+    assert_eq!(
+        RecordMatcher::new(&matcher.to_string())?.to_string(),
+        matcher.to_string()
+    );
+
+    let ctx = ParseContext::new().base_dir("tests/data");
+    let matcher =
+        RecordMatcher::with_context("002@.0 in @'bbg.txt'", &ctx)?;
+    assert_eq!(matcher.to_string(), "002@.0 in @bbg.txt");
+
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn record_matcher_serialize() -> anyhow::Result<()> {
+    use serde_json::json;
+
+    let matcher = RecordMatcher::new(
+        "003@? && (002@.0 =^ 'Tp' && !012A/01{a? || #b > 1}) \
+            && 060R.b >= ALL 060R.a",
+    )?;
+
+    assert_eq!(
+        serde_json::to_value(matcher.field_matcher())?,
+        json!({
+            "type": "and",
+            "operands": [
+                {"type": "exists", "tag": "003@", "occurrence": null},
+                {
+                    "type": "subfields",
+                    "tag": "002@",
+                    "occurrence": null,
+                    "quantifier": "ANY",
+                    "subfields": {
+                        "type": "relation",
+                        "quantifier": "ANY",
+                        "codes": ["0"],
+                        "op": "=^",
+                        "value": "Tp"
+                    }
+                },
+                {
+                    "type": "not",
+                    "operand": {
+                        "type": "subfields",
+                        "tag": "012A",
+                        "occurrence": "01",
+                        "quantifier": "ANY",
+                        "subfields": {
+                            "type": "or",
+                            "operands": [
+                                {"type": "exists", "codes": ["a"]},
+                                {
+                                    "type": "cardinality",
+                                    "codes": ["b"],
+                                    "op": ">",
+                                    "value": 1
+                                }
+                            ]
+                        }
+                    }
+                },
+                {
+                    "type": "reference",
+                    "lhs": {
+                        "tag": "060R",
+                        "occurrence": null,
+                        "codes": ["b"]
+                    },
+                    "op": ">=",
+                    "quantifier": "ALL",
+                    "rhs": {
+                        "tag": "060R",
+                        "occurrence": null,
+                        "codes": ["a"]
+                    }
+                }
+            ]
+        })
+    );
+
+    let ctx = ParseContext::new().base_dir("tests/data");
+    let matcher =
+        RecordMatcher::with_context("002@.0 not in @bbg.txt", &ctx)?;
+    let value = serde_json::to_value(matcher.field_matcher())?;
+    assert_eq!(value["subfields"]["file"], "bbg.txt");
+    assert_eq!(value["subfields"]["invert"], true);
+    assert!(value["subfields"]["values"].as_array().unwrap().len() > 1);
+
+    assert_eq!(
+        serde_json::to_value(&matcher)?,
+        json!("002@.0 not in @bbg.txt")
+    );
+
+    let matcher = RecordMatcher::parse("002@.0 in @missing.txt")?;
+    let value = serde_json::to_value(matcher.field_matcher())?;
+    assert_eq!(value["subfields"]["file"], "missing.txt");
+    assert!(value["subfields"].get("values").is_none());
+
+    let matcher =
+        RecordMatcher::new("003@? => 002@? => 012A? ^ 013A?")?;
+    let value = serde_json::to_value(matcher.field_matcher())?;
+    assert_eq!(value["type"], "implies");
+    assert_eq!(value["operands"][0]["type"], "exists");
+    assert_eq!(value["operands"][1]["type"], "implies");
+    assert_eq!(value["operands"][1]["operands"][1]["type"], "xor");
+
+    Ok(())
+}
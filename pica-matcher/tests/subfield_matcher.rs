@@ -2,7 +2,7 @@ use pica_matcher::subfield_matcher::{
     CardinalityMatcher, ExistsMatcher, InMatcher, Matcher,
     RegexMatcher, RelationMatcher, SubfieldMatcher,
 };
-use pica_matcher::MatcherOptions;
+use pica_matcher::{MatcherOptions, NormalizationForm, StrsimMetric};
 use pica_record::SubfieldRef;
 
 #[test]
@@ -274,6 +274,247 @@ fn relational_matcher_similar() -> anyhow::Result<()> {
         &options
     ));
 
+    // similarity metrics
+    let matcher = RelationMatcher::new("a =* 'Heike'")?;
+    let options =
+        MatcherOptions::new().strsim_metric(StrsimMetric::JaroWinkler);
+    assert!(matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1faHeiko")?, &options));
+    assert!(!matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1faHans")?, &options));
+
+    let options = MatcherOptions::new().strsim_threshold(0.75);
+    assert!(!matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1faHieke")?, &options));
+
+    let options = options.strsim_metric("damerau-levenshtein".parse()?);
+    assert!(matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1faHieke")?, &options));
+
+    let matcher = RelationMatcher::new("a =* 'Ada Lovelace'")?;
+    let options = MatcherOptions::default();
+    assert!(!matcher.is_match(
+        &SubfieldRef::from_bytes(b"\x1faLovelace, Ada")?,
+        &options
+    ));
+
+    let options = options.strsim_metric(StrsimMetric::TokenSet);
+    assert!(matcher.is_match(
+        &SubfieldRef::from_bytes(b"\x1faLovelace, Ada")?,
+        &options
+    ));
+    assert!(!matcher.is_match(
+        &SubfieldRef::from_bytes(b"\x1faByron, George")?,
+        &options
+    ));
+    assert!(!matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1fa-")?, &options));
+    assert_eq!(StrsimMetric::TokenSet.score("", ""), 0.0);
+
+    assert!("jaro".parse::<StrsimMetric>().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn relational_matcher_integer() -> anyhow::Result<()> {
+    let matcher = RelationMatcher::new("a > 999")?;
+    let options = MatcherOptions::default();
+
+    assert!(matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1fa1000")?, &options));
+    assert!(!matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1fa999")?, &options));
+    assert!(!matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1faabc")?, &options));
+
+    let matcher = RelationMatcher::new("a == 1990")?;
+    assert!(matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1fa01990")?, &options));
+
+    let matcher = RelationMatcher::new("a != 1990")?;
+    assert!(matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1fa1991")?, &options));
+    assert!(!matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1fa1990")?, &options));
+
+    // values, which can't be parsed, are unequal to any integer
+    assert!(matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1fa19xx")?, &options));
+    let matcher = RelationMatcher::new("a == 1990")?;
+    assert!(!matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1fa19xx")?, &options));
+
+    let matcher = RelationMatcher::new("a <= -1")?;
+    assert!(matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1fa-10")?, &options));
+
+    assert!(RelationMatcher::new("a > abc").is_err());
+    assert!(RelationMatcher::new("a > '1990'").is_err());
+    assert!(RelationMatcher::new("a > 1990.5").is_err());
+    assert!(RelationMatcher::new("a =^ 1990").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn relational_matcher_contains() -> anyhow::Result<()> {
+    // case sensitive
+    let matcher = RelationMatcher::new("a =? 'Love'")?;
+    let options = MatcherOptions::default();
+
+    assert!(matcher
+        .is_match(&SubfieldRef::new('a', "Ada Lovelace"), &options));
+    assert!(!matcher
+        .is_match(&SubfieldRef::new('a', "Ada LOVELACE"), &options));
+    assert!(matcher.is_match(&SubfieldRef::new('a', "Love"), &options));
+
+    // case insensitive
+    let options = MatcherOptions::new().case_ignore(true);
+    assert!(matcher
+        .is_match(&SubfieldRef::new('a', "Ada LOVELACE"), &options));
+
+    // unicode options
+    let matcher = RelationMatcher::new("a =? 'Muller'")?;
+    let options = MatcherOptions::new().diacritic_ignore(true);
+    assert!(matcher.is_match(
+        &SubfieldRef::new('a', "Hans Müller-Lüdenscheid"),
+        &options
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn relational_matcher_contains_word() -> anyhow::Result<()> {
+    let matcher = RelationMatcher::new("a =% 'Ada Lovelace'")?;
+    let options = MatcherOptions::default();
+
+    for (value, expected) in [
+        ("Augusta Ada Lovelace", true),
+        ("Ada Lovelace (1815-1852)", true),
+        ("Ada  Lovelace", true),
+        ("Ada, Lovelace", true),
+        ("Adam Lovelace", false),
+        ("Lovelace, Ada", false),
+        ("ada lovelace", false),
+    ] {
+        assert_eq!(
+            matcher.is_match(&SubfieldRef::new('a', value), &options),
+            expected,
+            "{value}"
+        );
+    }
+
+    let matcher = RelationMatcher::new("a =% 'LOVE'")?;
+    let options = MatcherOptions::new().case_ignore(true);
+    assert!(matcher
+        .is_match(&SubfieldRef::new('a', "love story"), &options));
+    assert!(
+        !matcher.is_match(&SubfieldRef::new('a', "Lovelace"), &options)
+    );
+
+    let matcher = RelationMatcher::new("a =% ''")?;
+    assert!(!matcher.is_match(&SubfieldRef::new('a', "abc"), &options));
+
+    Ok(())
+}
+
+#[test]
+fn relational_matcher_length() -> anyhow::Result<()> {
+    let matcher = RelationMatcher::new("len(a) > 5")?;
+    let options = MatcherOptions::default();
+
+    assert!(
+        matcher.is_match(&SubfieldRef::new('a', "abcdef"), &options)
+    );
+    assert!(
+        !matcher.is_match(&SubfieldRef::new('a', "abcde"), &options)
+    );
+    assert!(
+        !matcher.is_match(&SubfieldRef::new('b', "abcdef"), &options)
+    );
+
+    // characters, not bytes
+    let matcher = RelationMatcher::new("len([ab]) == 6")?;
+    assert!(
+        matcher.is_match(&SubfieldRef::new('b', "Müller"), &options)
+    );
+
+    let options = MatcherOptions::new()
+        .normalization(Some(NormalizationForm::Nfd));
+    assert!(
+        !matcher.is_match(&SubfieldRef::new('b', "Müller"), &options)
+    );
+
+    assert!(RelationMatcher::new("len(a) > -1").is_err());
+    assert!(RelationMatcher::new("len(a) =^ 1").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn relational_matcher_date() -> anyhow::Result<()> {
+    let matcher =
+        RelationMatcher::new("date(0, '%d-%m-%y') >= '2020-01-01'")?;
+    let options = MatcherOptions::default();
+
+    assert!(matcher.is_match(
+        &SubfieldRef::from_bytes(b"\x1f001-01-20")?,
+        &options
+    ));
+    assert!(matcher.is_match(
+        &SubfieldRef::from_bytes(b"\x1f015-06-21")?,
+        &options
+    ));
+    assert!(!matcher.is_match(
+        &SubfieldRef::from_bytes(b"\x1f031-12-19")?,
+        &options
+    ));
+    assert!(!matcher.is_match(
+        &SubfieldRef::from_bytes(b"\x1f02020-01-01")?,
+        &options
+    ));
+
+    let matcher =
+        RelationMatcher::new("date([ab], '%Y%m%d') < '2000-01-01'")?;
+    assert!(matcher.is_match(
+        vec![
+            &SubfieldRef::from_bytes(b"\x1fa20010101")?,
+            &SubfieldRef::from_bytes(b"\x1fb19991231")?,
+        ],
+        &options
+    ));
+
+    let matcher =
+        RelationMatcher::new("date(0, '%d-%m-%y') != '2020-01-01'")?;
+    assert!(matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1f0abc")?, &options));
+    assert!(!matcher.is_match(
+        &SubfieldRef::from_bytes(b"\x1f001-01-20")?,
+        &options
+    ));
+
+    assert!(
+        RelationMatcher::new("date(0, '%d') > '2020-13-01'").is_err()
+    );
+    assert!(
+        RelationMatcher::new("date(0, '%Y') > '2020-01-01'").is_err()
+    );
+    assert!(RelationMatcher::new("date(0, '%Y-%m') > '2020-01-01'")
+        .is_err());
+    assert!(RelationMatcher::new("date(0, '') > '2020-01-01'").is_err());
+    assert!(
+        RelationMatcher::new("date(0, '%Y-%j') > '2020-01-01'").is_ok()
+    );
+    assert!(RelationMatcher::new(
+        "date(0, '%d.%m.%Y %H:%M') > '2020-01-01'"
+    )
+    .is_ok());
+    assert!(
+        RelationMatcher::new("date(0, '%!') > '2020-01-01'").is_err()
+    );
+
     Ok(())
 }
 
@@ -389,6 +630,152 @@ fn in_matcher() -> anyhow::Result<()> {
         &options
     ));
 
+    let matcher = InMatcher::new("0 in @tests/data/bbg.txt")?;
+    let options = MatcherOptions::default();
+
+    assert!(matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1f0Tu1")?, &options));
+    assert!(!matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1f0Tb1")?, &options));
+    assert!(!matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1f0tp1")?, &options));
+
+    let options = MatcherOptions::new().case_ignore(true);
+    assert!(matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1f0tp1")?, &options));
+
+    let matcher = InMatcher::new("a not in @'tests/data/lang.csv'")?;
+    let options = MatcherOptions::default();
+
+    assert!(matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1fafre")?, &options));
+    assert!(!matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1faeng")?, &options));
+    assert!(matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1facode")?, &options));
+
+    assert!(InMatcher::new("0 in @tests/data/missing.txt").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn quantifier() -> anyhow::Result<()> {
+    let subfields = [
+        SubfieldRef::from_bytes(b"\x1faAbc")?,
+        SubfieldRef::from_bytes(b"\x1faBcd")?,
+        SubfieldRef::from_bytes(b"\x1fbefg")?,
+    ];
+    let options = MatcherOptions::default();
+
+    for (expr, expected) in [
+        ("a =~ '^[A-Z]'", true),
+        ("ALL a =~ '^[A-Z]'", true),
+        ("ANY a =~ '^A'", true),
+        ("ALL a =~ '^A'", false),
+        ("ALL [ab] =~ '^[A-Z]'", false),
+        ("ALL a !~ '^X'", true),
+        ("ALL a == 'Abc'", false),
+        ("ALL a != 'Xyz'", true),
+        ("ALL a in ['Abc', 'Bcd']", true),
+        ("ALL [ab] in ['Abc', 'Bcd']", false),
+        ("ALL a not in ['Abc']", false),
+        ("ALL c == 'x'", false),
+        ("ALL c != 'x'", false),
+        ("ALL c =~ 'x'", false),
+        ("ALL c in ['x']", false),
+    ] {
+        let matcher = SubfieldMatcher::new(expr)?;
+        assert_eq!(
+            matcher.is_match(&subfields, &options),
+            expected,
+            "{expr}"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn unicode_options() -> anyhow::Result<()> {
+    let nfc = SubfieldRef::new('a', "M\u{fc}ller");
+    let nfd = SubfieldRef::new('a', "Mu\u{308}ller");
+
+    // normalization
+    let matcher = RelationMatcher::new("a == 'M\u{fc}ller'")?;
+    assert!(matcher.is_match(&nfc, &MatcherOptions::default()));
+    assert!(!matcher.is_match(&nfd, &MatcherOptions::default()));
+
+    for nf in ["nfc", "nfd", "nfkc", "nfkd"] {
+        let options =
+            MatcherOptions::new().normalization(Some(nf.parse()?));
+        assert!(matcher.is_match(&nfc, &options));
+        assert!(matcher.is_match(&nfd, &options));
+    }
+
+    // diacritics
+    let matcher = RelationMatcher::new("a == 'Muller'")?;
+    let options = MatcherOptions::new().diacritic_ignore(true);
+    assert!(!matcher.is_match(&nfc, &MatcherOptions::default()));
+    assert!(matcher.is_match(&nfc, &options));
+    assert!(matcher.is_match(&nfd, &options));
+
+    let matcher = RelationMatcher::new("a =^ 'Mul'")?;
+    assert!(matcher.is_match(&nfd, &options));
+
+    let matcher = RegexMatcher::new("a =~ '^M[u]ll'")?;
+    assert!(matcher.is_match(&nfc, &options));
+    assert!(matcher.is_match(&nfd, &options));
+
+    // A combining mark inside a character class is kept as it is.
+    let matcher = RegexMatcher::new("a =~ '^Mu[\u{308}]?ller'")?;
+    assert!(matcher.is_match(&nfc, &options));
+    assert!(matcher.is_match(&nfd, &options));
+    assert!(matcher.is_match(&nfd, &MatcherOptions::default()));
+
+    let matcher = RegexMatcher::new("a =~ '^M\u{fc}ller'")?;
+    assert!(!matcher.is_match(&nfc, &options));
+
+    // The values are normalized per options, so the same matcher can
+    // be used with different options.
+    let matcher = InMatcher::new("a in ['M\u{fc}ller', 'Meier']")?;
+    assert!(matcher.is_match(&nfd, &options));
+    assert!(!matcher.is_match(
+        &nfd,
+        &options.clone().case_fold(true).diacritic_ignore(false)
+    ));
+    assert!(matcher.is_match(
+        &nfd,
+        &MatcherOptions::new()
+            .normalization(Some(NormalizationForm::Nfc))
+    ));
+    assert!(matcher.is_match(&nfd, &options));
+
+    // case folding
+    let subfield = SubfieldRef::new('a', "Stra\u{df}e");
+    let matcher = RelationMatcher::new("a == 'STRASSE'")?;
+    assert!(!matcher
+        .is_match(&subfield, &MatcherOptions::new().case_ignore(true)));
+    assert!(matcher
+        .is_match(&subfield, &MatcherOptions::new().case_fold(true)));
+
+    let matcher = RegexMatcher::new("a =~ '^STRA'")?;
+    assert!(matcher
+        .is_match(&subfield, &MatcherOptions::new().case_fold(true)));
+
+    // all options
+    let options = MatcherOptions::new()
+        .normalization(Some(NormalizationForm::Nfc))
+        .diacritic_ignore(true)
+        .case_fold(true);
+
+    let matcher = RelationMatcher::new("a == 'MULLER'")?;
+    assert!(matcher.is_match(&nfc, &options));
+    assert!(matcher.is_match(&nfd, &options));
+
+    let matcher = RelationMatcher::new("a =* 'MULER'")?;
+    assert!(matcher.is_match(&nfd, &options));
+
     Ok(())
 }
 
@@ -560,6 +947,46 @@ fn cardinality_matcher_lt() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn cardinality_matcher_count() -> anyhow::Result<()> {
+    let options = MatcherOptions::default();
+
+    let matcher = CardinalityMatcher::new("#[ab] == 2")?;
+    assert!(matcher.is_match(
+        vec![
+            &SubfieldRef::from_bytes(b"\x1faabc")?,
+            &SubfieldRef::from_bytes(b"\x1fbdef")?,
+            &SubfieldRef::from_bytes(b"\x1f0hij")?,
+        ],
+        &options
+    ));
+
+    let matcher = CardinalityMatcher::new("#a > #b")?;
+    assert!(matcher.is_match(
+        vec![
+            &SubfieldRef::from_bytes(b"\x1faabc")?,
+            &SubfieldRef::from_bytes(b"\x1fadef")?,
+            &SubfieldRef::from_bytes(b"\x1fbhij")?,
+        ],
+        &options
+    ));
+    assert!(!matcher.is_match(
+        vec![
+            &SubfieldRef::from_bytes(b"\x1faabc")?,
+            &SubfieldRef::from_bytes(b"\x1fbhij")?,
+        ],
+        &options
+    ));
+
+    let matcher = CardinalityMatcher::new("#* == #[ab]")?;
+    assert!(matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1faabc"), &options));
+    assert!(!matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1f0abc"), &options));
+
+    Ok(())
+}
+
 #[test]
 fn subfield_matcher_not() -> anyhow::Result<()> {
     // group
@@ -781,3 +1208,77 @@ fn subfield_matcher_and() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn subfield_matcher_xor() -> anyhow::Result<()> {
+    let matcher = SubfieldMatcher::new("a? ^ b?")?;
+    let options = MatcherOptions::default();
+
+    assert!(matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1faabc")?, &options));
+    assert!(matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1fbabc")?, &options));
+    assert!(!matcher.is_match(
+        [
+            &SubfieldRef::from_bytes(b"\x1faabc")?,
+            &SubfieldRef::from_bytes(b"\x1fbabc")?,
+        ],
+        &options
+    ));
+    assert!(!matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1fcabc")?, &options));
+
+    // `&&` binds tighter than `xor`
+    let matcher = SubfieldMatcher::new("a? xor b? && c?")?;
+    assert!(matcher.is_match(
+        [
+            &SubfieldRef::from_bytes(b"\x1faabc")?,
+            &SubfieldRef::from_bytes(b"\x1fbabc")?,
+        ],
+        &options
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn subfield_matcher_implies() -> anyhow::Result<()> {
+    let matcher = SubfieldMatcher::new("a =^ 'ab' => b?")?;
+    let options = MatcherOptions::default();
+
+    assert!(matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1facde")?, &options));
+    assert!(!matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1faabc")?, &options));
+    assert!(matcher.is_match(
+        [
+            &SubfieldRef::from_bytes(b"\x1faabc")?,
+            &SubfieldRef::from_bytes(b"\x1fbabc")?,
+        ],
+        &options
+    ));
+
+    // `||` binds tighter than `implies`
+    let matcher = SubfieldMatcher::new("a? || b? implies c?")?;
+    assert!(!matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1fbabc")?, &options));
+    assert!(matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1fcabc")?, &options));
+
+    // `=>` is right-associative
+    let matcher = SubfieldMatcher::new("a? => b? => c?")?;
+    assert!(matcher
+        .is_match(&SubfieldRef::from_bytes(b"\x1fbabc")?, &options));
+    assert!(!matcher.is_match(
+        [
+            &SubfieldRef::from_bytes(b"\x1faabc")?,
+            &SubfieldRef::from_bytes(b"\x1fbabc")?,
+        ],
+        &options
+    ));
+
+    assert!(SubfieldMatcher::new("a? =>").is_err());
+    assert!(SubfieldMatcher::new("a? ^ ^ b?").is_err());
+
+    Ok(())
+}
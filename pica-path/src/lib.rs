@@ -1,28 +1,50 @@
 use std::str::FromStr;
 
+#[cfg(feature = "serde")]
+use bstr::ByteSlice;
 use nom::branch::alt;
-use nom::character::complete::{char, multispace0};
-use nom::combinator::{all_consuming, map, opt};
+use nom::bytes::complete::tag;
+use nom::character::complete::{char, digit1, multispace0};
+use nom::combinator::{all_consuming, map, map_res, opt, recognize};
 use nom::error::ParseError;
 use nom::multi::{many1, separated_list1};
 use nom::sequence::{delimited, pair, preceded, terminated, tuple};
 use nom::{Finish, IResult};
 use pica_matcher::parser::{
-    parse_occurrence_matcher, parse_tag_matcher,
+    parse_occurrence_matcher, parse_subfield_code, parse_tag_matcher,
+    ParseResult,
 };
 use pica_matcher::subfield_matcher::{parse_subfield_matcher, Matcher};
 use pica_matcher::{
-    MatcherOptions, OccurrenceMatcher, SubfieldMatcher, TagMatcher,
+    MatcherOptions, OccurrenceMatcher, ParseContext, ParseMatcherError,
+    SubfieldMatcher, SyntaxError, TagMatcher,
 };
-use pica_record::parser::{parse_subfield_code, ParseResult};
-use pica_record::Record;
+use pica_record::{Field, Occurrence, Record, Tag};
+#[cfg(feature = "serde")]
+use serde::ser::SerializeMap;
 #[cfg(feature = "serde")]
-use serde::Deserialize;
+use serde::{Deserialize, Serialize, Serializer};
 use thiserror::Error;
 
+/// An error that can occur when parsing path expressions.
 #[derive(Debug, Error)]
-#[error("invalid path expression, got `{0}`")]
-pub struct ParsePathError(String);
+pub enum ParsePathError {
+    #[error("invalid path expression: {0}")]
+    Syntax(SyntaxError),
+    #[error("invalid path expression: {0}")]
+    Matcher(ParseMatcherError),
+}
+
+impl ParsePathError {
+    /// Returns the syntax error of the path expression, if the
+    /// expression couldn't be parsed.
+    pub fn syntax_error(&self) -> Option<&SyntaxError> {
+        match self {
+            Self::Syntax(e) => Some(e),
+            Self::Matcher(_) => None,
+        }
+    }
+}
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct Path {
@@ -30,6 +52,9 @@ pub struct Path {
     occurrence_matcher: OccurrenceMatcher,
     subfield_matcher: Option<SubfieldMatcher>,
     codes: Vec<char>,
+    field_index: Option<isize>,
+    value_index: Option<isize>,
+    fallback: Option<Box<Path>>,
 }
 
 impl Path {
@@ -53,6 +78,47 @@ impl Path {
     pub fn new(data: &str) -> Self {
         Self::from_str(data).expect("valid path expression.")
     }
+
+    /// Parses the path expression without reading the lists of list
+    /// file references (`0 in @codes.txt`), which are read afterwards
+    /// by [Path::read_lists].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use pica_matcher::ParseContext;
+    /// use pica_path::Path;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> anyhow::Result<()> {
+    ///     let mut path = Path::parse("002@{0 | 0 in @missing.txt}")?;
+    ///     assert!(path.read_lists(&ParseContext::new()).is_err());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn parse(s: &str) -> Result<Self, ParsePathError> {
+        all_consuming(parse_path)(s.as_bytes())
+            .finish()
+            .map_err(|e| ParsePathError::Syntax(SyntaxError::new(s, e)))
+            .map(|(_, path)| path)
+    }
+
+    /// Reads the lists of all list file references (`0 in @codes.txt`)
+    /// of the path and its fallbacks, whereby relative paths are
+    /// resolved against the base directory of the context.
+    pub fn read_lists(
+        &mut self,
+        ctx: &ParseContext,
+    ) -> Result<(), ParsePathError> {
+        if let Some(ref mut matcher) = self.subfield_matcher {
+            matcher.read_lists(ctx).map_err(ParsePathError::Matcher)?;
+        }
+
+        match self.fallback {
+            Some(ref mut fallback) => fallback.read_lists(ctx),
+            None => Ok(()),
+        }
+    }
 }
 
 impl FromStr for Path {
@@ -74,10 +140,9 @@ impl FromStr for Path {
     /// }
     /// ```
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        all_consuming(parse_path)(s.as_bytes())
-            .finish()
-            .map_err(|_| ParsePathError(s.into()))
-            .map(|(_, matcher)| matcher)
+        let mut path = Self::parse(s)?;
+        path.read_lists(&ParseContext::default())?;
+        Ok(path)
     }
 }
 
@@ -101,6 +166,20 @@ fn parse_subfield_codes(i: &[u8]) -> ParseResult<Vec<char>> {
     )(i)
 }
 
+/// Parse a positional index (`[1]`, `[-1]`). Negative indexes count
+/// from the end.
+fn parse_index(i: &[u8]) -> ParseResult<isize> {
+    delimited(
+        char('['),
+        map_res(recognize(pair(opt(char('-')), digit1)), |value| {
+            std::str::from_utf8(value)
+                .expect("valid utf8")
+                .parse::<isize>()
+        }),
+        char(']'),
+    )(i)
+}
+
 fn parse_path_simple(i: &[u8]) -> ParseResult<Path> {
     map(
         delimited(
@@ -108,15 +187,20 @@ fn parse_path_simple(i: &[u8]) -> ParseResult<Path> {
             tuple((
                 parse_tag_matcher,
                 parse_occurrence_matcher,
+                opt(parse_index),
                 parse_subfield_codes,
+                opt(parse_index),
             )),
             multispace0,
         ),
-        |(t, o, c)| Path {
+        |(t, o, fi, c, vi)| Path {
             tag_matcher: t,
             occurrence_matcher: o,
             subfield_matcher: None,
             codes: c,
+            field_index: fi,
+            value_index: vi,
+            fallback: None,
         },
     )(i)
 }
@@ -129,6 +213,7 @@ fn parse_path_matcher_old(i: &[u8]) -> ParseResult<Path> {
             tuple((
                 parse_tag_matcher,
                 parse_occurrence_matcher,
+                opt(parse_index),
                 delimited(
                     ws(char('{')),
                     pair(
@@ -143,14 +228,18 @@ fn parse_path_matcher_old(i: &[u8]) -> ParseResult<Path> {
                     ),
                     ws(char('}')),
                 ),
+                opt(parse_index),
             )),
             multispace0,
         ),
-        |(t, o, (m, c))| Path {
+        |(t, o, fi, (m, c), vi)| Path {
             tag_matcher: t,
             occurrence_matcher: o,
             subfield_matcher: m,
             codes: c,
+            field_index: fi,
+            value_index: vi,
+            fallback: None,
         },
     )(i)
 }
@@ -162,6 +251,7 @@ fn parse_path_matcher_new(i: &[u8]) -> ParseResult<Path> {
             tuple((
                 parse_tag_matcher,
                 parse_occurrence_matcher,
+                opt(parse_index),
                 delimited(
                     ws(char('{')),
                     pair(
@@ -176,19 +266,23 @@ fn parse_path_matcher_new(i: &[u8]) -> ParseResult<Path> {
                     ),
                     ws(char('}')),
                 ),
+                opt(parse_index),
             )),
             multispace0,
         ),
-        |(t, o, (c, m))| Path {
+        |(t, o, fi, (c, m), vi)| Path {
             tag_matcher: t,
             occurrence_matcher: o,
             subfield_matcher: m,
             codes: c,
+            field_index: fi,
+            value_index: vi,
+            fallback: None,
         },
     )(i)
 }
 
-fn parse_path(i: &[u8]) -> ParseResult<Path> {
+fn parse_path_alternative(i: &[u8]) -> ParseResult<Path> {
     alt((
         parse_path_matcher_new,
         parse_path_matcher_old,
@@ -196,8 +290,172 @@ fn parse_path(i: &[u8]) -> ParseResult<Path> {
     ))(i)
 }
 
+/// Parse a path expression with optional fallbacks (`028A.a ?? 028@.a`).
+fn parse_path(i: &[u8]) -> ParseResult<Path> {
+    map(
+        pair(
+            parse_path_alternative,
+            opt(preceded(tag("??"), parse_path)),
+        ),
+        |(mut path, fallback)| {
+            path.fallback = fallback.map(Box::new);
+            path
+        },
+    )(i)
+}
+
+fn parse_path_list_inner(i: &[u8]) -> ParseResult<Vec<Path>> {
+    separated_list1(char(','), parse_path)(i)
+}
+
+/// Parse a comma-separated list of path expressions.
+///
+/// # Example
+///
+/// ```rust
+/// use pica_path::parse_path_list;
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> anyhow::Result<()> {
+///     let paths = parse_path_list("002@.0, 044H{9 | b == 'GND'}")?;
+///     assert_eq!(paths.len(), 2);
+///
+///     assert!(parse_path_list("002@.0,").is_err());
+///     Ok(())
+/// }
+/// ```
+pub fn parse_path_list(s: &str) -> Result<Vec<Path>, ParsePathError> {
+    let (_, mut paths) =
+        all_consuming(parse_path_list_inner)(s.as_bytes())
+            .finish()
+            .map_err(|e| {
+                ParsePathError::Syntax(SyntaxError::new(s, e))
+            })?;
+
+    for path in paths.iter_mut() {
+        path.read_lists(&ParseContext::default())?;
+    }
+
+    Ok(paths)
+}
+
+/// A value selected by a path expression together with the field it
+/// was taken from.
+#[derive(Debug, PartialEq, Eq)]
+pub struct PathMatch<'a, T: AsRef<[u8]>> {
+    field: &'a Field<T>,
+    field_index: usize,
+    code: char,
+    value: &'a T,
+}
+
+impl<'a, T: AsRef<[u8]>> PathMatch<'a, T> {
+    /// Returns the field the value was taken from.
+    pub fn field(&self) -> &'a Field<T> {
+        self.field
+    }
+
+    /// Returns the tag of the field.
+    pub fn tag(&self) -> &'a Tag<T> {
+        self.field.tag()
+    }
+
+    /// Returns the occurrence of the field, if any.
+    pub fn occurrence(&self) -> Option<&'a Occurrence<T>> {
+        self.field.occurrence()
+    }
+
+    /// Returns the position of the field within the record (starting
+    /// at `0`). Values with the same field index come from the same
+    /// field.
+    pub fn field_index(&self) -> usize {
+        self.field_index
+    }
+
+    /// Returns the subfield code of the value.
+    pub fn code(&self) -> char {
+        self.code
+    }
+
+    /// Returns the subfield value.
+    pub fn value(&self) -> &'a T {
+        self.value
+    }
+}
+
+impl<'a, T: AsRef<[u8]>> Clone for PathMatch<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T: AsRef<[u8]>> Copy for PathMatch<'a, T> {}
+
+#[cfg(feature = "serde")]
+impl<'a, T: AsRef<[u8]>> Serialize for PathMatch<'a, T> {
+    fn serialize<S: Serializer>(
+        &self,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut map = s.serialize_map(Some(5))?;
+        map.serialize_entry("tag", &self.tag().to_string())?;
+        map.serialize_entry(
+            "occurrence",
+            &self.occurrence().map(ToString::to_string),
+        )?;
+        map.serialize_entry("field", &self.field_index)?;
+        map.serialize_entry("code", &self.code)?;
+        map.serialize_entry(
+            "value",
+            &self.value.as_ref().to_str_lossy(),
+        )?;
+        map.end()
+    }
+}
+
 pub trait PathExt<T: AsRef<[u8]>> {
-    fn path(&self, path: &Path, options: &MatcherOptions) -> Vec<&T>;
+    /// Returns all values which satisfies the path matcher together with
+    /// the field (tag, occurrence and position) and subfield code they
+    /// were taken from.
+    fn path_matches(
+        &self,
+        path: &Path,
+        options: &MatcherOptions,
+    ) -> Vec<PathMatch<'_, T>>;
+
+    /// Returns all subfield values which satisfies the path matcher.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use bstr::BString;
+    /// use pica_path::{Path, PathExt};
+    /// use pica_record::RecordRef;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> anyhow::Result<()> {
+    ///     let record = RecordRef::new(vec![
+    ///         ("012A", None, vec![('a', "123"), ('a', "456")]),
+    ///         ("012A", Some("01"), vec![('a', "789"), ('b', "xyz")]),
+    ///     ]);
+    ///
+    ///     assert_eq!(
+    ///         record.path(&Path::new("012A/*.a"), &Default::default()),
+    ///         vec![
+    ///             &BString::from("123"),
+    ///             &BString::from("456"),
+    ///             &BString::from("789")
+    ///         ]
+    ///     );
+    ///     Ok(())
+    /// }
+    /// ```
+    fn path(&self, path: &Path, options: &MatcherOptions) -> Vec<&T> {
+        self.path_matches(path, options)
+            .into_iter()
+            .map(|m| m.value())
+            .collect()
+    }
 
     /// Returns the idn of the record.
     ///
@@ -227,7 +485,8 @@ pub trait PathExt<T: AsRef<[u8]>> {
 }
 
 impl<T: AsRef<[u8]>> PathExt<T> for Record<T> {
-    /// Returns all subfield values which satisfies the path matcher.
+    /// Returns all values which satisfies the path matcher together with
+    /// the field and subfield code they were taken from.
     ///
     /// # Example
     ///
@@ -239,46 +498,95 @@ impl<T: AsRef<[u8]>> PathExt<T> for Record<T> {
     /// # fn main() { example().unwrap(); }
     /// fn example() -> anyhow::Result<()> {
     ///     let record = RecordRef::new(vec![
-    ///         ("012A", None, vec![('a', "123"), ('a', "456")]),
-    ///         ("012A", Some("01"), vec![('a', "789"), ('b', "xyz")]),
+    ///         ("003@", None, vec![('0', "123456789X")]),
+    ///         ("012A", Some("01"), vec![('a', "123"), ('b', "456")]),
     ///     ]);
     ///
-    ///     assert_eq!(
-    ///         record.path(&Path::new("012A/*.a"), &Default::default()),
-    ///         vec![
-    ///             &BString::from("123"),
-    ///             &BString::from("456"),
-    ///             &BString::from("789")
-    ///         ]
-    ///     );
+    ///     let path = Path::new("012A/*.[ab]");
+    ///     let matches = record.path_matches(&path, &Default::default());
+    ///     assert_eq!(matches.len(), 2);
+    ///
+    ///     assert_eq!(matches[1].tag(), &"012A");
+    ///     assert_eq!(matches[1].occurrence().unwrap(), &"01");
+    ///     assert_eq!(matches[1].field_index(), 1);
+    ///     assert_eq!(matches[1].code(), 'b');
+    ///     assert_eq!(matches[1].value(), &BString::from("456"));
     ///     Ok(())
     /// }
     /// ```
-    fn path(&self, path: &Path, options: &MatcherOptions) -> Vec<&T> {
-        self.iter()
-            .filter(|field| {
+    fn path_matches(
+        &self,
+        path: &Path,
+        options: &MatcherOptions,
+    ) -> Vec<PathMatch<'_, T>> {
+        let fields = self
+            .iter()
+            .enumerate()
+            .filter(|(_, field)| {
                 path.tag_matcher == field.tag()
                     && path.occurrence_matcher == field.occurrence()
             })
-            .filter(|field| {
+            .filter(|(_, field)| {
                 if let Some(ref matcher) = path.subfield_matcher {
                     matcher.is_match(field.subfields(), options)
                 } else {
                     true
                 }
             })
-            .flat_map(|field| field.subfields())
-            .filter_map(|subfield| {
-                if path.codes.contains(&subfield.code()) {
-                    Some(subfield.value())
-                } else {
-                    None
+            .collect::<Vec<_>>();
+
+        let fields = match path.field_index {
+            Some(index) => nth(fields, index).into_iter().collect(),
+            None => fields,
+        };
+
+        let matches = fields
+            .into_iter()
+            .flat_map(|(field_index, field)| {
+                let matches = field
+                    .subfields()
+                    .iter()
+                    .filter(|subfield| {
+                        path.codes.contains(&subfield.code())
+                    })
+                    .map(|subfield| PathMatch {
+                        field,
+                        field_index,
+                        code: subfield.code(),
+                        value: subfield.value(),
+                    })
+                    .collect::<Vec<_>>();
+
+                match path.value_index {
+                    Some(index) => {
+                        nth(matches, index).into_iter().collect()
+                    }
+                    None => matches,
                 }
             })
-            .collect()
+            .collect::<Vec<_>>();
+
+        match path.fallback {
+            Some(ref fallback) if matches.is_empty() => {
+                self.path_matches(fallback, options)
+            }
+            _ => matches,
+        }
     }
 }
 
+/// Returns the element at the given position. Negative positions count
+/// from the end (`-1` is the last element).
+fn nth<T>(items: Vec<T>, index: isize) -> Option<T> {
+    let index = if index < 0 {
+        items.len().checked_sub(index.unsigned_abs())?
+    } else {
+        index as usize
+    };
+
+    items.into_iter().nth(index)
+}
+
 #[cfg(feature = "serde")]
 impl<'de> Deserialize<'de> for Path {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -304,7 +612,10 @@ mod tests {
                 tag_matcher: TagMatcher::new("012A")?,
                 occurrence_matcher: OccurrenceMatcher::new("/*")?,
                 subfield_matcher: Some(SubfieldMatcher::new("a?")?),
-                codes: vec!['b']
+                codes: vec!['b'],
+                field_index: None,
+                value_index: None,
+                fallback: None,
             }
         );
 
@@ -314,7 +625,10 @@ mod tests {
                 tag_matcher: TagMatcher::new("012A")?,
                 occurrence_matcher: OccurrenceMatcher::new("/*")?,
                 subfield_matcher: Some(SubfieldMatcher::new("a?")?),
-                codes: vec!['b']
+                codes: vec!['b'],
+                field_index: None,
+                value_index: None,
+                fallback: None,
             }
         );
 
@@ -324,7 +638,10 @@ mod tests {
                 tag_matcher: TagMatcher::new("012A")?,
                 occurrence_matcher: OccurrenceMatcher::new("/*")?,
                 subfield_matcher: Some(SubfieldMatcher::new("a?")?),
-                codes: vec!['b', 'c']
+                codes: vec!['b', 'c'],
+                field_index: None,
+                value_index: None,
+                fallback: None,
             }
         );
 
@@ -334,7 +651,10 @@ mod tests {
                 tag_matcher: TagMatcher::new("012A")?,
                 occurrence_matcher: OccurrenceMatcher::new("/*")?,
                 subfield_matcher: Some(SubfieldMatcher::new("a?")?),
-                codes: vec!['b', 'c']
+                codes: vec!['b', 'c'],
+                field_index: None,
+                value_index: None,
+                fallback: None,
             }
         );
 
@@ -344,7 +664,10 @@ mod tests {
                 tag_matcher: TagMatcher::new("012A")?,
                 occurrence_matcher: OccurrenceMatcher::new("/*")?,
                 subfield_matcher: None,
-                codes: vec!['a']
+                codes: vec!['a'],
+                field_index: None,
+                value_index: None,
+                fallback: None,
             }
         );
 
@@ -354,7 +677,10 @@ mod tests {
                 tag_matcher: TagMatcher::new("012A")?,
                 occurrence_matcher: OccurrenceMatcher::new("/01")?,
                 subfield_matcher: None,
-                codes: vec!['a']
+                codes: vec!['a'],
+                field_index: None,
+                value_index: None,
+                fallback: None,
             }
         );
         assert_finished_and_eq!(
@@ -363,10 +689,91 @@ mod tests {
                 tag_matcher: TagMatcher::new("012A")?,
                 occurrence_matcher: OccurrenceMatcher::None,
                 subfield_matcher: None,
-                codes: vec!['a']
+                codes: vec!['a'],
+                field_index: None,
+                value_index: None,
+                fallback: None,
             }
         );
 
+        assert_finished_and_eq!(
+            parse_path(b"044H[1].a[-1]"),
+            Path {
+                tag_matcher: TagMatcher::new("044H")?,
+                occurrence_matcher: OccurrenceMatcher::None,
+                subfield_matcher: None,
+                codes: vec!['a'],
+                field_index: Some(1),
+                value_index: Some(-1),
+                fallback: None,
+            }
+        );
+
+        assert_finished_and_eq!(
+            parse_path(b"044H[0]{9 | b == 'GND'}[0]"),
+            Path {
+                tag_matcher: TagMatcher::new("044H")?,
+                occurrence_matcher: OccurrenceMatcher::None,
+                subfield_matcher: Some(SubfieldMatcher::new(
+                    "b == 'GND'"
+                )?),
+                codes: vec!['9'],
+                field_index: Some(0),
+                value_index: Some(0),
+                fallback: None,
+            }
+        );
+
+        assert_finished_and_eq!(
+            parse_path(b"028A.a ?? 028@.a"),
+            Path {
+                tag_matcher: TagMatcher::new("028A")?,
+                occurrence_matcher: OccurrenceMatcher::None,
+                subfield_matcher: None,
+                codes: vec!['a'],
+                field_index: None,
+                value_index: None,
+                fallback: Some(Box::new(Path {
+                    tag_matcher: TagMatcher::new("028@")?,
+                    occurrence_matcher: OccurrenceMatcher::None,
+                    subfield_matcher: None,
+                    codes: vec!['a'],
+                    field_index: None,
+                    value_index: None,
+                    fallback: None,
+                })),
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_path_list() -> anyhow::Result<()> {
+        assert_finished_and_eq!(
+            parse_path_list_inner(b"002@.0, 012A/*{a?, b}"),
+            vec![
+                Path {
+                    tag_matcher: TagMatcher::new("002@")?,
+                    occurrence_matcher: OccurrenceMatcher::None,
+                    subfield_matcher: None,
+                    codes: vec!['0'],
+                    field_index: None,
+                    value_index: None,
+                    fallback: None,
+                },
+                Path {
+                    tag_matcher: TagMatcher::new("012A")?,
+                    occurrence_matcher: OccurrenceMatcher::new("/*")?,
+                    subfield_matcher: Some(SubfieldMatcher::new("a?")?),
+                    codes: vec!['b'],
+                    field_index: None,
+                    value_index: None,
+                    fallback: None,
+                }
+            ]
+        );
+
         Ok(())
     }
 }
@@ -70,3 +70,190 @@ fn test_path_codes() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_path_syntax_error() {
+    let err = "012A/01.[ab".parse::<Path>().unwrap_err();
+    let err = err.syntax_error().unwrap();
+
+    assert_eq!(err.offset(), 11);
+    assert_eq!((err.line(), err.column()), (1, 12));
+    assert_eq!(
+        err.to_string(),
+        "unexpected end of expression, expected `]` at line 1, \
+        column 12\n  |\n1 | 012A/01.[ab\n  |            ^"
+    );
+
+    let err = "002@.!".parse::<Path>().unwrap_err();
+    let err = err.syntax_error().unwrap();
+    assert_eq!(err.offset(), 5);
+    assert_eq!(err.expected(), ["subfield code", "`[`"]);
+}
+
+#[test]
+fn test_path_occurrence() -> anyhow::Result<()> {
+    let record = ByteRecord::from_bytes(
+        b"003@ \x1f0123\x1e209A/01 \x1fa1\x1e209A/05 \x1fa5\x1e\
+        209A/50 \x1fa50\x1e209A/99 \x1fa99\x1e\n",
+    )?;
+
+    for (path, expected) in [
+        ("209A/>=50.a", vec!["50", "99"]),
+        ("209A/[01,05].a", vec!["1", "5"]),
+        ("209A/+.a", vec!["1", "5", "50", "99"]),
+        ("209A/!=05.a", vec!["1", "50", "99"]),
+    ] {
+        let values: Vec<_> = record
+            .path(&Path::new(path), &Default::default())
+            .into_iter()
+            .map(|value| value.to_str_lossy())
+            .collect();
+
+        assert_eq!(values, expected, "{path}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_path_index() -> anyhow::Result<()> {
+    let record = ByteRecord::from_bytes(
+        b"003@ \x1f0123\x1e044H \x1fa1\x1fa2\x1e044H \x1fa3\x1fa4\x1fa5\
+        \x1e044H \x1fb6\x1e\n",
+    )?;
+
+    for (path, expected) in [
+        ("044H[1].a[0]", vec!["3"]),
+        ("044H[1].a", vec!["3", "4", "5"]),
+        ("044H.a[0]", vec!["1", "3"]),
+        ("044H.a[-1]", vec!["2", "5"]),
+        ("044H[-1].[ab]", vec!["6"]),
+        ("044H[0]{a | a == '2'}[-2]", vec!["1"]),
+        ("044H[1]{a | a?}[-2]", vec!["4"]),
+        ("044H[3].a", vec![]),
+        ("044H.a[-4]", vec![]),
+    ] {
+        let values: Vec<_> = record
+            .path(&Path::new(path), &Default::default())
+            .into_iter()
+            .map(|value| value.to_str_lossy())
+            .collect();
+
+        assert_eq!(values, expected as Vec<&str>, "{path}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_path_fallback() -> anyhow::Result<()> {
+    let record = ByteRecord::from_bytes(
+        b"003@ \x1f0123\x1e028@ \x1faLovelace\x1e028@ \x1faByron\x1e\n",
+    )?;
+
+    for (path, expected) in [
+        ("028A.a ?? 028@.a", vec!["Lovelace", "Byron"]),
+        ("028A.a??028@[-1].a", vec!["Byron"]),
+        ("028A.a ?? 028C.a ?? 003@.0", vec!["123"]),
+        ("003@.0 ?? 028@.a", vec!["123"]),
+        ("028A.a ?? 028C.a", vec![]),
+    ] {
+        let values: Vec<_> = record
+            .path(&Path::new(path), &Default::default())
+            .into_iter()
+            .map(|value| value.to_str_lossy())
+            .collect();
+
+        assert_eq!(values, expected as Vec<&str>, "{path}");
+    }
+
+    assert!("028A.a ??".parse::<Path>().is_err());
+    assert!("044H[1.a".parse::<Path>().is_err());
+    assert!("044H.a[x]".parse::<Path>().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_path_matches() -> anyhow::Result<()> {
+    let record = ByteRecord::from_bytes(ada_lovelace())?;
+    let path = Path::new("060R{a, b | 4 == 'datx'} ?? 003@.0");
+    let matches = record.path_matches(&path, &Default::default());
+
+    assert_eq!(matches.len(), 2);
+    assert!(matches.iter().all(|m| *m.tag() == "060R"));
+    assert!(matches.iter().all(|m| m.occurrence().is_none()));
+    assert_eq!(matches[0].field_index(), matches[1].field_index());
+    assert_eq!(
+        matches
+            .iter()
+            .map(|m| (m.code(), m.value().to_str_lossy()))
+            .collect::<Vec<_>>(),
+        vec![('a', "10.12.1815".into()), ('b', "27.12.1852".into())]
+    );
+
+    let record = ByteRecord::from_bytes(
+        b"003@ \x1f0123\x1e047A/03 \x1feDE-386\x1e\
+        047A/03 \x1frDE-576\x1e\n",
+    )?;
+
+    let matches = record
+        .path_matches(&Path::new("047A/03.[er]"), &Default::default());
+    assert_eq!(
+        matches
+            .iter()
+            .map(|m| (
+                m.field_index(),
+                m.occurrence().map(ToString::to_string),
+                m.code()
+            ))
+            .collect::<Vec<_>>(),
+        vec![(1, Some("03".into()), 'e'), (2, Some("03".into()), 'r')]
+    );
+
+    let fallback = record.path_matches(
+        &Path::new("028A.a ?? 003@.0"),
+        &Default::default(),
+    );
+    assert_eq!(fallback.len(), 1);
+    assert_eq!(fallback[0].tag(), &"003@");
+    assert_eq!(fallback[0].field_index(), 0);
+
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_path_matches_serialize() -> anyhow::Result<()> {
+    let record = ByteRecord::from_bytes(
+        b"003@ \x1f0123\x1e047A/03 \x1feDE-386\x1e\n",
+    )?;
+
+    let matches = record
+        .path_matches(&Path::new("047A/03.e"), &Default::default());
+    assert_eq!(
+        serde_json::to_string(&matches)?,
+        "[{\"tag\":\"047A\",\"occurrence\":\"03\",\"field\":1,\
+        \"code\":\"e\",\"value\":\"DE-386\"}]"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_path_list_file() -> anyhow::Result<()> {
+    let record = ByteRecord::from_bytes(ada_lovelace())?;
+    let path: Path =
+        "002@{0 | 0 in @../pica-matcher/tests/data/bbg.txt}".parse()?;
+
+    assert_eq!(
+        record.path(&path, &Default::default()),
+        vec![&b"Tp1".as_bstr()]
+    );
+
+    let err = "002@{0 | 0 in @missing.txt}".parse::<Path>().unwrap_err();
+    assert!(err.syntax_error().is_none());
+    assert!(err.to_string().contains("invalid list file `missing.txt`"));
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use std::fs::read_to_string;
+use std::path::Path;
+
+use criterion::{
+    black_box, criterion_group, criterion_main, Criterion,
+};
+use pica_matcher::{MatcherOptions, RecordMatcher};
+use pica_record::RecordRef;
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let data = read_to_string(
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../tests/data/119232022.dat"),
+    )
+    .unwrap();
+
+    let record = RecordRef::from_bytes(data.as_bytes()).unwrap();
+    let options = MatcherOptions::default();
+
+    let regex_filter = RecordMatcher::new(
+        "028A.a =~ '^Ab' || 028A.a =~ '^Ba' || 028A.a =~ '^Ca' \
+         || 028A.a =~ '^Da' || 028A.a =~ '^Ea' || 028A.a =~ '^Fa' \
+         || 028A.a =~ '^Ga' || 028A.a =~ '^Ha' || 028A.a =~ '^Ia' \
+         || 028A.a =~ '^Lo'",
+    )
+    .unwrap();
+
+    c.bench_function("RecordMatcher::is_match (regex)", |b| {
+        b.iter(|| regex_filter.is_match(black_box(&record), &options))
+    });
+
+    let compiled = regex_filter.compile(&options);
+    c.bench_function("CompiledMatcher::is_match (regex)", |b| {
+        b.iter(|| compiled.is_match(black_box(&record)))
+    });
+
+    let field_filter = RecordMatcher::new(
+        "028A.a =* 'Lovelace' && 028@.d =~ '^Ada' && 003@? \
+         && 002@.0 == 'Tp1' && 008A? && !012A? && 060R?",
+    )
+    .unwrap();
+
+    c.bench_function("RecordMatcher::is_match (fields)", |b| {
+        b.iter(|| field_filter.is_match(black_box(&record), &options))
+    });
+
+    let compiled = field_filter.compile(&options);
+    c.bench_function("CompiledMatcher::is_match (fields)", |b| {
+        b.iter(|| compiled.is_match(black_box(&record)))
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
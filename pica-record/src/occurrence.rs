@@ -1,5 +1,6 @@
-use std::fmt::Display;
+use std::fmt::{self, Display};
 use std::io::{self, Write};
+use std::ops::Deref;
 
 use bstr::{BStr, BString, ByteSlice};
 use nom::character::complete::{char, satisfy};
@@ -168,9 +169,17 @@ impl<'a> From<OccurrenceRef<'a>> for OccurrenceMut {
     }
 }
 
-impl<'a> ToString for OccurrenceRef<'a> {
-    fn to_string(&self) -> String {
-        self.0.to_string()
+impl<T: AsRef<[u8]>> Deref for Occurrence<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: AsRef<[u8]>> Display for Occurrence<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.as_ref().as_bstr())
     }
 }
 
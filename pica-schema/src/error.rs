@@ -0,0 +1,12 @@
+use thiserror::Error;
+
+/// An error that can occur when reading or compiling an Avram schema.
+#[derive(Error, Debug)]
+pub enum SchemaError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("invalid schema: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid pattern `{0}`")]
+    InvalidPattern(String),
+}
@@ -0,0 +1,369 @@
+use std::cmp::{max_by, min_by};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use bstr::ByteSlice;
+use pica_record::RecordRef;
+
+use crate::schema::cmp_occurrence;
+use crate::{
+    CodeDefinition, FieldDefinition, Schema, SubfieldDefinition,
+};
+
+/// Infers an Avram schema from a set of records.
+///
+/// # Example
+///
+/// ```rust
+/// use pica_record::RecordRef;
+/// use pica_schema::SchemaInferrer;
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> anyhow::Result<()> {
+///     let mut inferrer = SchemaInferrer::new();
+///     inferrer.update(&RecordRef::new(vec![
+///         ("003@", None, vec![('0', "123456789X")]),
+///         ("012A", None, vec![('a', "1"), ('a', "2")]),
+///     ]));
+///     inferrer.update(&RecordRef::new(vec![(
+///         "003@",
+///         None,
+///         vec![('0', "234567890X")],
+///     )]));
+///
+///     let schema = inferrer.finish();
+///     assert!(schema.fields["003@"].required);
+///     assert!(!schema.fields["012A"].required);
+///     assert!(schema.fields["012A"].subfields["a"].repeatable);
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct SchemaInferrer {
+    max_codes: usize,
+    records: u64,
+    fields: BTreeMap<String, FieldStats>,
+}
+
+impl Default for SchemaInferrer {
+    fn default() -> Self {
+        Self {
+            max_codes: 10,
+            records: 0,
+            fields: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct FieldStats {
+    tag: String,
+    occurrence: Option<(String, String)>,
+    records: u64,
+    total: u64,
+    max: u64,
+    subfields: BTreeMap<char, SubfieldStats>,
+}
+
+#[derive(Debug, Default)]
+struct SubfieldStats {
+    records: u64,
+    /// The number of fields containing the subfield.
+    fields: u64,
+    total: u64,
+    max: u64,
+    shape: Shape,
+    codes: Option<BTreeMap<String, u64>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Digit,
+    Upper,
+    Lower,
+    Literal(char),
+}
+
+impl CharClass {
+    fn from_char(c: char) -> Self {
+        if c.is_ascii_digit() {
+            Self::Digit
+        } else if c.is_ascii_uppercase() {
+            Self::Upper
+        } else if c.is_ascii_lowercase() {
+            Self::Lower
+        } else {
+            Self::Literal(c)
+        }
+    }
+}
+
+/// The shape of all values of a subfield, which is a sequence of runs
+/// of character classes together with the minimum and maximum length
+/// of each run.
+#[derive(Debug, Default, PartialEq, Eq)]
+enum Shape {
+    #[default]
+    Unknown,
+    Runs(Vec<(CharClass, usize, usize)>),
+    Mixed,
+}
+
+impl Shape {
+    fn update(&mut self, value: &str) {
+        let mut runs: Vec<(CharClass, usize)> = vec![];
+        for c in value.chars() {
+            let class = CharClass::from_char(c);
+            match runs.last_mut() {
+                Some((last, len)) if *last == class => *len += 1,
+                _ => runs.push((class, 1)),
+            }
+        }
+
+        match self {
+            Self::Unknown => {
+                *self = Self::Runs(
+                    runs.into_iter()
+                        .map(|(class, len)| (class, len, len))
+                        .collect(),
+                );
+            }
+            Self::Runs(ref mut shape) => {
+                if shape.len() != runs.len()
+                    || shape
+                        .iter()
+                        .zip(runs.iter())
+                        .any(|(a, b)| a.0 != b.0)
+                {
+                    *self = Self::Mixed;
+                    return;
+                }
+
+                for (run, (_, len)) in shape.iter_mut().zip(runs) {
+                    run.1 = run.1.min(len);
+                    run.2 = run.2.max(len);
+                }
+            }
+            Self::Mixed => (),
+        }
+    }
+
+    fn pattern(&self) -> Option<String> {
+        let Self::Runs(ref runs) = self else {
+            return None;
+        };
+
+        let mut pattern = String::from("^");
+        for (class, min, max) in runs.iter() {
+            match class {
+                CharClass::Digit => pattern.push_str("[0-9]"),
+                CharClass::Upper => pattern.push_str("[A-Z]"),
+                CharClass::Lower => pattern.push_str("[a-z]"),
+                CharClass::Literal(c) => {
+                    pattern.push_str(&regex::escape(&c.to_string()))
+                }
+            }
+
+            match (min, max) {
+                (1, 1) => (),
+                (min, max) if min == max => {
+                    pattern.push_str(&format!("{{{min}}}"))
+                }
+                (min, max) => {
+                    pattern.push_str(&format!("{{{min},{max}}}"))
+                }
+            }
+        }
+
+        pattern.push('$');
+        Some(pattern)
+    }
+}
+
+impl SchemaInferrer {
+    /// Creates a new inferrer with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of distinct values of a subfield, which
+    /// are turned into a codelist. Codelists are only inferred, if
+    /// each value occurs at least twice on average. A value of `0`
+    /// disables the inference of codelists.
+    pub fn max_codes(mut self, value: usize) -> Self {
+        self.max_codes = value;
+        self
+    }
+
+    /// Updates the statistics with the given record.
+    pub fn update(&mut self, record: &RecordRef) {
+        let mut counts: HashMap<String, u64> = HashMap::new();
+        let mut seen: HashSet<(String, char)> = HashSet::new();
+        self.records += 1;
+
+        for field in record.iter() {
+            let tag = field.tag().to_string();
+            let occurrence = field.occurrence().map(|o| o.to_string());
+
+            // Level 2 fields (copy data) are numbered consecutively, so
+            // all occurrences are combined into a single definition
+            // with an occurrence range.
+            let key = match occurrence {
+                Some(ref occurrence) if !tag.starts_with('2') => {
+                    format!("{tag}/{occurrence}")
+                }
+                _ => tag.clone(),
+            };
+
+            let stats =
+                self.fields.entry(key.clone()).or_insert_with(|| {
+                    FieldStats {
+                        tag: tag.clone(),
+                        ..Default::default()
+                    }
+                });
+
+            if let Some(occurrence) = occurrence {
+                stats.occurrence = match stats.occurrence.take() {
+                    Some((min, max)) => Some((
+                        min_by(min, occurrence.clone(), cmp_occurrence),
+                        max_by(max, occurrence, cmp_occurrence),
+                    )),
+                    None => Some((occurrence.clone(), occurrence)),
+                };
+            }
+
+            stats.total += 1;
+            *counts.entry(key.clone()).or_insert(0) += 1;
+
+            let mut codes: HashMap<char, u64> = HashMap::new();
+            for subfield in field.subfields() {
+                let value = subfield.value().to_str_lossy();
+                let sf_stats = stats
+                    .subfields
+                    .entry(subfield.code())
+                    .or_insert_with(|| SubfieldStats {
+                        codes: Some(BTreeMap::new()),
+                        ..Default::default()
+                    });
+
+                sf_stats.total += 1;
+                sf_stats.shape.update(&value);
+
+                if let Some(ref mut codes) = sf_stats.codes {
+                    *codes.entry(value.to_string()).or_insert(0) += 1;
+                    if codes.len() > self.max_codes {
+                        sf_stats.codes = None;
+                    }
+                }
+
+                *codes.entry(subfield.code()).or_insert(0) += 1;
+            }
+
+            for (code, count) in codes {
+                let sf_stats = stats.subfields.get_mut(&code).unwrap();
+                sf_stats.fields += 1;
+                sf_stats.max = sf_stats.max.max(count);
+
+                if seen.insert((key.clone(), code)) {
+                    sf_stats.records += 1;
+                }
+            }
+        }
+
+        for (key, count) in counts {
+            let stats = self.fields.get_mut(&key).unwrap();
+            stats.records += 1;
+            stats.max = stats.max.max(count);
+        }
+    }
+
+    /// Returns the inferred schema.
+    pub fn finish(self) -> Schema {
+        let records = self.records;
+        let fields = self
+            .fields
+            .into_values()
+            .map(|stats| {
+                let occurrence = stats.occurrence.map(|(min, max)| {
+                    if min == max {
+                        min
+                    } else {
+                        format!("{min}-{max}")
+                    }
+                });
+
+                let key = match occurrence {
+                    Some(ref occurrence) => {
+                        format!("{}/{occurrence}", stats.tag)
+                    }
+                    None => stats.tag.clone(),
+                };
+
+                let field_total = stats.total;
+                let subfields = stats
+                    .subfields
+                    .into_iter()
+                    .map(|(code, sf_stats)| {
+                        let codes = sf_stats
+                            .codes
+                            .filter(|codes| {
+                                !codes.is_empty()
+                                    && codes.len() as u64 * 2
+                                        <= sf_stats.total
+                            })
+                            .map(|codes| {
+                                codes
+                                    .into_keys()
+                                    .map(|code| {
+                                        (
+                                            code.clone(),
+                                            CodeDefinition {
+                                                code,
+                                                label: None,
+                                            },
+                                        )
+                                    })
+                                    .collect::<BTreeMap<_, _>>()
+                            });
+
+                        let definition = SubfieldDefinition {
+                            code: code.to_string(),
+                            repeatable: sf_stats.max > 1,
+                            required: sf_stats.fields == field_total,
+                            pattern: if codes.is_none() {
+                                sf_stats.shape.pattern()
+                            } else {
+                                None
+                            },
+                            codes,
+                            records: Some(sf_stats.records),
+                            total: Some(sf_stats.total),
+                            ..Default::default()
+                        };
+
+                        (code.to_string(), definition)
+                    })
+                    .collect();
+
+                let definition = FieldDefinition {
+                    tag: stats.tag,
+                    occurrence,
+                    repeatable: stats.max > 1,
+                    required: stats.records == records,
+                    subfields,
+                    records: Some(stats.records),
+                    total: Some(stats.total),
+                    ..Default::default()
+                };
+
+                (key, definition)
+            })
+            .collect();
+
+        Schema {
+            fields,
+            records: Some(records),
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,18 @@
+//! This crate provides the data model of [Avram] schemas, which are
+//! used to describe formats based on PICA+, the inference of such
+//! schemas from a set of records and the validation of records against
+//! a schema.
+//!
+//! [Avram]: https://format.gbv.de/schema/avram/specification
+
+mod error;
+mod infer;
+mod schema;
+mod validate;
+
+pub use error::SchemaError;
+pub use infer::SchemaInferrer;
+pub use schema::{
+    CodeDefinition, FieldDefinition, Schema, SubfieldDefinition,
+};
+pub use validate::{Validator, Violation, ViolationKind};
@@ -0,0 +1,169 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs::read_to_string;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::SchemaError;
+
+/// An Avram schema.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Schema {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// The field definitions, indexed by the field identifier (the tag
+    /// and an optional occurrence, e.g. "003@" or "047A/03").
+    #[serde(default)]
+    pub fields: BTreeMap<String, FieldDefinition>,
+
+    /// The number of records the schema was inferred from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub records: Option<u64>,
+}
+
+/// Compares two occurrences numerically (e.g. `99 < 100`), so that
+/// occurrences of different widths are ordered correctly.
+pub(crate) fn cmp_occurrence<A, B>(a: &A, b: &B) -> Ordering
+where
+    A: AsRef<[u8]> + ?Sized,
+    B: AsRef<[u8]> + ?Sized,
+{
+    let parse = |value: &[u8]| {
+        std::str::from_utf8(value).ok()?.parse::<u32>().ok()
+    };
+
+    let (a, b) = (a.as_ref(), b.as_ref());
+    parse(a).cmp(&parse(b)).then_with(|| a.cmp(b))
+}
+
+/// The definition of a field.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldDefinition {
+    pub tag: String,
+
+    /// The occurrence of the field. An occurrence range (e.g. "01-99")
+    /// is allowed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub occurrence: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+
+    #[serde(default)]
+    pub repeatable: bool,
+
+    #[serde(default)]
+    pub required: bool,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub subfields: BTreeMap<String, SubfieldDefinition>,
+
+    /// The number of records containing the field.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub records: Option<u64>,
+
+    /// The total number of occurrences of the field.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
+}
+
+/// The definition of a subfield.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubfieldDefinition {
+    pub code: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+
+    #[serde(default)]
+    pub repeatable: bool,
+
+    #[serde(default)]
+    pub required: bool,
+
+    /// A regular expression, which must match all values.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+
+    /// A list of allowed values (codelist).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub codes: Option<BTreeMap<String, CodeDefinition>>,
+
+    /// The number of records containing the subfield.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub records: Option<u64>,
+
+    /// The total number of occurrences of the subfield.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
+}
+
+/// The definition of a code of a codelist.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CodeDefinition {
+    pub code: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl Schema {
+    /// Reads an Avram schema from a JSON file.
+    pub fn from_path<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<Self, SchemaError> {
+        Self::from_str(&read_to_string(path)?)
+    }
+
+    /// Serializes the schema to (pretty-printed) JSON.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use pica_schema::Schema;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> anyhow::Result<()> {
+    ///     let schema: Schema = r#"{"fields": {}}"#.parse()?;
+    ///     assert_eq!(schema.to_json(), "{\n  \"fields\": {}\n}");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+impl FromStr for Schema {
+    type Err = SchemaError;
+
+    /// Parse an Avram schema from a JSON string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::str::FromStr;
+    ///
+    /// use pica_schema::Schema;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> anyhow::Result<()> {
+    ///     let schema = Schema::from_str(
+    ///         r#"{"fields": {"003@": {"tag": "003@", "required": true}}}"#,
+    ///     )?;
+    ///
+    ///     assert!(schema.fields["003@"].required);
+    ///     assert!(!schema.fields["003@"].repeatable);
+    ///     Ok(())
+    /// }
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(serde_json::from_str(s)?)
+    }
+}
@@ -0,0 +1,287 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Display};
+
+use bstr::ByteSlice;
+use pica_record::{FieldRef, RecordRef};
+use regex::Regex;
+
+use crate::schema::cmp_occurrence;
+use crate::{FieldDefinition, Schema, SchemaError};
+
+/// The kind of a schema violation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ViolationKind {
+    UnknownField,
+    InvalidOccurrence,
+    NonRepeatableField,
+    MissingField,
+    UnknownSubfield,
+    NonRepeatableSubfield,
+    MissingSubfield,
+    PatternMismatch(String),
+    InvalidCode(String),
+}
+
+/// A violation of a schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// The field identifier (tag and optional occurrence).
+    pub field: String,
+    /// The subfield code, if the violation concerns a subfield.
+    pub code: Option<char>,
+    pub kind: ViolationKind,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ViolationKind::UnknownField => write!(f, "unknown field"),
+            ViolationKind::InvalidOccurrence => {
+                write!(f, "invalid occurrence")
+            }
+            ViolationKind::NonRepeatableField => {
+                write!(f, "field is not repeatable")
+            }
+            ViolationKind::MissingField => {
+                write!(f, "missing required field")
+            }
+            ViolationKind::UnknownSubfield => {
+                write!(f, "unknown subfield")
+            }
+            ViolationKind::NonRepeatableSubfield => {
+                write!(f, "subfield is not repeatable")
+            }
+            ViolationKind::MissingSubfield => {
+                write!(f, "missing required subfield")
+            }
+            ViolationKind::PatternMismatch(ref value) => {
+                write!(f, "value `{value}` does not match pattern")
+            }
+            ViolationKind::InvalidCode(ref value) => {
+                write!(f, "value `{value}` is not in codelist")
+            }
+        }
+    }
+}
+
+/// Validates records against an Avram schema.
+///
+/// # Example
+///
+/// ```rust
+/// use pica_record::RecordRef;
+/// use pica_schema::{Validator, ViolationKind};
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> anyhow::Result<()> {
+///     let validator = Validator::new(
+///         r#"{"fields": {"003@": {"tag": "003@", "required": true}}}"#
+///             .parse()?,
+///     )?;
+///
+///     let violations = validator.validate(&RecordRef::new(vec![
+///         ("002@", None, vec![('0', "Tp1")]),
+///     ]));
+///
+///     assert_eq!(violations.len(), 2);
+///     assert_eq!(violations[0].kind, ViolationKind::UnknownField);
+///     assert_eq!(violations[1].kind, ViolationKind::MissingField);
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Validator {
+    schema: Schema,
+    patterns: HashMap<(String, String), Regex>,
+    /// The keys of the field definitions of a tag.
+    tags: HashMap<Vec<u8>, Vec<String>>,
+}
+
+impl Validator {
+    /// Creates a new validator. Fails, if a pattern of the schema is
+    /// not a valid regular expression.
+    pub fn new(schema: Schema) -> Result<Self, SchemaError> {
+        let mut patterns = HashMap::new();
+        let mut tags: HashMap<Vec<u8>, Vec<String>> = HashMap::new();
+
+        for (key, field) in schema.fields.iter() {
+            tags.entry(field.tag.as_bytes().to_vec())
+                .or_default()
+                .push(key.clone());
+
+            for (code, subfield) in field.subfields.iter() {
+                if let Some(ref pattern) = subfield.pattern {
+                    let re = Regex::new(pattern).map_err(|_| {
+                        SchemaError::InvalidPattern(pattern.clone())
+                    })?;
+
+                    patterns.insert((key.clone(), code.clone()), re);
+                }
+            }
+        }
+
+        Ok(Self {
+            schema,
+            patterns,
+            tags,
+        })
+    }
+
+    /// Returns the key and definition of the given field.
+    fn definition(
+        &self,
+        field: &FieldRef,
+    ) -> Result<(&String, &FieldDefinition), ViolationKind> {
+        let Some(keys) = self.tags.get(field.tag().as_bytes()) else {
+            return Err(ViolationKind::UnknownField);
+        };
+
+        let occurrence = field.occurrence().map(|o| o.as_bytes());
+        for (key, definition) in keys
+            .iter()
+            .filter_map(|key| self.schema.fields.get_key_value(key))
+        {
+            let is_match = match (occurrence, &definition.occurrence) {
+                (None, None) => true,
+                (Some(occurrence), Some(expected)) => {
+                    match expected.split_once('-') {
+                        Some((min, max)) => {
+                            cmp_occurrence(occurrence, min).is_ge()
+                                && cmp_occurrence(occurrence, max)
+                                    .is_le()
+                        }
+                        None => occurrence == expected.as_bytes(),
+                    }
+                }
+                (Some(occurrence), None) => occurrence == b"00",
+                (None, Some(_)) => false,
+            };
+
+            if is_match {
+                return Ok((key, definition));
+            }
+        }
+
+        Err(ViolationKind::InvalidOccurrence)
+    }
+
+    /// Validates a record and returns all violations.
+    pub fn validate(&self, record: &RecordRef) -> Vec<Violation> {
+        let mut violations = vec![];
+        let mut counts: BTreeMap<&String, usize> = BTreeMap::new();
+
+        for field in record.iter() {
+            let (key, definition) = match self.definition(field) {
+                Ok(result) => result,
+                Err(kind) => {
+                    let mut field_id = field.tag().to_string();
+                    if let Some(occurrence) = field.occurrence() {
+                        field_id.push('/');
+                        field_id.push_str(&occurrence.to_string());
+                    }
+
+                    violations.push(Violation {
+                        field: field_id,
+                        code: None,
+                        kind,
+                    });
+                    continue;
+                }
+            };
+
+            *counts.entry(key).or_insert(0) += 1;
+
+            let mut codes: BTreeMap<char, usize> = BTreeMap::new();
+            for subfield in field.subfields() {
+                let code = subfield.code();
+                *codes.entry(code).or_insert(0) += 1;
+
+                let Some(sf_definition) =
+                    definition.subfields.get(&code.to_string())
+                else {
+                    violations.push(Violation {
+                        field: key.clone(),
+                        code: Some(code),
+                        kind: ViolationKind::UnknownSubfield,
+                    });
+                    continue;
+                };
+
+                let value = subfield.value().to_str_lossy();
+                if let Some(re) =
+                    self.patterns.get(&(key.clone(), code.to_string()))
+                {
+                    if !re.is_match(&value) {
+                        violations.push(Violation {
+                            field: key.clone(),
+                            code: Some(code),
+                            kind: ViolationKind::PatternMismatch(
+                                value.to_string(),
+                            ),
+                        });
+                    }
+                }
+
+                if let Some(ref allowed) = sf_definition.codes {
+                    if !allowed.contains_key(value.as_ref()) {
+                        violations.push(Violation {
+                            field: key.clone(),
+                            code: Some(code),
+                            kind: ViolationKind::InvalidCode(
+                                value.to_string(),
+                            ),
+                        });
+                    }
+                }
+            }
+
+            for (code, sf_definition) in definition.subfields.iter() {
+                let Some(code) = code.chars().next() else {
+                    continue;
+                };
+
+                match codes.get(&code) {
+                    Some(count)
+                        if *count > 1 && !sf_definition.repeatable =>
+                    {
+                        violations.push(Violation {
+                            field: key.clone(),
+                            code: Some(code),
+                            kind: ViolationKind::NonRepeatableSubfield,
+                        });
+                    }
+                    None if sf_definition.required => {
+                        violations.push(Violation {
+                            field: key.clone(),
+                            code: Some(code),
+                            kind: ViolationKind::MissingSubfield,
+                        });
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        for (key, definition) in self.schema.fields.iter() {
+            match counts.get(key) {
+                Some(count) if *count > 1 && !definition.repeatable => {
+                    violations.push(Violation {
+                        field: key.clone(),
+                        code: None,
+                        kind: ViolationKind::NonRepeatableField,
+                    });
+                }
+                None if definition.required => {
+                    violations.push(Violation {
+                        field: key.clone(),
+                        code: None,
+                        kind: ViolationKind::MissingField,
+                    });
+                }
+                _ => (),
+            }
+        }
+
+        violations
+    }
+}
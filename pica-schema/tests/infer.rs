@@ -0,0 +1,183 @@
+use pica_record::RecordRef;
+use pica_schema::SchemaInferrer;
+
+#[test]
+fn infer_required_and_repeatable() -> anyhow::Result<()> {
+    let mut inferrer = SchemaInferrer::new();
+    inferrer.update(&RecordRef::new(vec![
+        ("003@", None, vec![('0', "123456789X")]),
+        ("041A", None, vec![('9', "1"), ('a', "foo")]),
+        ("041A", None, vec![('9', "2")]),
+    ]));
+    inferrer.update(&RecordRef::new(vec![(
+        "003@",
+        None,
+        vec![('0', "234567890X")],
+    )]));
+
+    let schema = inferrer.finish();
+    assert_eq!(schema.records, Some(2));
+
+    let field = &schema.fields["003@"];
+    assert!(field.required);
+    assert!(!field.repeatable);
+    assert_eq!(field.records, Some(2));
+    assert!(field.subfields["0"].required);
+    assert!(!field.subfields["0"].repeatable);
+
+    let field = &schema.fields["041A"];
+    assert!(!field.required);
+    assert!(field.repeatable);
+    assert_eq!(field.records, Some(1));
+    assert_eq!(field.total, Some(2));
+    assert!(field.subfields["9"].required);
+    assert!(!field.subfields["a"].required);
+
+    // Subfields are required per field, but counted per record.
+    assert_eq!(field.subfields["9"].records, Some(1));
+    assert_eq!(field.subfields["9"].total, Some(2));
+
+    Ok(())
+}
+
+#[test]
+fn infer_pattern() -> anyhow::Result<()> {
+    let mut inferrer = SchemaInferrer::new();
+    for value in ["118540238", "1185402380", "04011856X"] {
+        inferrer.update(&RecordRef::new(vec![(
+            "003@",
+            None,
+            vec![('0', value)],
+        )]));
+    }
+
+    let schema = inferrer.finish();
+    assert_eq!(schema.fields["003@"].subfields["0"].pattern, None);
+
+    let mut inferrer = SchemaInferrer::new();
+    for value in ["118540238", "1185402380", "04011856.1"] {
+        inferrer.update(&RecordRef::new(vec![(
+            "003@",
+            None,
+            vec![('0', value)],
+        )]));
+    }
+
+    let schema = inferrer.finish();
+    assert_eq!(schema.fields["003@"].subfields["0"].pattern, None);
+
+    let mut inferrer = SchemaInferrer::new();
+    for value in ["Tp1", "Ts1", "Tpz"] {
+        inferrer.update(&RecordRef::new(vec![(
+            "002@",
+            None,
+            vec![('0', value), ('x', "a-1")],
+        )]));
+    }
+
+    let schema = inferrer.finish();
+    let field = &schema.fields["002@"];
+    assert_eq!(field.subfields["0"].pattern, None);
+    assert_eq!(field.subfields["x"].pattern, None);
+    assert!(field.subfields["x"].codes.is_some());
+
+    let mut inferrer = SchemaInferrer::new().max_codes(0);
+    for value in ["2023-01-01", "2023-1-31"] {
+        inferrer.update(&RecordRef::new(vec![(
+            "001A",
+            None,
+            vec![('0', value)],
+        )]));
+    }
+
+    let schema = inferrer.finish();
+    assert_eq!(
+        schema.fields["001A"].subfields["0"].pattern,
+        Some("^[0-9]{4}\\-[0-9]{1,2}\\-[0-9]{2}$".into())
+    );
+
+    Ok(())
+}
+
+#[test]
+fn infer_codes() -> anyhow::Result<()> {
+    let mut inferrer = SchemaInferrer::new().max_codes(2);
+    for value in ["ger", "eng", "ger", "eng"] {
+        inferrer.update(&RecordRef::new(vec![(
+            "010@",
+            None,
+            vec![('a', value)],
+        )]));
+    }
+
+    let schema = inferrer.finish();
+    let subfield = &schema.fields["010@"].subfields["a"];
+    let codes = subfield.codes.as_ref().unwrap();
+    assert_eq!(codes.len(), 2);
+    assert!(codes.contains_key("ger"));
+    assert!(codes.contains_key("eng"));
+    assert_eq!(subfield.pattern, None);
+
+    let mut inferrer = SchemaInferrer::new().max_codes(2);
+    for value in ["ger", "eng", "fre", "ger", "eng", "fre"] {
+        inferrer.update(&RecordRef::new(vec![(
+            "010@",
+            None,
+            vec![('a', value)],
+        )]));
+    }
+
+    let schema = inferrer.finish();
+    let subfield = &schema.fields["010@"].subfields["a"];
+    assert_eq!(subfield.codes, None);
+    assert_eq!(subfield.pattern, Some("^[a-z]{3}$".into()));
+
+    Ok(())
+}
+
+#[test]
+fn infer_occurrences() -> anyhow::Result<()> {
+    let mut inferrer = SchemaInferrer::new();
+    inferrer.update(&RecordRef::new(vec![
+        ("047A", Some("03"), vec![('a', "foo")]),
+        ("203@", Some("01"), vec![('0', "123")]),
+        ("203@", Some("02"), vec![('0', "456")]),
+    ]));
+    inferrer.update(&RecordRef::new(vec![(
+        "203@",
+        Some("05"),
+        vec![('0', "789")],
+    )]));
+
+    let schema = inferrer.finish();
+    assert_eq!(schema.fields.len(), 2);
+
+    let field = &schema.fields["047A/03"];
+    assert_eq!(field.tag, "047A");
+    assert_eq!(field.occurrence, Some("03".into()));
+    assert!(!field.required);
+
+    let field = &schema.fields["203@/01-05"];
+    assert_eq!(field.tag, "203@");
+    assert_eq!(field.occurrence, Some("01-05".into()));
+    assert!(field.required);
+    assert!(field.repeatable);
+
+    // Occurrences are compared numerically.
+    let mut inferrer = SchemaInferrer::new();
+    for occurrence in ["99", "100", "09"] {
+        inferrer.update(&RecordRef::new(vec![(
+            "203@",
+            Some(occurrence),
+            vec![('0', "123")],
+        )]));
+    }
+
+    let schema = inferrer.finish();
+    assert_eq!(
+        schema.fields["203@/09-100"].occurrence,
+        Some("09-100".into())
+    );
+
+    Ok(())
+}
@@ -0,0 +1,171 @@
+use pica_record::RecordRef;
+use pica_schema::{
+    Schema, SchemaError, SchemaInferrer, Validator, ViolationKind,
+};
+
+fn schema() -> anyhow::Result<Schema> {
+    Ok(r#"{
+        "fields": {
+            "003@": {
+                "tag": "003@",
+                "required": true,
+                "subfields": {
+                    "0": {
+                        "code": "0",
+                        "required": true,
+                        "pattern": "^[0-9]{8,9}[0-9X]$"
+                    }
+                }
+            },
+            "010@": {
+                "tag": "010@",
+                "subfields": {
+                    "a": {
+                        "code": "a",
+                        "codes": {"ger": {"code": "ger"}}
+                    }
+                }
+            },
+            "047A/03": {
+                "tag": "047A",
+                "occurrence": "03",
+                "subfields": {"a": {"code": "a"}}
+            },
+            "203@/01-99": {
+                "tag": "203@",
+                "occurrence": "01-99",
+                "repeatable": true,
+                "subfields": {"0": {"code": "0"}}
+            }
+        }
+    }"#
+    .parse()?)
+}
+
+#[test]
+fn validate_valid_record() -> anyhow::Result<()> {
+    let validator = Validator::new(schema()?)?;
+    let violations = validator.validate(&RecordRef::new(vec![
+        ("003@", None, vec![('0', "123456789X")]),
+        ("010@", None, vec![('a', "ger")]),
+        ("047A", Some("03"), vec![('a', "foo")]),
+        ("203@", Some("01"), vec![('0', "1")]),
+        ("203@", Some("02"), vec![('0', "2")]),
+    ]));
+
+    assert!(violations.is_empty());
+    Ok(())
+}
+
+#[test]
+fn validate_fields() -> anyhow::Result<()> {
+    let validator = Validator::new(schema()?)?;
+    let violations = validator.validate(&RecordRef::new(vec![
+        ("002@", None, vec![('0', "Tp1")]),
+        ("010@", None, vec![('a', "ger")]),
+        ("010@", None, vec![('a', "ger")]),
+        ("047A", Some("01"), vec![('a', "foo")]),
+    ]));
+
+    let kinds: Vec<_> = violations
+        .iter()
+        .map(|v| (v.field.as_str(), v.kind.clone()))
+        .collect();
+
+    assert_eq!(
+        kinds,
+        vec![
+            ("002@", ViolationKind::UnknownField),
+            ("047A/01", ViolationKind::InvalidOccurrence),
+            ("003@", ViolationKind::MissingField),
+            ("010@", ViolationKind::NonRepeatableField),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn validate_subfields() -> anyhow::Result<()> {
+    let validator = Validator::new(schema()?)?;
+    let violations = validator.validate(&RecordRef::new(vec![
+        ("003@", None, vec![('0', "123"), ('0', "123456789X")]),
+        ("010@", None, vec![('a', "eng"), ('b', "foo")]),
+        ("047A", Some("03"), vec![]),
+    ]));
+
+    let kinds: Vec<_> = violations
+        .iter()
+        .map(|v| (v.field.as_str(), v.code, v.kind.clone()))
+        .collect();
+
+    assert_eq!(
+        kinds,
+        vec![
+            (
+                "003@",
+                Some('0'),
+                ViolationKind::PatternMismatch("123".into())
+            ),
+            ("003@", Some('0'), ViolationKind::NonRepeatableSubfield),
+            (
+                "010@",
+                Some('a'),
+                ViolationKind::InvalidCode("eng".into())
+            ),
+            ("010@", Some('b'), ViolationKind::UnknownSubfield),
+        ]
+    );
+
+    assert_eq!(
+        violations[0].to_string(),
+        "value `123` does not match pattern"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn validate_invalid_pattern() -> anyhow::Result<()> {
+    let schema: Schema = r#"{"fields": {"003@": {"tag": "003@",
+        "subfields": {"0": {"code": "0", "pattern": "^[0-9$"}}}}}"#
+        .parse()?;
+
+    assert!(matches!(
+        Validator::new(schema).unwrap_err(),
+        SchemaError::InvalidPattern(_)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn validate_inferred_schema() -> anyhow::Result<()> {
+    let records = [
+        RecordRef::new(vec![
+            ("001A", None, vec![('0', "1250:01-07-88")]),
+            ("003@", None, vec![('0', "123456789X")]),
+            ("010@", None, vec![('a', "ger")]),
+            ("203@", Some("01"), vec![('0', "1")]),
+        ]),
+        RecordRef::new(vec![
+            ("001A", None, vec![('0', "1250:16-06-11")]),
+            ("003@", None, vec![('0', "234567890")]),
+            ("010@", None, vec![('a', "ger"), ('a', "eng")]),
+            ("203@", Some("01"), vec![('0', "2")]),
+            ("203@", Some("02"), vec![('0', "3")]),
+        ]),
+    ];
+
+    let mut inferrer = SchemaInferrer::new();
+    for record in records.iter() {
+        inferrer.update(record);
+    }
+
+    let validator = Validator::new(inferrer.finish())?;
+    for record in records.iter() {
+        assert!(validator.validate(record).is_empty());
+    }
+
+    Ok(())
+}
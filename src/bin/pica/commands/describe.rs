@@ -0,0 +1,374 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{self, Write};
+
+use bstr::{BString, ByteSlice};
+use clap::Parser;
+use pica_record::io::{ReaderBuilder, RecordsIterator};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::sketch::TopK;
+use crate::skip_invalid_flag;
+use crate::util::CliResult;
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct DescribeConfig {
+    /// Skip invalid records that can't be decoded.
+    pub(crate) skip_invalid: Option<bool>,
+}
+
+/// Print a profile of all tags and subfields of a dataset
+///
+/// For each tag (e.g. "003@") and each subfield (e.g. "003@.0") the
+/// number of records containing it, the minimum, maximum and mean
+/// number of occurrences and whether it is repeatable is reported. The
+/// occurrences of a tag are counted per record and the occurrences of a
+/// subfield per field (or per record with --per-record), so a minimum
+/// of 0 indicates that a tag or subfield is optional. For subfields, the minimum, maximum and mean
+/// length of the values (in characters) and the most common values are
+/// reported too. The most common values are computed approximately in
+/// bounded memory.
+#[derive(Parser, Debug)]
+pub(crate) struct Describe {
+    /// Skip invalid records that can't be decoded as normalized PICA+.
+    #[arg(long, short)]
+    skip_invalid: bool,
+
+    /// Count the occurrences of a subfield per record instead of per
+    /// field.
+    #[arg(long)]
+    per_record: bool,
+
+    /// The number of most common values per subfield.
+    #[arg(long, short = 'n', value_name = "n", default_value = "3")]
+    top: usize,
+
+    /// Write output tab-separated (TSV)
+    #[arg(long, short, conflicts_with = "json")]
+    tsv: bool,
+
+    /// Write output as JSON (an array of objects)
+    #[arg(long)]
+    json: bool,
+
+    /// Write output to <filename> instead of stdout.
+    #[arg(short, long, value_name = "filename")]
+    output: Option<OsString>,
+
+    /// Read one or more files in normalized PICA+ format. With no
+    /// files, or when a filename is '-', read from stanard input
+    /// (stdin).
+    #[arg(default_value = "-", hide_default_value = true)]
+    filenames: Vec<OsString>,
+}
+
+/// The key of a profile: the tag and, for subfields, the subfield
+/// code.
+type Key = ([u8; 4], Option<char>);
+
+/// Formats the key of a profile (e.g. `003@` or `003@.0`).
+fn fmt_key((tag, code): &Key) -> String {
+    let tag = tag.as_bstr();
+    match code {
+        Some(code) => format!("{tag}.{code}"),
+        None => tag.to_string(),
+    }
+}
+
+/// The profile of a single tag or subfield.
+#[derive(Debug)]
+struct Profile {
+    records: u64,
+    /// The number of fields containing the subfield (unused for tags
+    /// and for subfields, which are counted per record).
+    fields: u64,
+    occurrences: u64,
+    min: u64,
+    max: u64,
+    lengths: Option<(u64, u64, u64)>,
+    values: Option<TopK<BString>>,
+}
+
+impl Profile {
+    fn new(is_subfield: bool, top: usize) -> Self {
+        Self {
+            records: 0,
+            fields: 0,
+            occurrences: 0,
+            min: u64::MAX,
+            max: 0,
+            lengths: None,
+            values: if is_subfield && top > 0 {
+                Some(TopK::new((top * 10).max(100)))
+            } else {
+                None
+            },
+        }
+    }
+
+    fn insert_value(&mut self, value: &[u8]) {
+        let len = value.chars().count() as u64;
+        self.lengths = match self.lengths {
+            Some((min, max, sum)) => {
+                Some((min.min(len), max.max(len), sum + len))
+            }
+            None => Some((len, len, len)),
+        };
+
+        if let Some(ref mut values) = self.values {
+            values.insert(BString::from(value));
+        }
+    }
+}
+
+/// A row of the output table.
+#[derive(Debug, Serialize)]
+struct Row {
+    key: String,
+    records: u64,
+    min: u64,
+    max: u64,
+    mean: f64,
+    repeatable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min_len: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_len: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mean_len: Option<f64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    top_values: Vec<Value>,
+}
+
+#[derive(Debug, Serialize)]
+struct Value {
+    value: String,
+    count: u64,
+}
+
+impl Describe {
+    pub(crate) fn run(self, config: &Config) -> CliResult<()> {
+        let skip_invalid = skip_invalid_flag!(
+            self.skip_invalid,
+            config.describe,
+            config.global
+        );
+
+        let mut profiles: BTreeMap<Key, Profile> = BTreeMap::new();
+        let mut records: u64 = 0;
+
+        for filename in self.filenames.iter() {
+            let mut reader =
+                ReaderBuilder::new().from_path(filename)?;
+
+            while let Some(result) = reader.next() {
+                match result {
+                    Err(e) => {
+                        if e.is_invalid_record() && skip_invalid {
+                            continue;
+                        } else {
+                            return Err(e.into());
+                        }
+                    }
+                    Ok(record) => {
+                        let mut counts: HashMap<Key, u64> =
+                            HashMap::new();
+                        let mut seen: HashSet<Key> = HashSet::new();
+                        records += 1;
+
+                        for field in record.iter() {
+                            let tag: [u8; 4] = field
+                                .tag()
+                                .as_bytes()
+                                .try_into()
+                                .expect("tag of four bytes");
+                            let mut codes: BTreeMap<char, u64> =
+                                BTreeMap::new();
+
+                            for subfield in field.subfields() {
+                                profiles
+                                    .entry((tag, Some(subfield.code())))
+                                    .or_insert_with(|| {
+                                        Profile::new(true, self.top)
+                                    })
+                                    .insert_value(subfield.value());
+                                *codes
+                                    .entry(subfield.code())
+                                    .or_insert(0) += 1;
+                            }
+
+                            // By default, the occurrences of a subfield
+                            // are counted per field, so that a subfield
+                            // is only repeatable if it's repeated within
+                            // a single field.
+                            for (code, count) in codes {
+                                let key = (tag, Some(code));
+                                if self.per_record {
+                                    *counts.entry(key).or_insert(0) +=
+                                        count;
+                                    continue;
+                                }
+
+                                let profile =
+                                    profiles.get_mut(&key).unwrap();
+                                profile.fields += 1;
+                                profile.occurrences += count;
+                                profile.min = profile.min.min(count);
+                                profile.max = profile.max.max(count);
+
+                                if seen.insert(key) {
+                                    profile.records += 1;
+                                }
+                            }
+
+                            profiles.entry((tag, None)).or_insert_with(
+                                || Profile::new(false, self.top),
+                            );
+                            *counts.entry((tag, None)).or_insert(0) +=
+                                1;
+                        }
+
+                        for (key, count) in counts {
+                            let profile =
+                                profiles.get_mut(&key).unwrap();
+                            profile.records += 1;
+                            profile.occurrences += count;
+                            profile.min = profile.min.min(count);
+                            profile.max = profile.max.max(count);
+                        }
+                    }
+                }
+            }
+        }
+
+        // The number of fields per tag, which is the basis of the
+        // occurrence statistics of the subfields.
+        let fields: HashMap<[u8; 4], u64> = profiles
+            .iter()
+            .filter(|((_, code), _)| code.is_none())
+            .map(|((tag, _), profile)| (*tag, profile.occurrences))
+            .collect();
+
+        let rows = profiles
+            .into_iter()
+            .map(|(key, profile)| {
+                let per_field = key.1.is_some() && !self.per_record;
+                let total =
+                    if per_field { fields[&key.0] } else { records };
+                self.row(&key, profile, total, per_field)
+            })
+            .collect::<Vec<Row>>();
+
+        let mut writer: Box<dyn Write> = match self.output {
+            Some(ref filename) => Box::new(File::create(filename)?),
+            None => Box::new(io::stdout()),
+        };
+
+        if self.json {
+            writeln!(
+                writer,
+                "{}",
+                serde_json::to_string(&rows).unwrap()
+            )?;
+            writer.flush()?;
+            return Ok(());
+        }
+
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(if self.tsv { b'\t' } else { b',' })
+            .from_writer(writer);
+
+        writer.write_record([
+            "key",
+            "records",
+            "min",
+            "max",
+            "mean",
+            "repeatable",
+            "min_len",
+            "max_len",
+            "mean_len",
+            "top_values",
+        ])?;
+
+        for row in rows {
+            writer.write_record([
+                row.key,
+                row.records.to_string(),
+                row.min.to_string(),
+                row.max.to_string(),
+                format!("{:.2}", row.mean),
+                row.repeatable.to_string(),
+                row.min_len.map(|v| v.to_string()).unwrap_or_default(),
+                row.max_len.map(|v| v.to_string()).unwrap_or_default(),
+                row.mean_len
+                    .map(|v| format!("{v:.2}"))
+                    .unwrap_or_default(),
+                row.top_values
+                    .iter()
+                    .map(|v| format!("{} ({})", v.value, v.count))
+                    .collect::<Vec<_>>()
+                    .join("; "),
+            ])?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Creates the row of a profile. The total is the number of
+    /// records or the number of fields of the tag (subfields, which
+    /// are counted per field), in which the tag or subfield could
+    /// occur.
+    fn row(
+        &self,
+        key: &Key,
+        profile: Profile,
+        total: u64,
+        per_field: bool,
+    ) -> Row {
+        let mut top_values: Vec<(BString, u64)> = profile
+            .values
+            .map(|values| values.into_counts().into_iter().collect())
+            .unwrap_or_default();
+
+        top_values
+            .sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let present = if per_field {
+            profile.fields
+        } else {
+            profile.records
+        };
+
+        Row {
+            key: fmt_key(key),
+            records: profile.records,
+            min: if present < total { 0 } else { profile.min },
+            max: profile.max,
+            mean: round(profile.occurrences as f64 / total as f64),
+            repeatable: profile.max > 1,
+            min_len: profile.lengths.map(|(min, _, _)| min),
+            max_len: profile.lengths.map(|(_, max, _)| max),
+            mean_len: profile.lengths.map(|(_, _, sum)| {
+                round(sum as f64 / profile.occurrences as f64)
+            }),
+            top_values: top_values
+                .into_iter()
+                .take(self.top)
+                .map(|(value, count)| Value {
+                    value: value.to_str_lossy().to_string(),
+                    count,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[inline]
+fn round(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
@@ -1,18 +1,24 @@
 use std::ffi::OsString;
 use std::fs::read_to_string;
+use std::io::{self, Write};
 use std::path::PathBuf;
 
 use clap::{value_parser, Parser};
 use pica_matcher::{
-    MatcherOptions, OccurrenceMatcher, ParseMatcherError,
-    RecordMatcher, TagMatcher,
+    MatcherOptions, NormalizationForm, OccurrenceMatcher,
+    ParseMatcherError, RecordMatcher, StrsimMetric, TagMatcher, Trace,
 };
+use pica_path::PathExt;
 use pica_record::io::{ReaderBuilder, RecordsIterator, WriterBuilder};
+use pica_record::ByteRecord;
 use serde::{Deserialize, Serialize};
+use termcolor::{
+    Color, ColorChoice, ColorSpec, StandardStream, WriteColor,
+};
 
 use crate::common::FilterList;
 use crate::translit::translit_maybe2;
-use crate::util::{CliError, CliResult};
+use crate::util::CliResult;
 use crate::{gzip_flag, skip_invalid_flag, Config};
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -44,6 +50,29 @@ pub(crate) struct Filter {
         default_value = "75")]
     strsim_threshold: u8,
 
+    /// The metric for string similarity comparisons (possible values:
+    /// "levenshtein", "damerau-levenshtein", "jaro-winkler" and
+    /// "token-set")
+    #[arg(long, value_name = "METRIC", default_value = "levenshtein")]
+    strsim_metric: StrsimMetric,
+
+    /// Normalize values into the Unicode normal form <NF> before
+    /// comparing them (possible values: "nfd", "nfkd", "nfc" and
+    /// "nfkc")
+    #[arg(long, value_name = "NF")]
+    normalize: Option<NormalizationForm>,
+
+    /// When this flag is provided, comparision operations will fold
+    /// the case (e.g. "Straße" equals "STRASSE"), which approximates
+    /// the full Unicode case folding
+    #[arg(long)]
+    case_fold: bool,
+
+    /// When this flag is provided, comparision operations will ignore
+    /// diacritics (e.g. "Müller" equals "Muller")
+    #[arg(long)]
+    ignore_diacritics: bool,
+
     /// Keep only fields specified by a list of predicates.
     #[arg(long, short)]
     keep: Option<String>,
@@ -87,6 +116,15 @@ pub(crate) struct Filter {
     #[arg(long, short, value_name = "n", default_value = "0")]
     limit: usize,
 
+    /// Explain why a record matched or failed the filter expression
+    ///
+    /// Instead of writing the matching records, the evaluation of each
+    /// sub-expression is printed for every record. Use this option
+    /// together with `--limit` (which then limits the number of
+    /// explained records) to explain the first <n> records only.
+    #[arg(long, conflicts_with_all = ["output", "tee", "append", "gzip"])]
+    explain: bool,
+
     /// Connects the filter with additional expressions using the
     /// logical AND-operator (conjunction)
     ///
@@ -166,6 +204,15 @@ impl Filter {
         let keep_predicates =
             parse_predicates(&self.keep.unwrap_or_default())?;
 
+        // File references in a filter expression file are resolved
+        // relative to the directory of the file.
+        let base_dir = match self.expr_file {
+            Some(ref filename) => {
+                filename.parent().map(PathBuf::from).unwrap_or_default()
+            }
+            None => PathBuf::new(),
+        };
+
         let filter_str = if let Some(filename) = self.expr_file {
             read_to_string(filename).unwrap()
         } else {
@@ -178,30 +225,30 @@ impl Filter {
             filter_str
         };
 
-        let mut filter = match RecordMatcher::new(&filter_str) {
-            Ok(f) => f,
-            _ => {
-                return Err(CliError::Other(format!(
-                    "invalid filter: \"{filter_str}\""
-                )))
-            }
-        };
+        let ctx = config.parse_context();
+        let mut filter = RecordMatcher::with_context(
+            &filter_str,
+            &ctx.clone().base_dir(base_dir),
+        )?;
 
         if !self.and.is_empty() {
             for predicate in self.and.iter() {
-                filter = filter & RecordMatcher::new(predicate)?;
+                filter = filter
+                    & RecordMatcher::with_context(predicate, &ctx)?;
             }
         }
 
         if !self.not.is_empty() {
             for predicate in self.not.iter() {
-                filter = filter & !RecordMatcher::new(predicate)?;
+                filter = filter
+                    & !RecordMatcher::with_context(predicate, &ctx)?;
             }
         }
 
         if !self.or.is_empty() {
             for predicate in self.or.iter() {
-                filter = filter | RecordMatcher::new(predicate)?;
+                filter = filter
+                    | RecordMatcher::with_context(predicate, &ctx)?;
             }
         }
 
@@ -217,10 +264,22 @@ impl Filter {
             FilterList::default()
         };
 
+        let color_choice = if atty::is(atty::Stream::Stdout) {
+            ColorChoice::Auto
+        } else {
+            ColorChoice::Never
+        };
+
+        let mut stdout = StandardStream::stdout(color_choice);
         let mut count = 0;
         let options = MatcherOptions::new()
             .strsim_threshold(self.strsim_threshold as f64 / 100.0)
-            .case_ignore(self.ignore_case);
+            .strsim_metric(self.strsim_metric)
+            .case_ignore(self.ignore_case)
+            .case_fold(self.case_fold)
+            .diacritic_ignore(self.ignore_diacritics)
+            .normalization(self.normalize);
+        let compiled = filter.compile(&options);
 
         for filename in self.filenames {
             let mut reader =
@@ -248,8 +307,24 @@ impl Filter {
                             continue;
                         }
 
-                        let mut is_match =
-                            filter.is_match(&record, &options);
+                        if self.explain {
+                            let trace = filter.trace(&record, &options);
+                            explain(
+                                &mut stdout,
+                                &record,
+                                &trace,
+                                self.invert_match,
+                            )?;
+
+                            count += 1;
+                            if self.limit > 0 && count >= self.limit {
+                                break;
+                            }
+
+                            continue;
+                        }
+
+                        let mut is_match = compiled.is_match(&record);
 
                         if self.invert_match {
                             is_match = !is_match;
@@ -306,10 +381,69 @@ impl Filter {
             writer.finish()?;
         }
 
+        stdout.reset()?;
+        stdout.flush()?;
+
         Ok(())
     }
 }
 
+/// Prints the evaluation trace of a record.
+fn explain(
+    out: &mut StandardStream,
+    record: &ByteRecord,
+    trace: &Trace,
+    invert_match: bool,
+) -> io::Result<()> {
+    let is_match = trace.result() != invert_match;
+    let idn = record
+        .idn()
+        .map(|idn| idn.to_string())
+        .unwrap_or_else(|| "?".into());
+
+    out.set_color(ColorSpec::new().set_bold(true))?;
+    write!(out, "{idn}: ")?;
+    out.set_color(ColorSpec::new().set_bold(true).set_fg(Some(
+        if is_match { Color::Green } else { Color::Red },
+    )))?;
+    writeln!(out, "{}", if is_match { "match" } else { "no match" })?;
+    out.reset()?;
+
+    explain_trace(out, trace, 1)?;
+    writeln!(out)
+}
+
+/// Prints the trace of a (sub-)expression and its sub-expressions.
+fn explain_trace(
+    out: &mut StandardStream,
+    trace: &Trace,
+    depth: usize,
+) -> io::Result<()> {
+    write!(out, "{}", "  ".repeat(depth))?;
+    if trace.result() {
+        out.set_color(ColorSpec::new().set_fg(Some(Color::Green)))?;
+        write!(out, "✓")?;
+    } else {
+        out.set_color(ColorSpec::new().set_fg(Some(Color::Red)))?;
+        write!(out, "✗")?;
+    }
+    out.reset()?;
+
+    write!(out, " {}", trace.expr())?;
+    if !trace.matches().is_empty() {
+        out.set_color(ColorSpec::new().set_dimmed(true))?;
+        write!(out, "  [{}]", trace.matches().join(", "))?;
+        out.reset()?;
+    }
+    writeln!(out)?;
+
+    for child in trace.children() {
+        explain_trace(out, child, depth + 1)?;
+    }
+
+    Ok(())
+}
+
 fn parse_predicates(
     s: &str,
 ) -> Result<Vec<(TagMatcher, OccurrenceMatcher)>, ParseMatcherError> {
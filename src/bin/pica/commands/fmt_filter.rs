@@ -0,0 +1,86 @@
+use std::ffi::OsString;
+use std::fs::{read_to_string, File};
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use clap::Parser;
+use pica_matcher::RecordMatcher;
+
+use crate::config::Config;
+use crate::util::{CliError, CliResult};
+
+/// Format a filter expression in its canonical form
+///
+/// The expression is parsed and written with normalized operators,
+/// quoting and parentheses (e.g. `003@ $0 == "abc" && (002@?)`
+/// becomes `003@.0 == 'abc' && 002@?`). References to list files
+/// (`0 in @codes.txt`) are kept. With `--json` the abstract syntax
+/// tree (AST) of the expression is written as JSON instead.
+#[derive(Parser, Debug)]
+pub(crate) struct FmtFilter {
+    /// Write the abstract syntax tree (AST) of the expression as JSON
+    #[arg(long)]
+    json: bool,
+
+    /// Don't write the formatted expression, but fail if the
+    /// expression isn't in its canonical form
+    #[arg(long, conflicts_with_all = ["json", "output"])]
+    check: bool,
+
+    /// Take the filter expression from <EXPR_FILE>
+    #[arg(long = "file", short = 'f', conflicts_with = "filter")]
+    expr_file: Option<PathBuf>,
+
+    /// Write output to <filename> instead of stdout
+    #[arg(short, long, value_name = "filename")]
+    output: Option<OsString>,
+
+    /// The filter expression to format
+    #[arg(required_unless_present = "expr_file")]
+    filter: Option<String>,
+}
+
+impl FmtFilter {
+    pub(crate) fn run(self, config: &Config) -> CliResult<()> {
+        // References to list files are kept as they are, so the files
+        // don't need to exist.
+        let filter_str = match self.expr_file {
+            Some(filename) => read_to_string(filename)?,
+            None => self.filter.unwrap_or_default(),
+        };
+
+        let filter = RecordMatcher::parse_with_context(
+            &filter_str,
+            &config.parse_context(),
+        )?;
+
+        if self.check {
+            return if filter.to_string() == filter_str.trim() {
+                Ok(())
+            } else {
+                Err(CliError::Other(format!(
+                    "filter expression isn't formatted (expected `{filter}`)"
+                )))
+            };
+        }
+
+        let mut writer: Box<dyn Write> = match self.output {
+            Some(filename) => Box::new(File::create(filename)?),
+            None => Box::new(io::stdout()),
+        };
+
+        if self.json {
+            serde_json::to_writer_pretty(
+                &mut writer,
+                filter.field_matcher(),
+            )
+            .map_err(io::Error::from)?;
+            writeln!(writer)?;
+        } else {
+            writeln!(writer, "{filter}")?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+}
@@ -1,21 +1,22 @@
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ffi::OsString;
 use std::fs::File;
 use std::io::{self, Write};
-use std::str::FromStr;
 
-use bstr::BString;
+use bstr::{BString, ByteSlice};
 use clap::{value_parser, Parser};
-use pica_matcher::MatcherOptions;
-use pica_path::{Path, PathExt};
+use pica_matcher::{MatcherOptions, NormalizationForm, StrsimMetric};
+use pica_path::{parse_path_list, PathExt};
 use pica_record::io::{ReaderBuilder, RecordsIterator};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 use crate::config::Config;
+use crate::sketch::{HyperLogLog, TopK};
 use crate::skip_invalid_flag;
 use crate::translit::{translit_maybe, translit_maybe2};
-use crate::util::CliResult;
+use crate::util::{CliError, CliResult};
 
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
@@ -35,6 +36,21 @@ pub(crate) struct FrequencyConfig {
 /// be resricted by an optional subfield filter. A subfield filter
 /// requires the {}-notation and is expected at the first position (e.g.
 /// "044H/*{b == 'GND && 9?, 9}").
+///
+/// If a comma-separated list of path expressions is given, the
+/// combinations of the values are counted (cross tabulation). As with
+/// the `select` command, the combinations of a record are the cartesian
+/// product of the values of each path.
+///
+/// With the `--approx` flag, only a bounded number of candidates is
+/// kept in memory and their frequencies are estimated by a Count-Min
+/// sketch. The estimated number of distinct values (HyperLogLog) is
+/// printed to stderr.
+///
+/// The complete frequency table can be saved with `--save-state` and
+/// combined with other saved tables later on with `--merge`. In that
+/// case, the path expression and the input files are optional. Saving
+/// the state isn't possible in approximate mode.
 #[derive(Parser, Debug)]
 pub(crate) struct Frequency {
     /// Skip invalid records that can't be decoded as normalized PICA+.
@@ -52,10 +68,49 @@ pub(crate) struct Frequency {
           default_value = "75")]
     strsim_threshold: u8,
 
+    /// The metric for string similarity comparisons (possible values:
+    /// "levenshtein", "damerau-levenshtein", "jaro-winkler" and
+    /// "token-set")
+    #[arg(long, value_name = "METRIC", default_value = "levenshtein")]
+    strsim_metric: StrsimMetric,
+
+    /// Normalize values into the Unicode normal form <NF> before
+    /// comparing them (possible values: "nfd", "nfkd", "nfc" and
+    /// "nfkc")
+    #[arg(long, value_name = "NF")]
+    normalize: Option<NormalizationForm>,
+
+    /// When this flag is provided, comparision operations will fold
+    /// the case (e.g. "Straße" equals "STRASSE"), which approximates
+    /// the full Unicode case folding
+    #[arg(long)]
+    case_fold: bool,
+
+    /// When this flag is provided, comparision operations will ignore
+    /// diacritics (e.g. "Müller" equals "Muller")
+    #[arg(long)]
+    ignore_diacritics: bool,
+
     /// Sort results in reverse order.
-    #[arg(long, short)]
+    #[arg(long, short, conflicts_with = "approx")]
     reverse: bool,
 
+    /// Approximate the frequencies of the most frequent values in
+    /// bounded memory and estimate the number of distinct values.
+    #[arg(long)]
+    approx: bool,
+
+    /// The number of candidates, which are tracked in approximate
+    /// mode.
+    #[arg(
+        long,
+        value_name = "n",
+        default_value = "1000",
+        requires = "approx",
+        value_parser = value_parser!(u64).range(1..),
+    )]
+    approx_capacity: u64,
+
     /// Limit result to the <n> most frequent subfield values.
     #[arg(
         long,
@@ -75,14 +130,32 @@ pub(crate) struct Frequency {
     )]
     threshold: u64,
 
+    /// Add a column with the relative frequency (in percent).
+    #[arg(long, short)]
+    percentage: bool,
+
+    /// Add a column with the cumulative relative frequency (in
+    /// percent).
+    #[arg(long)]
+    cumulative: bool,
+
     /// Comma-separated list of column names.
     #[arg(long, short = 'H')]
     header: Option<String>,
 
     /// Write output tab-separated (TSV)
-    #[arg(long, short)]
+    #[arg(long, short, conflicts_with_all = ["markdown", "json"])]
     tsv: bool,
 
+    /// Write output as a Markdown table
+    #[arg(long, conflicts_with_all = ["tsv", "json"])]
+    markdown: bool,
+
+    /// Write output as JSON (an array of objects, whose keys are in
+    /// column order)
+    #[arg(long, conflicts_with_all = ["tsv", "markdown"])]
+    json: bool,
+
     /// Transliterate output into the selected normalform <NF>
     /// (possible values: "nfd", "nfkd", "nfc" and "nfkc").
     #[arg(long,
@@ -96,8 +169,22 @@ pub(crate) struct Frequency {
     #[arg(short, long, value_name = "filename")]
     output: Option<OsString>,
 
-    /// A PICA path expression
-    path: String,
+    /// Save the complete (unlimited) frequency table to <filename>,
+    /// which can be merged with other tables by the `--merge` option.
+    /// The estimated frequencies of the `--approx` mode can't be
+    /// saved.
+    #[arg(long, value_name = "filename", conflicts_with = "approx")]
+    save_state: Option<OsString>,
+
+    /// Merge the frequency table saved in <filename> into the result.
+    /// This option can be given multiple times.
+    #[arg(long, value_name = "filename")]
+    merge: Vec<OsString>,
+
+    /// A PICA path expression or a comma-separated list of path
+    /// expressions
+    #[arg(required_unless_present = "merge")]
+    path: Option<String>,
 
     /// Read one or more files in normalized PICA+ format. With no
     /// files, or when a filename is '-', read from stanard input
@@ -114,30 +201,63 @@ impl Frequency {
             config.global
         );
 
-        let path = if let Some(ref global) = config.global {
-            Path::from_str(&translit_maybe2(
-                &self.path,
-                global.translit,
-            ))?
+        let paths = match self.path {
+            Some(ref path) => {
+                if let Some(ref global) = config.global {
+                    Some(parse_path_list(&translit_maybe2(
+                        path,
+                        global.translit,
+                    ))?)
+                } else {
+                    Some(parse_path_list(path)?)
+                }
+            }
+            None => None,
+        };
+
+        let mut states = vec![];
+        for filename in self.merge.iter() {
+            states.push(read_state(filename)?);
+        }
+
+        let num_paths = match paths {
+            Some(ref paths) => paths.len(),
+            None => states.first().map(|(n, _)| *n).unwrap_or(1),
+        };
+
+        if states.iter().any(|(n, _)| *n != num_paths) {
+            return Err(CliError::Other(format!(
+                "invalid frequency table: expected {num_paths} value \
+                 column(s)"
+            )));
+        }
+
+        let header = self.header(num_paths)?;
+        let mut ftable: HashMap<Vec<BString>, u64> = HashMap::new();
+        let mut total: u64 = 0;
+        let mut approx = if self.approx {
+            let capacity =
+                (self.approx_capacity as usize).max(self.limit);
+            Some((TopK::new(capacity), HyperLogLog::new(14)))
         } else {
-            Path::from_str(&self.path)?
+            None
         };
 
-        let mut ftable: HashMap<BString, u64> = HashMap::new();
         let options = MatcherOptions::new()
             .strsim_threshold(self.strsim_threshold as f64 / 100f64)
-            .case_ignore(self.ignore_case);
+            .strsim_metric(self.strsim_metric)
+            .case_ignore(self.ignore_case)
+            .case_fold(self.case_fold)
+            .diacritic_ignore(self.ignore_diacritics)
+            .normalization(self.normalize);
 
-        let writer: Box<dyn Write> = match self.output {
-            Some(filename) => Box::new(File::create(filename)?),
-            None => Box::new(io::stdout()),
+        let filenames = if paths.is_some() {
+            self.filenames.as_slice()
+        } else {
+            &[]
         };
 
-        let mut writer = csv::WriterBuilder::new()
-            .delimiter(if self.tsv { b'\t' } else { b',' })
-            .from_writer(writer);
-
-        for filename in self.filenames {
+        for filename in filenames.iter() {
             let mut reader =
                 ReaderBuilder::new().from_path(filename)?;
 
@@ -151,21 +271,69 @@ impl Frequency {
                         }
                     }
                     Ok(record) => {
-                        for value in record.path(&path, &options) {
-                            *ftable
-                                .entry(BString::from(value.to_vec()))
-                                .or_insert(0) += 1;
+                        let rows = paths
+                            .iter()
+                            .flatten()
+                            .map(|path| {
+                                let values =
+                                    record.path(path, &options);
+                                if values.is_empty() {
+                                    vec![BString::from("")]
+                                } else {
+                                    values
+                                        .into_iter()
+                                        .map(|value| {
+                                            BString::from(
+                                                value.to_vec(),
+                                            )
+                                        })
+                                        .collect()
+                                }
+                            })
+                            .fold(vec![vec![]], cartesian_product);
+
+                        for row in rows {
+                            if row.iter().all(|col| col.is_empty()) {
+                                continue;
+                            }
+
+                            total += 1;
+
+                            if let Some((ref mut topk, ref mut hll)) =
+                                approx
+                            {
+                                hll.insert(&row);
+                                topk.insert(row);
+                            } else {
+                                *ftable.entry(row).or_insert(0) += 1;
+                            }
                         }
                     }
                 }
             }
         }
 
-        if let Some(header) = self.header {
-            writer.write_record(header.split(',').map(|s| s.trim()))?;
+        if let Some((topk, mut hll)) = approx {
+            // The values of merged tables are distinct values as well.
+            for (values, _) in states.iter().flat_map(|(_, s)| s) {
+                hll.insert(values);
+            }
+
+            eprintln!(
+                "estimated number of distinct values: {}",
+                hll.count()
+            );
+            ftable = topk.into_counts();
+        }
+
+        for (_, state) in states {
+            for (values, frequency) in state {
+                total += frequency;
+                *ftable.entry(values).or_insert(0) += frequency;
+            }
         }
 
-        let mut ftable_sorted: Vec<(&BString, &u64)> =
+        let mut ftable_sorted: Vec<(&Vec<BString>, &u64)> =
             ftable.iter().collect();
         if self.reverse {
             ftable_sorted.sort_by(|a, b| match a.1.cmp(b.1) {
@@ -179,25 +347,317 @@ impl Frequency {
             });
         }
 
-        for (i, (value, frequency)) in ftable_sorted.iter().enumerate()
+        if let Some(ref filename) = self.save_state {
+            let mut writer = csv::Writer::from_path(filename)?;
+            writer.write_record(&header.1[..=num_paths])?;
+
+            for (values, frequency) in ftable_sorted.iter() {
+                let mut record = (*values).clone();
+                record.push(frequency.to_string().into());
+                writer.write_record(record)?;
+            }
+
+            writer.flush()?;
+        }
+
+        let mut rows: Vec<Row> = vec![];
+        let mut cumulative = 0;
+
+        // Rows are filtered before the limit is applied, because the
+        // rows below the threshold come first in reverse order.
+        for (i, (values, frequency)) in ftable_sorted
+            .iter()
+            .filter(|(_, frequency)| **frequency > self.threshold)
+            .enumerate()
         {
             if self.limit > 0 && i >= self.limit {
                 break;
             }
 
-            if **frequency <= self.threshold {
-                break;
+            cumulative += **frequency;
+
+            rows.push(Row {
+                values: values
+                    .iter()
+                    .map(|value| {
+                        translit_maybe(
+                            &value.to_str_lossy(),
+                            self.translit.as_deref(),
+                        )
+                    })
+                    .collect(),
+                frequency: **frequency,
+                percentage: percentage(**frequency, total),
+                cumulative: percentage(cumulative, total),
+            });
+        }
+
+        let writer: Box<dyn Write> = match self.output {
+            Some(ref filename) => Box::new(File::create(filename)?),
+            None => Box::new(io::stdout()),
+        };
+
+        if self.json {
+            self.write_json(writer, header, &rows)
+        } else if self.markdown {
+            self.write_markdown(writer, header, &rows)
+        } else {
+            self.write_csv(writer, header, &rows)
+        }
+    }
+
+    /// Returns the column names of the frequency table. If no header
+    /// was given, default column names are returned, which are only
+    /// used by output formats that require a header.
+    fn header(
+        &self,
+        num_paths: usize,
+    ) -> CliResult<(bool, Vec<String>)> {
+        let num_columns = num_paths
+            + 1
+            + usize::from(self.percentage)
+            + usize::from(self.cumulative);
+
+        if let Some(ref header) = self.header {
+            let columns: Vec<String> = header
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .collect();
+
+            if columns.len() != num_columns {
+                return Err(CliError::Other(format!(
+                    "invalid header: expected {num_columns} column names, \
+                     got {}",
+                    columns.len()
+                )));
             }
 
-            let value = translit_maybe(
-                &value.to_string(),
-                self.translit.as_deref(),
-            );
+            let mut seen = HashSet::new();
+            if let Some(name) =
+                columns.iter().find(|name| !seen.insert(*name))
+            {
+                return Err(CliError::Other(format!(
+                    "invalid header: duplicate column name `{name}`"
+                )));
+            }
+
+            return Ok((true, columns));
+        }
+
+        let mut columns: Vec<String> = if num_paths == 1 {
+            vec!["value".into()]
+        } else {
+            (1..=num_paths).map(|i| format!("value{i}")).collect()
+        };
+
+        columns.push("count".into());
+        if self.percentage {
+            columns.push("percentage".into());
+        }
+        if self.cumulative {
+            columns.push("cumulative".into());
+        }
+
+        Ok((false, columns))
+    }
+
+    fn columns(&self, row: &Row) -> Vec<String> {
+        let mut columns = row.values.clone();
+        columns.push(row.frequency.to_string());
+        if self.percentage {
+            columns.push(format!("{:.2}", row.percentage));
+        }
+        if self.cumulative {
+            columns.push(format!("{:.2}", row.cumulative));
+        }
+
+        columns
+    }
+
+    fn write_csv(
+        &self,
+        writer: Box<dyn Write>,
+        (has_header, header): (bool, Vec<String>),
+        rows: &[Row],
+    ) -> CliResult<()> {
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(if self.tsv { b'\t' } else { b',' })
+            .from_writer(writer);
+
+        if has_header {
+            writer.write_record(header)?;
+        }
+
+        for row in rows {
+            writer.write_record(self.columns(row))?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn write_markdown(
+        &self,
+        mut writer: Box<dyn Write>,
+        (_, header): (bool, Vec<String>),
+        rows: &[Row],
+    ) -> CliResult<()> {
+        let num_values = header.len()
+            - 1
+            - usize::from(self.percentage)
+            - usize::from(self.cumulative);
+
+        writeln!(writer, "| {} |", header.join(" | "))?;
+        writeln!(
+            writer,
+            "|{}",
+            (0..header.len())
+                .map(|i| if i < num_values {
+                    " --- |"
+                } else {
+                    " ---: |"
+                })
+                .collect::<String>()
+        )?;
+
+        for row in rows {
+            let columns: Vec<String> = self
+                .columns(row)
+                .iter()
+                .map(|column| column.replace('|', "\\|"))
+                .collect();
 
-            writer.write_record(&[value, frequency.to_string()])?;
+            writeln!(writer, "| {} |", columns.join(" | "))?;
         }
 
         writer.flush()?;
         Ok(())
     }
+
+    fn write_json(
+        &self,
+        mut writer: Box<dyn Write>,
+        (_, header): (bool, Vec<String>),
+        rows: &[Row],
+    ) -> CliResult<()> {
+        let data = rows
+            .iter()
+            .map(|row| {
+                let mut values: Vec<Value> = row
+                    .values
+                    .iter()
+                    .map(|value| Value::from(value.as_str()))
+                    .collect();
+
+                values.push(Value::from(row.frequency));
+                if self.percentage {
+                    values.push(Value::from(round(row.percentage)));
+                }
+                if self.cumulative {
+                    values.push(Value::from(round(row.cumulative)));
+                }
+
+                Value::Object(
+                    header
+                        .iter()
+                        .cloned()
+                        .zip(values)
+                        .collect::<Map<_, _>>(),
+                )
+            })
+            .collect::<Vec<Value>>();
+
+        writeln!(
+            writer,
+            "{}",
+            serde_json::to_string(&Value::Array(data)).unwrap()
+        )?;
+
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// A saved frequency table (the number of value columns and the rows).
+type State = (usize, Vec<(Vec<BString>, u64)>);
+
+/// Reads a frequency table, which was saved by the `--save-state`
+/// option, and returns the number of value columns and the table.
+fn read_state(filename: &OsString) -> CliResult<State> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_path(filename)?;
+
+    let width = reader.byte_headers()?.len();
+    if width < 2 {
+        return Err(CliError::Other(format!(
+            "invalid frequency table `{}`",
+            filename.to_string_lossy()
+        )));
+    }
+
+    let mut table = vec![];
+    for result in reader.byte_records() {
+        let record = result?;
+        let frequency = record[width - 1]
+            .to_str()
+            .ok()
+            .and_then(|value| value.parse::<u64>().ok())
+            .ok_or_else(|| {
+                CliError::Other(format!(
+                    "invalid frequency table `{}`",
+                    filename.to_string_lossy()
+                ))
+            })?;
+
+        let values = record
+            .iter()
+            .take(width - 1)
+            .map(|value| BString::from(value.to_vec()))
+            .collect();
+
+        table.push((values, frequency));
+    }
+
+    Ok((width - 1, table))
+}
+
+/// A row of the frequency table.
+struct Row {
+    values: Vec<String>,
+    frequency: u64,
+    percentage: f64,
+    cumulative: f64,
+}
+
+/// Extends each row by every value of the next column.
+fn cartesian_product(
+    rows: Vec<Vec<BString>>,
+    values: Vec<BString>,
+) -> Vec<Vec<BString>> {
+    let mut result = Vec::with_capacity(rows.len() * values.len());
+
+    for row in rows.iter() {
+        for value in values.iter() {
+            let mut row = row.clone();
+            row.push(value.clone());
+            result.push(row);
+        }
+    }
+
+    result
+}
+
+#[inline]
+fn percentage(frequency: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        frequency as f64 * 100.0 / total as f64
+    }
+}
+
+#[inline]
+fn round(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
 }
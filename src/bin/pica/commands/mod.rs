@@ -2,31 +2,39 @@ mod cat;
 mod completions;
 mod convert;
 mod count;
+mod describe;
 mod filter;
+mod fmt_filter;
 mod frequency;
 mod invalid;
 mod json;
 mod partition;
 mod print;
 mod sample;
+mod schema;
 mod select;
 mod slice;
 mod split;
+mod validate;
 mod xml;
 
 pub(crate) use cat::{Cat, CatConfig};
 pub(crate) use completions::Completions;
 pub(crate) use convert::{Convert, ConvertConfig};
 pub(crate) use count::{Count, CountConfig};
+pub(crate) use describe::{Describe, DescribeConfig};
 pub(crate) use filter::{Filter, FilterConfig};
+pub(crate) use fmt_filter::FmtFilter;
 pub(crate) use frequency::{Frequency, FrequencyConfig};
 pub(crate) use invalid::Invalid;
 pub(crate) use json::{Json, JsonConfig};
 pub(crate) use partition::{Partition, PartitionConfig};
 pub(crate) use print::{Print, PrintConfig};
 pub(crate) use sample::{Sample, SampleConfig};
+pub(crate) use schema::{Schema, SchemaConfig};
 pub(crate) use select::{Select, SelectConfig};
 pub(crate) use slice::{Slice, SliceConfig};
 pub(crate) use split::{Split, SplitConfig};
+pub(crate) use validate::{Validate, ValidateConfig};
 
 pub(crate) use self::xml::{Xml, XmlConfig};
@@ -0,0 +1,117 @@
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{self, Write};
+
+use clap::{Parser, Subcommand};
+use pica_record::io::{ReaderBuilder, RecordsIterator};
+use pica_schema::SchemaInferrer;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::skip_invalid_flag;
+use crate::util::CliResult;
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct SchemaConfig {
+    /// Skip invalid records that can't be decoded.
+    pub(crate) skip_invalid: Option<bool>,
+}
+
+/// Work with Avram schemas
+#[derive(Parser, Debug)]
+pub(crate) struct Schema {
+    #[command(subcommand)]
+    command: SchemaCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum SchemaCommand {
+    Infer(Infer),
+}
+
+/// Infer an Avram schema from a set of records
+///
+/// The schema contains a definition of each field (a field with an
+/// occurrence is defined separately, except for level 2 fields) and
+/// each subfield. A field or subfield is required, if it occurs in
+/// every record (field) and repeatable, if it occurs more than once in
+/// a record (field). For each subfield, either a codelist (if the
+/// number of distinct values is small) or a pattern of the observed
+/// values is inferred.
+#[derive(Parser, Debug)]
+struct Infer {
+    /// Skip invalid records that can't be decoded as normalized PICA+.
+    #[arg(long, short)]
+    skip_invalid: bool,
+
+    /// The maximum number of distinct values of a subfield, which are
+    /// turned into a codelist (a value of 0 disables codelists).
+    #[arg(long, value_name = "n", default_value = "10")]
+    max_codes: usize,
+
+    /// The title of the schema.
+    #[arg(long)]
+    title: Option<String>,
+
+    /// Write output to <filename> instead of stdout.
+    #[arg(short, long, value_name = "filename")]
+    output: Option<OsString>,
+
+    /// Read one or more files in normalized PICA+ format. With no
+    /// files, or when a filename is '-', read from stanard input
+    /// (stdin).
+    #[arg(default_value = "-", hide_default_value = true)]
+    filenames: Vec<OsString>,
+}
+
+impl Schema {
+    pub(crate) fn run(self, config: &Config) -> CliResult<()> {
+        match self.command {
+            SchemaCommand::Infer(cmd) => cmd.run(config),
+        }
+    }
+}
+
+impl Infer {
+    fn run(self, config: &Config) -> CliResult<()> {
+        let skip_invalid = skip_invalid_flag!(
+            self.skip_invalid,
+            config.schema,
+            config.global
+        );
+
+        let mut inferrer =
+            SchemaInferrer::new().max_codes(self.max_codes);
+
+        for filename in self.filenames.iter() {
+            let mut reader =
+                ReaderBuilder::new().from_path(filename)?;
+
+            while let Some(result) = reader.next() {
+                match result {
+                    Err(e) => {
+                        if e.is_invalid_record() && skip_invalid {
+                            continue;
+                        } else {
+                            return Err(e.into());
+                        }
+                    }
+                    Ok(record) => inferrer.update(&record),
+                }
+            }
+        }
+
+        let mut schema = inferrer.finish();
+        schema.title = self.title;
+
+        let mut writer: Box<dyn Write> = match self.output {
+            Some(ref filename) => Box::new(File::create(filename)?),
+            None => Box::new(io::stdout()),
+        };
+
+        writeln!(writer, "{}", schema.to_json())?;
+        writer.flush()?;
+        Ok(())
+    }
+}
@@ -7,7 +7,8 @@ use std::io::{self, Read, Write};
 
 use clap::Parser;
 use pica::matcher::{MatcherFlags, RecordMatcher};
-use pica::{Outcome, Reader, ReaderBuilder, Selectors};
+use pica::{Outcome, Reader, ReaderBuilder, RecordView, Selectors};
+use pica_matcher::{NormalizationForm, Scope};
 use serde::{Deserialize, Serialize};
 
 use crate::config::Config;
@@ -40,6 +41,28 @@ pub(crate) struct Select {
     #[arg(long, short)]
     ignore_case: bool,
 
+    /// Normalize values into the Unicode normal form <NF> before
+    /// comparing them (possible values: "nfd", "nfkd", "nfc" and
+    /// "nfkc")
+    #[arg(long, value_name = "NF")]
+    normalize: Option<NormalizationForm>,
+
+    /// When this flag is provided, comparision operations will fold
+    /// the case (e.g. "Straße" equals "STRASSE"), which approximates
+    /// the full Unicode case folding
+    #[arg(long)]
+    case_fold: bool,
+
+    /// When this flag is provided, comparision operations will ignore
+    /// diacritics (e.g. "Müller" equals "Muller")
+    #[arg(long)]
+    ignore_diacritics: bool,
+
+    /// Form the cartesian product of the selectors only within one
+    /// holding block (possible values: "local" and "copy")
+    #[arg(long, value_name = "SCOPE")]
+    scope: Option<Scope>,
+
     /// Write output tab-separated (TSV)
     #[arg(long, short)]
     tsv: bool,
@@ -113,30 +136,31 @@ impl Select {
             self.selectors.to_string()
         };
 
-        let selectors = match Selectors::decode(&selectors) {
-            Ok(val) => val,
-            _ => {
-                return Err(CliError::Other(format!(
-                    "invalid select list: {}",
-                    self.selectors
-                )))
-            }
-        };
+        let selectors = Selectors::decode(&selectors).map_err(|e| {
+            CliError::Other(format!("invalid select list: {e}"))
+        })?;
 
         if let Some(header) = self.header {
             writer.write_record(header.split(',').map(|s| s.trim()))?;
         }
 
-        let flags = MatcherFlags::default();
+        let flags = MatcherFlags::new()
+            .case_fold(self.case_fold)
+            .ignore_diacritics(self.ignore_diacritics)
+            .normalization(self.normalize);
+
+        // Subfield filters of selectors are case insensitive with
+        // `--ignore-case`.
+        let select_flags = flags
+            .clone()
+            .ignore_case(self.ignore_case)
+            .strsim_threshold(0.0);
+
         let filter = match self.filter {
-            Some(filter_str) => match RecordMatcher::new(&filter_str) {
-                Ok(f) => f,
-                _ => {
-                    return Err(CliError::Other(format!(
-                        "invalid filter: \"{filter_str}\""
-                    )))
-                }
-            },
+            Some(filter_str) => RecordMatcher::with_definitions(
+                filter_str,
+                &config.filters.clone().unwrap_or_default(),
+            )?,
             None => RecordMatcher::True,
         };
 
@@ -157,12 +181,23 @@ impl Select {
                     continue;
                 }
 
-                let outcome = selectors
-                    .iter()
-                    .map(|selector| {
-                        record.select(selector, self.ignore_case)
-                    })
-                    .fold(Outcome::default(), |acc, x| acc * x);
+                let select = |view: &RecordView| {
+                    selectors
+                        .iter()
+                        .map(|selector| {
+                            view.select(selector, &select_flags)
+                        })
+                        .fold(Outcome::default(), |acc, x| acc * x)
+                };
+
+                let outcome = match self.scope {
+                    Some(ref scope) => record
+                        .split(scope)
+                        .iter()
+                        .map(select)
+                        .fold(Outcome::default(), |acc, x| acc + x),
+                    None => select(&RecordView::from(&*record)),
+                };
 
                 for row in outcome.iter() {
                     if self.no_empty_columns
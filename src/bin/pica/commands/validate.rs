@@ -0,0 +1,109 @@
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use clap::Parser;
+use pica_path::PathExt;
+use pica_record::io::{ReaderBuilder, RecordsIterator};
+use pica_schema::{Schema, Validator};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::skip_invalid_flag;
+use crate::util::CliResult;
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct ValidateConfig {
+    /// Skip invalid records that can't be decoded.
+    pub(crate) skip_invalid: Option<bool>,
+}
+
+/// Validate records against an Avram schema
+///
+/// Each violation of the schema is reported as a row, which contains
+/// the IDN of the record, the field (tag and optional occurrence), the
+/// subfield code (if any) and a description of the violation. The
+/// following violations are reported: unknown fields and subfields,
+/// invalid occurrences, repeated non-repeatable fields and subfields,
+/// missing required fields and subfields and values that don't match a
+/// pattern or aren't in a codelist.
+#[derive(Parser, Debug)]
+pub(crate) struct Validate {
+    /// Skip invalid records that can't be decoded as normalized PICA+.
+    #[arg(long, short)]
+    skip_invalid: bool,
+
+    /// The Avram schema (JSON) to validate the records against.
+    #[arg(long, value_name = "filename")]
+    schema: PathBuf,
+
+    /// Write output tab-separated (TSV)
+    #[arg(long, short)]
+    tsv: bool,
+
+    /// Write output to <filename> instead of stdout.
+    #[arg(short, long, value_name = "filename")]
+    output: Option<OsString>,
+
+    /// Read one or more files in normalized PICA+ format. With no
+    /// files, or when a filename is '-', read from stanard input
+    /// (stdin).
+    #[arg(default_value = "-", hide_default_value = true)]
+    filenames: Vec<OsString>,
+}
+
+impl Validate {
+    pub(crate) fn run(self, config: &Config) -> CliResult<()> {
+        let skip_invalid = skip_invalid_flag!(
+            self.skip_invalid,
+            config.validate,
+            config.global
+        );
+
+        let validator =
+            Validator::new(Schema::from_path(&self.schema)?)?;
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(if self.tsv { b'\t' } else { b',' })
+            .from_writer(config.writer(self.output)?);
+
+        writer.write_record(["idn", "field", "subfield", "error"])?;
+
+        for filename in self.filenames.iter() {
+            let mut reader =
+                ReaderBuilder::new().from_path(filename)?;
+
+            while let Some(result) = reader.next() {
+                match result {
+                    Err(e) => {
+                        if e.is_invalid_record() && skip_invalid {
+                            continue;
+                        } else {
+                            return Err(e.into());
+                        }
+                    }
+                    Ok(record) => {
+                        let idn = record
+                            .idn()
+                            .map(|idn| idn.to_string())
+                            .unwrap_or_default();
+
+                        for violation in validator.validate(&record) {
+                            writer.write_record([
+                                idn.clone(),
+                                violation.field.clone(),
+                                violation
+                                    .code
+                                    .map(String::from)
+                                    .unwrap_or_default(),
+                                violation.to_string(),
+                            ])?;
+                        }
+                    }
+                }
+            }
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+}
@@ -1,8 +1,10 @@
+use std::collections::BTreeMap;
 use std::fs::{create_dir_all, read_to_string, File};
 use std::io::{self, BufWriter, Write};
 use std::path::{Path, PathBuf};
 
 use directories::ProjectDirs;
+use pica_matcher::ParseContext;
 use serde::{Deserialize, Serialize};
 
 use crate::commands::*;
@@ -31,18 +33,22 @@ pub(crate) struct Config {
     #[serde(skip)]
     pub(crate) path: Option<PathBuf>,
     pub(crate) global: Option<GlobalConfig>,
+    pub(crate) filters: Option<BTreeMap<String, String>>,
     pub(crate) cat: Option<CatConfig>,
     pub(crate) convert: Option<ConvertConfig>,
     pub(crate) count: Option<CountConfig>,
+    pub(crate) describe: Option<DescribeConfig>,
     pub(crate) filter: Option<FilterConfig>,
     pub(crate) frequency: Option<FrequencyConfig>,
     pub(crate) json: Option<JsonConfig>,
     pub(crate) partition: Option<PartitionConfig>,
     pub(crate) print: Option<PrintConfig>,
     pub(crate) sample: Option<SampleConfig>,
+    pub(crate) schema: Option<SchemaConfig>,
     pub(crate) select: Option<SelectConfig>,
     pub(crate) slice: Option<SliceConfig>,
     pub(crate) split: Option<SplitConfig>,
+    pub(crate) validate: Option<ValidateConfig>,
     pub(crate) xml: Option<XmlConfig>,
 }
 
@@ -91,6 +97,13 @@ impl Config {
         }
     }
 
+    /// Returns the context of filter expressions, which provides the
+    /// named filters of the `[filters]` table.
+    pub(crate) fn parse_context(&self) -> ParseContext {
+        ParseContext::new()
+            .definitions(self.filters.clone().unwrap_or_default())
+    }
+
     pub(crate) fn writer<P: AsRef<Path>>(
         &self,
         path: Option<P>,
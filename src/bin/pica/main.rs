@@ -9,6 +9,7 @@ mod commands;
 mod common;
 mod config;
 mod macros;
+mod sketch;
 mod translit;
 mod util;
 
@@ -17,8 +18,9 @@ use std::{io, process};
 
 use clap::{CommandFactory, Parser, Subcommand};
 use commands::{
-    Cat, Completions, Convert, Count, Filter, Frequency, Invalid, Json,
-    Partition, Print, Sample, Select, Slice, Split, Xml,
+    Cat, Completions, Convert, Count, Describe, Filter, FmtFilter,
+    Frequency, Invalid, Json, Partition, Print, Sample, Schema, Select,
+    Slice, Split, Validate, Xml,
 };
 use config::Config;
 use util::{CliError, CliResult};
@@ -45,7 +47,9 @@ enum Commands {
     Completions(Completions),
     Convert(Convert),
     Count(Count),
+    Describe(Describe),
     Filter(Filter),
+    FmtFilter(FmtFilter),
     Frequency(Frequency),
     Invalid(Invalid),
 
@@ -58,11 +62,13 @@ enum Commands {
 
     /// Selects a random permutation of records
     Sample(Sample),
+    Schema(Schema),
 
     /// Select subfield values from records
     Select(Select),
     Slice(Slice),
     Split(Split),
+    Validate(Validate),
 
     /// Serialize records to PICA XML
     Xml(Xml),
@@ -77,16 +83,20 @@ fn run() -> CliResult<()> {
         Commands::Completions(cmd) => cmd.run(&mut Cli::command()),
         Commands::Convert(cmd) => cmd.run(&config),
         Commands::Count(cmd) => cmd.run(&config),
+        Commands::Describe(cmd) => cmd.run(&config),
         Commands::Filter(cmd) => cmd.run(&config),
+        Commands::FmtFilter(cmd) => cmd.run(&config),
         Commands::Frequency(cmd) => cmd.run(&config),
         Commands::Invalid(cmd) => cmd.run(&config),
         Commands::Json(cmd) => cmd.run(&config),
         Commands::Partition(cmd) => cmd.run(&config),
         Commands::Print(cmd) => cmd.run(&config),
         Commands::Sample(cmd) => cmd.run(&config),
+        Commands::Schema(cmd) => cmd.run(&config),
         Commands::Select(cmd) => cmd.run(&config),
         Commands::Slice(cmd) => cmd.run(&config),
         Commands::Split(cmd) => cmd.run(&config),
+        Commands::Validate(cmd) => cmd.run(&config),
         Commands::Xml(cmd) => cmd.run(&config),
     }
 }
@@ -116,6 +126,10 @@ fn main() {
             eprintln!("Parse Matcher Error: {err}");
             process::exit(1);
         }
+        Err(CliError::Schema(err)) => {
+            eprintln!("Schema Error: {err}");
+            process::exit(1);
+        }
         Err(CliError::Pica(err)) => {
             eprintln!("Pica Error: {err}");
             process::exit(1);
@@ -0,0 +1,211 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap};
+use std::hash::{Hash, Hasher};
+
+#[inline]
+fn hash_with_seed<T: Hash + ?Sized>(value: &T, seed: u64) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    seed.hash(&mut hasher);
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A Count-Min sketch, which estimates the frequency of a value in
+/// sub-linear space. The estimate never underestimates the true
+/// frequency.
+#[derive(Debug)]
+pub(crate) struct CountMinSketch {
+    width: usize,
+    table: Vec<Vec<u64>>,
+}
+
+impl CountMinSketch {
+    pub(crate) fn new(width: usize, depth: usize) -> Self {
+        assert!(width > 0 && depth > 0);
+
+        Self {
+            width,
+            table: vec![vec![0; width]; depth],
+        }
+    }
+
+    /// Increments the counter of the given value and returns the new
+    /// estimated frequency.
+    pub(crate) fn increment<T: Hash + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> u64 {
+        let mut estimate = u64::MAX;
+
+        for (seed, row) in self.table.iter_mut().enumerate() {
+            let index = (hash_with_seed(value, seed as u64)
+                % self.width as u64) as usize;
+            row[index] += 1;
+            estimate = estimate.min(row[index]);
+        }
+
+        estimate
+    }
+}
+
+/// A HyperLogLog counter, which estimates the number of distinct
+/// values in constant space.
+#[derive(Debug)]
+pub(crate) struct HyperLogLog {
+    precision: u32,
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    pub(crate) fn new(precision: u32) -> Self {
+        assert!((4..=18).contains(&precision));
+
+        Self {
+            precision,
+            registers: vec![0; 1 << precision],
+        }
+    }
+
+    pub(crate) fn insert<T: Hash + ?Sized>(&mut self, value: &T) {
+        let hash = hash_with_seed(value, u64::MAX);
+        let index = (hash >> (64 - self.precision)) as usize;
+        let rank = ((hash << self.precision)
+            | (1 << (self.precision - 1)))
+            .leading_zeros()
+            + 1;
+
+        self.registers[index] = self.registers[index].max(rank as u8);
+    }
+
+    pub(crate) fn count(&self) -> u64 {
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|r| 2f64.powi(-(*r as i32)))
+            .sum();
+
+        let estimate = alpha * m * m / sum;
+        let zeros = self.registers.iter().filter(|r| **r == 0).count();
+
+        if estimate <= 2.5 * m && zeros > 0 {
+            (m * (m / zeros as f64).ln()).round() as u64
+        } else {
+            estimate.round() as u64
+        }
+    }
+}
+
+/// Tracks the (approximately) most frequent values of a stream in
+/// bounded memory. The frequencies are estimated by a Count-Min sketch
+/// and only the `capacity` values with the highest estimates are kept.
+#[derive(Debug)]
+pub(crate) struct TopK<T: Hash + Ord + Clone> {
+    capacity: usize,
+    sketch: CountMinSketch,
+    counts: HashMap<T, u64>,
+    order: BTreeSet<(u64, T)>,
+}
+
+impl<T: Hash + Ord + Clone> TopK<T> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        assert!(capacity > 0);
+
+        Self {
+            capacity,
+            sketch: CountMinSketch::new((capacity * 8).max(1024), 4),
+            counts: HashMap::new(),
+            order: BTreeSet::new(),
+        }
+    }
+
+    pub(crate) fn insert(&mut self, value: T) {
+        let estimate = self.sketch.increment(&value);
+
+        if let Some(count) = self.counts.get_mut(&value) {
+            self.order.remove(&(*count, value.clone()));
+            *count = estimate;
+            self.order.insert((estimate, value));
+            return;
+        }
+
+        if self.counts.len() >= self.capacity {
+            match self.order.first() {
+                Some((min, _)) if *min < estimate => {
+                    let (_, evicted) = self.order.pop_first().unwrap();
+                    self.counts.remove(&evicted);
+                }
+                _ => return,
+            }
+        }
+
+        self.counts.insert(value.clone(), estimate);
+        self.order.insert((estimate, value));
+    }
+
+    /// Returns the tracked values together with their estimated
+    /// frequencies.
+    pub(crate) fn into_counts(self) -> HashMap<T, u64> {
+        self.counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_min_sketch() {
+        let mut sketch = CountMinSketch::new(1024, 4);
+        for i in 1..100u32 {
+            for _ in 0..i - 1 {
+                sketch.increment(&i);
+            }
+        }
+
+        for i in 1..100u32 {
+            assert!(sketch.increment(&i) >= i as u64);
+        }
+        assert_eq!(sketch.increment(&99u32), 100);
+    }
+
+    #[test]
+    fn test_hyper_log_log() {
+        let mut hll = HyperLogLog::new(14);
+        assert_eq!(hll.count(), 0);
+
+        for i in 0..100_000u32 {
+            hll.insert(&i);
+            hll.insert(&i);
+        }
+
+        let count = hll.count() as f64;
+        assert!((count - 100_000.0).abs() / 100_000.0 < 0.02);
+    }
+
+    #[test]
+    fn test_top_k() {
+        let mut topk = TopK::new(3);
+        for i in 0..1000u32 {
+            topk.insert(i % 50);
+            if i % 2 == 0 {
+                topk.insert(1000);
+            }
+            if i % 4 == 0 {
+                topk.insert(2000);
+            }
+        }
+
+        let counts = topk.into_counts();
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts[&1000], 500);
+        assert_eq!(counts[&2000], 250);
+    }
+}
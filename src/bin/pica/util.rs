@@ -12,6 +12,7 @@ pub(crate) enum CliError {
     ParsePica(pica_record::ParsePicaError),
     ParsePath(pica_path::ParsePathError),
     ParseMatcher(pica_matcher::ParseMatcherError),
+    Schema(pica_schema::SchemaError),
     Other(String),
 }
 
@@ -25,6 +26,7 @@ impl fmt::Display for CliError {
             CliError::ParsePica(ref e) => e.fmt(f),
             CliError::ParsePath(ref e) => e.fmt(f),
             CliError::ParseMatcher(ref e) => e.fmt(f),
+            CliError::Schema(ref e) => e.fmt(f),
             CliError::Other(ref s) => f.write_str(s),
         }
     }
@@ -76,3 +78,9 @@ impl From<pica_matcher::ParseMatcherError> for CliError {
         CliError::ParseMatcher(err)
     }
 }
+
+impl From<pica_schema::SchemaError> for CliError {
+    fn from(err: pica_schema::SchemaError) -> Self {
+        CliError::Schema(err)
+    }
+}
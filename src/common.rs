@@ -8,13 +8,14 @@ use nom::error::ParseError;
 use nom::multi::fold_many0;
 use nom::sequence::{delimited, preceded};
 use nom::IResult;
+use pica_matcher::parser::ParserError;
 
 /// Parser result.
 pub(crate) type ParseResult<'a, O> =
-    Result<(&'a [u8], O), nom::Err<()>>;
+    Result<(&'a [u8], O), nom::Err<ParserError<'a>>>;
 
 /// Strip whitespaces from the beginning and end.
-pub(crate) fn ws<'a, F: 'a, O, E: ParseError<&'a [u8]>>(
+pub(crate) fn ws<'a, F, O, E: ParseError<&'a [u8]>>(
     inner: F,
 ) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], O, E>
 where
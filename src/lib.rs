@@ -10,7 +10,7 @@ pub use self::occurrence::Occurrence;
 pub use self::parser::{ParsePathError, ParsePicaError};
 pub use self::path::Path;
 pub use self::reader::{Reader, ReaderBuilder};
-pub use self::record::{ByteRecord, StringRecord};
+pub use self::record::{ByteRecord, RecordView, StringRecord};
 pub use self::select::{Outcome, Selector, Selectors};
 pub use self::subfield::Subfield;
 pub use self::tag::{Level, Tag};
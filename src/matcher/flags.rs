@@ -1,6 +1,13 @@
-#[derive(Debug)]
+use std::borrow::Cow;
+
+use pica_matcher::{MatcherOptions, NormalizationForm};
+
+#[derive(Debug, Clone)]
 pub struct MatcherFlags {
     pub ignore_case: bool,
+    pub case_fold: bool,
+    pub ignore_diacritics: bool,
+    pub normalization: Option<NormalizationForm>,
     pub strsim_threshold: f64,
 }
 
@@ -8,6 +15,9 @@ impl Default for MatcherFlags {
     fn default() -> Self {
         Self {
             ignore_case: false,
+            case_fold: false,
+            ignore_diacritics: false,
+            normalization: None,
             strsim_threshold: 0.8,
         }
     }
@@ -24,9 +34,69 @@ impl MatcherFlags {
         self
     }
 
+    #[must_use]
+    pub fn case_fold(mut self, yes: bool) -> Self {
+        self.case_fold = yes;
+        self
+    }
+
+    #[must_use]
+    pub fn ignore_diacritics(mut self, yes: bool) -> Self {
+        self.ignore_diacritics = yes;
+        self
+    }
+
+    #[must_use]
+    pub fn normalization(
+        mut self,
+        normalization: Option<NormalizationForm>,
+    ) -> Self {
+        self.normalization = normalization;
+        self
+    }
+
     #[must_use]
     pub fn strsim_threshold(mut self, threshold: f64) -> Self {
         self.strsim_threshold = threshold;
         self
     }
+
+    /// Returns the corresponding options of the `pica-matcher` crate,
+    /// which implement the normalization of values.
+    fn options(&self) -> MatcherOptions {
+        MatcherOptions::new()
+            .case_ignore(self.ignore_case)
+            .case_fold(self.case_fold)
+            .diacritic_ignore(self.ignore_diacritics)
+            .normalization(self.normalization)
+    }
+
+    /// Transforms a value into the form in which it is compared to
+    /// other values.
+    pub(crate) fn normalize<'a>(
+        &self,
+        value: &'a [u8],
+    ) -> Cow<'a, [u8]> {
+        self.options().normalize(value)
+    }
+
+    /// Applies the Unicode normalization and the removal of
+    /// diacritics to a value, without changing its case.
+    pub(crate) fn normalize_unicode<'a>(
+        &self,
+        value: &'a [u8],
+    ) -> Cow<'a, [u8]> {
+        self.options().normalize_unicode(value)
+    }
+
+    /// Returns `true` if regular expressions should be matched case
+    /// insensitive.
+    pub(crate) fn is_case_insensitive(&self) -> bool {
+        self.ignore_case || self.case_fold
+    }
+
+    /// Returns `true` if one of the Unicode options is set.
+    pub(crate) fn is_unicode_aware(&self) -> bool {
+        self.options().is_unicode_aware()
+    }
 }
@@ -1,13 +1,16 @@
+use std::collections::BTreeMap;
 use std::fmt;
 use std::ops::{BitAnd, BitOr, Not};
 
 use nom::branch::alt;
-use nom::bytes::complete::tag;
+use nom::bytes::complete::{tag, take_while1};
 use nom::character::complete::{char, digit1};
 use nom::combinator::{all_consuming, cut, map, map_res, opt};
 use nom::multi::many1;
 use nom::sequence::{preceded, terminated, tuple};
 use nom::Finish;
+use pica_matcher::parser::ParserError;
+use pica_matcher::SyntaxError;
 
 use super::subfield_matcher::parse_subfield_matcher_exists;
 use crate::common::{ws, ParseResult};
@@ -72,14 +75,55 @@ impl RecordMatcher {
     /// }
     /// ```
     pub fn new<S: AsRef<str>>(data: S) -> Result<Self, Error> {
+        Self::with_definitions(data, &BTreeMap::new())
+    }
+
+    /// Creates a record matcher from a string slice, which may
+    /// reference the given named filters (`%name`).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::collections::BTreeMap;
+    ///
+    /// use pica::matcher::RecordMatcher;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let definitions = BTreeMap::from([(
+    ///         "persons".to_string(),
+    ///         "002@.0 =^ 'Tp'".to_string(),
+    ///     )]);
+    ///
+    ///     let matcher = RecordMatcher::with_definitions(
+    ///         "%persons && 028A?",
+    ///         &definitions,
+    ///     )?;
+    ///     assert_eq!(matcher.to_string(), "(002@.0 =^ 'Tp') && 028A?");
+    ///     assert!(RecordMatcher::new("%persons && 028A?").is_err());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_definitions<S: AsRef<str>>(
+        data: S,
+        definitions: &BTreeMap<String, String>,
+    ) -> Result<Self, Error> {
         let data = data.as_ref();
+        let defs = Definitions {
+            library: definitions,
+            stack: vec![],
+        };
+
+        let result = all_consuming(|i| parse_record_matcher(i, &defs))(
+            data.as_bytes(),
+        )
+        .finish();
 
-        match all_consuming(parse_record_matcher)(data.as_bytes())
-            .finish()
-        {
+        match result {
             Ok((_, matcher)) => Ok(matcher),
-            Err(_) => Err(Error::InvalidMatcher(format!(
-                "Expected valid record matcher, got '{data}'"
+            Err(e) => Err(Error::InvalidMatcher(format!(
+                "invalid record matcher: {}",
+                SyntaxError::new(data, e)
             ))),
         }
     }
@@ -232,17 +276,21 @@ fn parse_record_matcher_exists(i: &[u8]) -> ParseResult<RecordMatcher> {
     )(i)
 }
 
-fn parse_record_matcher_group(i: &[u8]) -> ParseResult<RecordMatcher> {
+fn parse_record_matcher_group<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, RecordMatcher> {
     map(
         preceded(
             ws(char('(')),
             cut(terminated(
                 alt((
-                    parse_record_matcher_composite,
+                    |i| parse_record_matcher_composite(i, defs),
                     parse_record_matcher_singleton,
-                    parse_record_matcher_not,
+                    |i| parse_record_matcher_not(i, defs),
                     parse_record_matcher_cardinality,
-                    parse_record_matcher_group,
+                    |i| parse_record_matcher_named(i, defs),
+                    |i| parse_record_matcher_group(i, defs),
                 )),
                 ws(char(')')),
             )),
@@ -251,42 +299,100 @@ fn parse_record_matcher_group(i: &[u8]) -> ParseResult<RecordMatcher> {
     )(i)
 }
 
-fn parse_record_matcher_not(i: &[u8]) -> ParseResult<RecordMatcher> {
+/// The library of named filters, which can be referenced as `%name`.
+struct Definitions<'a> {
+    library: &'a BTreeMap<String, String>,
+    /// The filters, which are currently parsed, in order to detect
+    /// cyclic definitions.
+    stack: Vec<&'a str>,
+}
+
+/// Parses a reference to a named filter (`%name`).
+fn parse_record_matcher_named<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, RecordMatcher> {
+    let (rest, name) = preceded(
+        char('%'),
+        map_res(
+            take_while1(|c: u8| c.is_ascii_alphanumeric() || c == b'_'),
+            std::str::from_utf8,
+        ),
+    )(i)?;
+
+    let invalid = |reason: String| {
+        Err(nom::Err::Failure(ParserError::invalid(i, reason)))
+    };
+
+    let Some(expr) = defs.library.get(name) else {
+        return invalid(format!("`%{name}` is not defined"));
+    };
+
+    if defs.stack.contains(&name) {
+        return invalid(format!(
+            "`%{name}` is defined in terms of itself"
+        ));
+    }
+
+    let mut stack = defs.stack.clone();
+    stack.push(name);
+
+    let inner = Definitions {
+        library: defs.library,
+        stack,
+    };
+
+    let result = all_consuming(|i| parse_record_matcher(i, &inner))(
+        expr.as_bytes(),
+    )
+    .finish();
+
+    match result {
+        Ok((_, matcher)) => {
+            Ok((rest, RecordMatcher::Group(Box::new(matcher))))
+        }
+        Err(e) => invalid(format!(
+            "invalid filter `%{name}`: {}",
+            SyntaxError::new(expr, e).message()
+        )),
+    }
+}
+
+fn parse_record_matcher_not<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, RecordMatcher> {
     map(
         preceded(
             ws(char('!')),
             cut(alt((
-                parse_record_matcher_group,
+                |i| parse_record_matcher_group(i, defs),
                 parse_record_matcher_exists,
-                parse_record_matcher_not,
+                |i| parse_record_matcher_named(i, defs),
+                |i| parse_record_matcher_not(i, defs),
             ))),
         ),
         |matcher| RecordMatcher::Not(Box::new(matcher)),
     )(i)
 }
 
-fn parse_record_matcher_composite_and(
-    i: &[u8],
-) -> ParseResult<RecordMatcher> {
-    let (i, (first, remainder)) = tuple((
+fn parse_record_matcher_composite_and<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, RecordMatcher> {
+    let operand = |i| {
         alt((
-            ws(parse_record_matcher_group),
+            ws(|i| parse_record_matcher_group(i, defs)),
             ws(parse_record_matcher_cardinality),
             ws(parse_record_matcher_singleton),
-            ws(parse_record_matcher_not),
+            ws(|i| parse_record_matcher_named(i, defs)),
+            ws(|i| parse_record_matcher_not(i, defs)),
             ws(parse_record_matcher_exists),
-        )),
-        many1(preceded(
-            ws(tag("&&")),
-            alt((
-                ws(parse_record_matcher_group),
-                ws(parse_record_matcher_cardinality),
-                ws(parse_record_matcher_singleton),
-                ws(parse_record_matcher_not),
-                ws(parse_record_matcher_exists),
-            )),
-        )),
-    ))(i)?;
+        ))(i)
+    };
+
+    let (i, (first, remainder)) =
+        tuple((operand, many1(preceded(ws(tag("&&")), operand))))(i)?;
 
     Ok((
         i,
@@ -294,27 +400,24 @@ fn parse_record_matcher_composite_and(
     ))
 }
 
-fn parse_record_matcher_composite_or(
-    i: &[u8],
-) -> ParseResult<RecordMatcher> {
-    let (i, (first, remainder)) = tuple((
+fn parse_record_matcher_composite_or<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, RecordMatcher> {
+    let operand = |i| {
         alt((
-            ws(parse_record_matcher_group),
-            ws(parse_record_matcher_composite_and),
+            ws(|i| parse_record_matcher_group(i, defs)),
+            ws(|i| parse_record_matcher_composite_and(i, defs)),
             ws(parse_record_matcher_cardinality),
             ws(parse_record_matcher_singleton),
-            ws(parse_record_matcher_not),
-        )),
-        many1(preceded(
-            ws(tag("||")),
-            cut(alt((
-                ws(parse_record_matcher_group),
-                ws(parse_record_matcher_composite_and),
-                ws(parse_record_matcher_cardinality),
-                ws(parse_record_matcher_singleton),
-                ws(parse_record_matcher_not),
-            ))),
-        )),
+            ws(|i| parse_record_matcher_named(i, defs)),
+            ws(|i| parse_record_matcher_not(i, defs)),
+        ))(i)
+    };
+
+    let (i, (first, remainder)) = tuple((
+        operand,
+        many1(preceded(ws(tag("||")), cut(operand))),
     ))(i)?;
 
     Ok((
@@ -323,12 +426,13 @@ fn parse_record_matcher_composite_or(
     ))
 }
 
-fn parse_record_matcher_composite(
-    i: &[u8],
-) -> ParseResult<RecordMatcher> {
+fn parse_record_matcher_composite<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, RecordMatcher> {
     alt((
-        parse_record_matcher_composite_or,
-        parse_record_matcher_composite_and,
+        |i| parse_record_matcher_composite_or(i, defs),
+        |i| parse_record_matcher_composite_and(i, defs),
     ))(i)
 }
 
@@ -360,13 +464,15 @@ fn parse_record_matcher_cardinality(
     )(i)
 }
 
-pub(crate) fn parse_record_matcher(
-    i: &[u8],
-) -> ParseResult<RecordMatcher> {
+fn parse_record_matcher<'a>(
+    i: &'a [u8],
+    defs: &Definitions,
+) -> ParseResult<'a, RecordMatcher> {
     alt((
-        ws(parse_record_matcher_composite),
-        ws(parse_record_matcher_group),
-        ws(parse_record_matcher_not),
+        ws(|i| parse_record_matcher_composite(i, defs)),
+        ws(|i| parse_record_matcher_group(i, defs)),
+        ws(|i| parse_record_matcher_not(i, defs)),
+        ws(|i| parse_record_matcher_named(i, defs)),
         ws(parse_record_matcher_singleton),
         ws(parse_record_matcher_cardinality),
     ))(i)
@@ -1,15 +1,16 @@
+use std::collections::{BTreeSet, HashSet};
 use std::fmt;
 
 use bstr::{BString, ByteSlice};
 use nom::branch::alt;
-use nom::bytes::complete::tag;
+use nom::bytes::complete::{is_not, tag};
 use nom::character::complete::char;
-use nom::combinator::{all_consuming, cut, map, opt, value, verify};
+use nom::combinator::{all_consuming, cut, map, map_res, opt, value};
 use nom::multi::{many1, separated_list1};
 use nom::sequence::{preceded, terminated, tuple};
 use nom::Finish;
-use regex::bytes::RegexBuilder;
-use regex::Regex;
+use pica_matcher::read_list;
+use regex::bytes::{Regex, RegexBuilder};
 use strsim::normalized_levenshtein;
 
 use crate::common::{parse_string, ws, ParseResult};
@@ -19,25 +20,49 @@ use crate::matcher::{
 use crate::subfield::parse_subfield_code;
 use crate::{Error, Subfield};
 
-macro_rules! maybe_lowercase {
-    ($value:expr, $flag:expr) => {
-        if $flag {
-            $value.to_lowercase()
-        } else {
-            $value
-        }
-    };
-}
-
 /// A subfield matcher.
 #[derive(Debug, PartialEq, Eq)]
 pub enum SubfieldMatcher {
     Comparison(Vec<char>, ComparisonOp, BString),
     Exists(Vec<char>),
-    In(Vec<char>, Vec<BString>, bool),
-    Regex(Vec<char>, String, bool),
+    /// Checks if a value is in the list. The second set contains the
+    /// lowercased values, which are used for case-insensitive lookups.
+    In(Vec<char>, BTreeSet<BString>, HashSet<BString>, bool),
+    Regex(Vec<char>, CompiledRegex, bool),
+}
+
+/// A regular expression, which is compiled once for case-sensitive and
+/// once for case-insensitive matching.
+#[derive(Debug)]
+pub struct CompiledRegex {
+    sensitive: Regex,
+    insensitive: Regex,
+}
+
+impl CompiledRegex {
+    fn new(pattern: &str) -> Result<Self, regex::Error> {
+        Ok(Self {
+            sensitive: Regex::new(pattern)?,
+            insensitive: RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()?,
+        })
+    }
+
+    /// Returns the pattern of the regular expression.
+    pub fn as_str(&self) -> &str {
+        self.sensitive.as_str()
+    }
 }
 
+impl PartialEq for CompiledRegex {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for CompiledRegex {}
+
 fn fmt_codes(codes: &Vec<char>) -> String {
     let result = String::from_iter(codes);
     if result.len() > 1 {
@@ -54,7 +79,7 @@ impl fmt::Display for SubfieldMatcher {
                 write!(f, "{} {op} '{value}'", fmt_codes(codes))
             }
             Self::Exists(codes) => write!(f, "{}?", fmt_codes(codes)),
-            Self::In(codes, values, invert) => {
+            Self::In(codes, values, _, invert) => {
                 let values: String = values
                     .iter()
                     .map(|s| format!("'{s}'"))
@@ -68,6 +93,7 @@ impl fmt::Display for SubfieldMatcher {
                 }
             }
             Self::Regex(codes, regex, invert) => {
+                let regex = regex.as_str();
                 if *invert {
                     write!(f, "{} !~ '{regex}'", fmt_codes(codes))
                 } else {
@@ -132,11 +158,7 @@ impl SubfieldMatcher {
         flags: &MatcherFlags,
     ) -> bool {
         let case_cmp = |lhs: &BString, rhs: &BString| -> bool {
-            if flags.ignore_case {
-                lhs.to_lowercase() == rhs.to_lowercase()
-            } else {
-                lhs == rhs
-            }
+            flags.normalize(lhs) == flags.normalize(rhs)
         };
 
         match self {
@@ -154,34 +176,20 @@ impl SubfieldMatcher {
                 value,
             ) => {
                 codes.contains(&subfield.code())
-                    && if flags.ignore_case {
-                        subfield
-                            .value()
-                            .to_lowercase()
-                            .starts_with(&value.to_lowercase())
-                    } else {
-                        subfield.value().starts_with(value)
-                    }
+                    && flags
+                        .normalize(subfield.value())
+                        .starts_with(&flags.normalize(value))
             }
             Self::Comparison(codes, ComparisonOp::EndsWith, value) => {
                 codes.contains(&subfield.code())
-                    && if flags.ignore_case {
-                        subfield
-                            .value()
-                            .to_lowercase()
-                            .ends_with(&value.to_lowercase())
-                    } else {
-                        subfield.value().ends_with(value)
-                    }
+                    && flags
+                        .normalize(subfield.value())
+                        .ends_with(&flags.normalize(value))
             }
             Self::Comparison(codes, ComparisonOp::Similar, value) => {
                 if codes.contains(&subfield.code()) {
-                    let flag = flags.ignore_case;
-                    let lhs = maybe_lowercase!(
-                        subfield.value().to_vec(),
-                        flag
-                    );
-                    let rhs = maybe_lowercase!(value.to_vec(), flag);
+                    let lhs = flags.normalize(subfield.value());
+                    let rhs = flags.normalize(value);
 
                     let score = normalized_levenshtein(
                         &lhs.to_str_lossy(),
@@ -196,12 +204,15 @@ impl SubfieldMatcher {
             Self::Comparison(_, _, _) => unreachable!(),
             Self::Regex(codes, regex, invert) => {
                 if codes.contains(&subfield.code()) {
-                    let re = RegexBuilder::new(regex)
-                        .case_insensitive(flags.ignore_case)
-                        .build()
-                        .unwrap();
+                    let re = if flags.is_case_insensitive() {
+                        &regex.insensitive
+                    } else {
+                        &regex.sensitive
+                    };
 
-                    let mut result = re.is_match(subfield.value());
+                    let mut result = re.is_match(
+                        &flags.normalize_unicode(subfield.value()),
+                    );
                     if *invert {
                         result = !result;
                     }
@@ -211,14 +222,21 @@ impl SubfieldMatcher {
                     false
                 }
             }
-            Self::In(codes, values, invert) => {
+            Self::In(codes, values, folded, invert) => {
                 if !codes.contains(&subfield.code()) {
                     false
                 } else {
-                    let mut result =
+                    let mut result = if flags.is_unicode_aware() {
                         values.iter().any(|x: &BString| {
                             case_cmp(subfield.value(), x)
-                        });
+                        })
+                    } else if flags.ignore_case {
+                        folded.contains(
+                            subfield.value().to_lowercase().as_slice(),
+                        )
+                    } else {
+                        values.contains(subfield.value())
+                    };
 
                     if *invert {
                         result = !result;
@@ -255,7 +273,7 @@ fn parse_subfield_matcher_comparison(
         tuple((
             ws(parse_subfield_codes),
             ws(parse_comparison_op_bstring),
-            ws(parse_string),
+            cut(ws(parse_string)),
         )),
         |(codes, op, value)| {
             SubfieldMatcher::Comparison(codes, op, BString::from(value))
@@ -273,7 +291,7 @@ fn parse_subfield_matcher_regex(
                 value(false, ws(tag("=~"))),
                 value(true, ws(tag("!~"))),
             )),
-            verify(parse_string, |x| Regex::new(x).is_ok()),
+            map_res(parse_string, |x| CompiledRegex::new(&x)),
         )),
         |(codes, invert, regex)| {
             SubfieldMatcher::Regex(codes, regex, invert)
@@ -281,25 +299,53 @@ fn parse_subfield_matcher_regex(
     )(i)
 }
 
+/// Parse the path of a list file (`@codes.txt` or `@'my codes.txt'`)
+/// and read the list.
+fn parse_list_file(i: &[u8]) -> ParseResult<BTreeSet<BString>> {
+    map_res(
+        preceded(
+            char('@'),
+            alt((
+                parse_string,
+                map_res(is_not(" \t\r\n()[]{}&|!,"), |s: &[u8]| {
+                    std::str::from_utf8(s).map(String::from)
+                }),
+            )),
+        ),
+        |path| read_list(path).map(BTreeSet::from_iter),
+    )(i)
+}
+
 fn parse_subfield_matcher_in(i: &[u8]) -> ParseResult<SubfieldMatcher> {
     map(
         tuple((
             parse_subfield_codes,
             opt(ws(tag("not"))),
             ws(tag("in")),
-            preceded(
-                ws(char('[')),
-                cut(terminated(
-                    separated_list1(
-                        ws(char(',')),
-                        map(parse_string, BString::from),
-                    ),
-                    ws(char(']')),
-                )),
-            ),
+            alt((
+                preceded(
+                    ws(char('[')),
+                    cut(terminated(
+                        map(
+                            separated_list1(
+                                ws(char(',')),
+                                map(parse_string, BString::from),
+                            ),
+                            BTreeSet::from_iter,
+                        ),
+                        ws(char(']')),
+                    )),
+                ),
+                ws(parse_list_file),
+            )),
         )),
         |(codes, invert, _, values)| {
-            SubfieldMatcher::In(codes, values, invert.is_some())
+            let folded = values
+                .iter()
+                .map(|value| value.to_lowercase().into())
+                .collect();
+
+            SubfieldMatcher::In(codes, values, folded, invert.is_some())
         },
     )(i)
 }
@@ -537,6 +583,12 @@ mod tests {
         let flags = MatcherFlags::new().ignore_case(true);
         assert!(matcher.is_match(&Subfield::new('0', "A")?, &flags));
 
+        let matcher = SubfieldMatcher::new("0 not in ['Ab', 'cD']")?;
+        let flags = MatcherFlags::new().ignore_case(true);
+        assert!(!matcher.is_match(&Subfield::new('0', "aB")?, &flags));
+        assert!(!matcher.is_match(&Subfield::new('0', "CD")?, &flags));
+        assert!(matcher.is_match(&Subfield::new('0', "EF")?, &flags));
+
         Ok(())
     }
 
@@ -5,6 +5,7 @@ use std::ops::Deref;
 use std::result::Result as StdResult;
 
 use bstr::BString;
+use pica_matcher::Scope;
 use serde::ser::{Serialize, SerializeStruct, Serializer};
 
 use crate::error::Result;
@@ -272,79 +273,13 @@ impl ByteRecord {
             .collect()
     }
 
+    /// Returns the values of the selector.
     pub fn select(
         &self,
         selector: &Selector,
-        ignore_case: bool,
+        flags: &MatcherFlags,
     ) -> Outcome {
-        match selector {
-            Selector::Value(value) => {
-                Outcome::from_values(vec![BString::from(
-                    value.as_bytes(),
-                )])
-            }
-            Selector::Field(selector) => {
-                let result = self
-                    .iter()
-                    .filter(|field| selector.tag.is_match(field.tag()))
-                    .filter(|field| {
-                        selector.occurrence.is_match(field.occurrence())
-                    })
-                    .filter(|field| {
-                        if let Some(filter) = &selector.filter {
-                            filter.is_match(
-                                field,
-                                &MatcherFlags {
-                                    ignore_case,
-                                    strsim_threshold: 0.0,
-                                },
-                            )
-                        } else {
-                            true
-                        }
-                    })
-                    .map(|field| &field.subfields)
-                    .map(|subfields| {
-                        selector
-                            .subfields
-                            .iter()
-                            .map(|code| {
-                                subfields
-                                    .iter()
-                                    .filter(|subfield| {
-                                        subfield.code == *code
-                                    })
-                                    .map(|subfield| {
-                                        vec![subfield
-                                            .value()
-                                            .to_owned()]
-                                    })
-                                    .collect::<Vec<Vec<BString>>>()
-                            })
-                            .map(|x| {
-                                if x.is_empty() {
-                                    Outcome::one()
-                                } else {
-                                    Outcome(x)
-                                }
-                            })
-                            .fold(Outcome::default(), |acc, x| acc * x)
-                    })
-                    .fold(Outcome::default(), |acc, x| acc + x);
-
-                if result.is_empty() {
-                    let mut values: Vec<BString> =
-                        Vec::with_capacity(selector.subfields.len());
-                    for _ in 0..selector.subfields.len() {
-                        values.push(BString::from(""));
-                    }
-
-                    Outcome::from_values(values)
-                } else {
-                    result
-                }
-            }
-        }
+        select(self.fields.iter(), selector, flags)
     }
 
     /// Reduce the record to the given fields.
@@ -400,6 +335,61 @@ impl ByteRecord {
                 .collect();
         }
     }
+
+    /// Splits the record into one view per holding block of the given
+    /// scope. Each view consists of the fields of level 0 and the fields
+    /// of the block. If the record doesn't have any holdings, a view of
+    /// the whole record is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use pica::ByteRecord;
+    /// use pica_matcher::Scope;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let record = ByteRecord::from_bytes(
+    ///         "003@ \x1f0123\x1e101@ \x1fa1\x1e\
+    ///          209A/01 \x1faX\x1e209A/02 \x1faY\x1e",
+    ///     )?;
+    ///
+    ///     assert_eq!(record.split(&Scope::Local).len(), 1);
+    ///     assert_eq!(record.split(&Scope::Copy).len(), 2);
+    ///     assert_eq!(record.split(&Scope::Copy)[0].len(), 3);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn split(&self, scope: &Scope) -> Vec<RecordView<'_>> {
+        let blocks = scope.blocks(self.fields.iter(), |field| {
+            (
+                field.tag().as_slice(),
+                field
+                    .occurrence()
+                    .map(|occurrence| occurrence.as_slice()),
+            )
+        });
+
+        if blocks.is_empty() {
+            return vec![RecordView::from(self)];
+        }
+
+        let fields: Vec<&Field> = self
+            .fields
+            .iter()
+            .filter(|field| field.tag().starts_with(b"0"))
+            .collect();
+
+        blocks
+            .into_iter()
+            .map(|block| {
+                RecordView(
+                    fields.iter().copied().chain(block).collect(),
+                )
+            })
+            .collect()
+    }
 }
 
 impl fmt::Display for ByteRecord {
@@ -447,6 +437,38 @@ impl Deref for ByteRecord {
     }
 }
 
+/// A borrowed view of some fields of a record (see
+/// [ByteRecord::split]).
+#[derive(Debug, PartialEq, Eq)]
+pub struct RecordView<'a>(Vec<&'a Field>);
+
+impl<'a> RecordView<'a> {
+    /// Returns the values of the selector.
+    pub fn select(
+        &self,
+        selector: &Selector,
+        flags: &MatcherFlags,
+    ) -> Outcome {
+        select(self.0.iter().copied(), selector, flags)
+    }
+}
+
+impl<'a> From<&'a ByteRecord> for RecordView<'a> {
+    /// Creates a view of all fields of the record.
+    fn from(record: &'a ByteRecord) -> Self {
+        Self(record.fields.iter().collect())
+    }
+}
+
+impl<'a> Deref for RecordView<'a> {
+    type Target = [&'a Field];
+
+    /// Dereferences the value
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
 /// A PICA+ record, that guarantees valid UTF-8 data.
 #[derive(Debug, PartialEq, Eq)]
 pub struct StringRecord(ByteRecord);
@@ -563,6 +585,118 @@ impl Serialize for StringRecord {
     }
 }
 
+/// Returns the values of the selector for the given fields.
+fn select<'a>(
+    fields: impl Iterator<Item = &'a Field> + Clone,
+    selector: &Selector,
+    flags: &MatcherFlags,
+) -> Outcome {
+    match selector {
+        Selector::Value(value) => {
+            Outcome::from_values(vec![BString::from(value.as_bytes())])
+        }
+        Selector::Field(selector) => {
+            let fields = fields
+                .filter(|field| selector.tag.is_match(field.tag()))
+                .filter(|field| {
+                    selector.occurrence.is_match(field.occurrence())
+                })
+                .filter(|field| {
+                    if let Some(filter) = &selector.filter {
+                        filter.is_match(field, flags)
+                    } else {
+                        true
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let fields = match selector.field_index {
+                Some(index) => nth(fields, index).into_iter().collect(),
+                None => fields,
+            };
+
+            let result = fields
+                .into_iter()
+                .map(|field| &field.subfields)
+                .map(|subfields| {
+                    selector
+                        .subfields
+                        .iter()
+                        .map(|code| {
+                            let values = subfields
+                                .iter()
+                                .filter(|subfield| {
+                                    subfield.code == *code
+                                })
+                                .map(|subfield| {
+                                    vec![subfield.value().to_owned()]
+                                })
+                                .collect::<Vec<Vec<BString>>>();
+
+                            match selector.value_index {
+                                Some(index) => nth(values, index)
+                                    .into_iter()
+                                    .collect(),
+                                None => values,
+                            }
+                        })
+                        .map(|x| {
+                            if x.is_empty() {
+                                Outcome::one()
+                            } else {
+                                Outcome(x)
+                            }
+                        })
+                        .fold(Outcome::default(), |acc, x| acc * x)
+                })
+                .fold(Outcome::default(), |acc, x| acc + x);
+
+            if result.is_empty() {
+                let mut values: Vec<BString> =
+                    Vec::with_capacity(selector.subfields.len());
+                for _ in 0..selector.subfields.len() {
+                    values.push(BString::from(""));
+                }
+
+                Outcome::from_values(values)
+            } else {
+                result
+            }
+        }
+        Selector::Fallback(selectors) => {
+            let mut outcome = Outcome::default();
+
+            // The first alternative with at least one non-empty value
+            // wins; otherwise the (empty) result of the last one is
+            // returned.
+            for selector in selectors {
+                outcome = select(fields.clone(), selector, flags);
+                if outcome
+                    .iter()
+                    .flatten()
+                    .any(|value| !value.is_empty())
+                {
+                    break;
+                }
+            }
+
+            outcome
+        }
+    }
+}
+
+/// Returns the element at the given position. Negative positions count
+/// from the end (`-1` is the last element).
+fn nth<T>(items: Vec<T>, index: isize) -> Option<T> {
+    let index = if index < 0 {
+        items.len().checked_sub(index.unsigned_abs())?
+    } else {
+        index as usize
+    };
+
+    items.into_iter().nth(index)
+}
+
 #[cfg(test)]
 mod tests {
     use std::io::Cursor;
@@ -3,11 +3,15 @@ use std::ops::{Add, Deref, Mul};
 
 use bstr::BString;
 use nom::branch::alt;
-use nom::character::complete::{char, multispace0};
-use nom::combinator::{all_consuming, map, opt};
+use nom::bytes::complete::tag;
+use nom::character::complete::{char, digit1, multispace0};
+use nom::combinator::{
+    all_consuming, cut, map, map_res, opt, recognize, verify,
+};
 use nom::multi::separated_list1;
 use nom::sequence::{delimited, pair, terminated, tuple};
 use nom::Finish;
+use pica_matcher::SyntaxError;
 
 use crate::common::{parse_string, ws, ParseResult};
 use crate::matcher::{
@@ -23,12 +27,26 @@ pub struct FieldSelector {
     pub(crate) occurrence: OccurrenceMatcher,
     pub(crate) filter: Option<SubfieldListMatcher>,
     pub(crate) subfields: Vec<char>,
+    pub(crate) field_index: Option<isize>,
+    pub(crate) value_index: Option<isize>,
 }
 
 #[derive(Debug, PartialEq)]
 pub enum Selector {
     Field(Box<FieldSelector>),
     Value(String),
+    Fallback(Vec<Selector>),
+}
+
+impl Selector {
+    /// Returns the number of columns produced by the selector.
+    fn width(&self) -> usize {
+        match self {
+            Self::Field(selector) => selector.subfields.len(),
+            Self::Value(_) => 1,
+            Self::Fallback(selectors) => selectors[0].width(),
+        }
+    }
 }
 
 #[derive(Debug, Default)]
@@ -114,6 +132,8 @@ impl FieldSelector {
             occurrence,
             filter,
             subfields,
+            field_index: None,
+            value_index: None,
         }
     }
 }
@@ -121,11 +141,14 @@ impl FieldSelector {
 pub struct Selectors(Vec<Selector>);
 
 impl Selectors {
-    pub fn decode(s: &str) -> Result<Self, String> {
-        match parse_selectors(s.as_bytes()).finish() {
-            Ok((_, selectors)) => Ok(selectors),
-            _ => Err("invalid select statement".to_string()),
-        }
+    /// Decodes a comma-separated list of selectors. If the list can't
+    /// be parsed, the error points to the position of the invalid
+    /// input.
+    pub fn decode(s: &str) -> Result<Self, SyntaxError> {
+        parse_selectors(s.as_bytes())
+            .finish()
+            .map(|(_, selectors)| selectors)
+            .map_err(|e| SyntaxError::new(s, e))
     }
 }
 
@@ -137,35 +160,62 @@ impl Deref for Selectors {
     }
 }
 
-fn parse_selector(i: &[u8]) -> ParseResult<Selector> {
+/// Parse a positional index (`[1]`, `[-1]`). Negative indexes count
+/// from the end.
+fn parse_index(i: &[u8]) -> ParseResult<isize> {
+    delimited(
+        char('['),
+        map_res(recognize(pair(opt(char('-')), digit1)), |value| {
+            std::str::from_utf8(value)
+                .expect("valid utf8")
+                .parse::<isize>()
+        }),
+        char(']'),
+    )(i)
+}
+
+fn parse_selector_alternative(i: &[u8]) -> ParseResult<Selector> {
     alt((
         map(ws(parse_string), Selector::Value),
         map(
             tuple((
                 parse_tag_matcher,
                 parse_occurrence_matcher,
+                opt(parse_index),
                 pair(
                     opt(alt((char('.'), ws(char('$'))))),
                     parse_subfield_code,
                 ),
+                opt(parse_index),
             )),
-            |(tag, occurrence, (prefix, subfield))| {
+            |(
+                tag,
+                occurrence,
+                field_index,
+                (prefix, subfield),
+                value_index,
+            )| {
                 if prefix.is_none() {
                     eprintln!("Don't use lazy syntax!");
                 }
 
-                Selector::Field(Box::new(FieldSelector::new(
-                    tag,
-                    occurrence,
-                    None,
-                    vec![subfield],
-                )))
+                Selector::Field(Box::new(FieldSelector {
+                    field_index,
+                    value_index,
+                    ..FieldSelector::new(
+                        tag,
+                        occurrence,
+                        None,
+                        vec![subfield],
+                    )
+                }))
             },
         ),
         map(
             tuple((
                 parse_tag_matcher,
                 parse_occurrence_matcher,
+                opt(parse_index),
                 delimited(
                     ws(char('{')),
                     pair(
@@ -180,21 +230,54 @@ fn parse_selector(i: &[u8]) -> ParseResult<Selector> {
                     ),
                     ws(char('}')),
                 ),
+                opt(parse_index),
             )),
-            |(tag, occurrence, (filter, subfields))| {
-                Selector::Field(Box::new(FieldSelector::new(
-                    tag, occurrence, filter, subfields,
-                )))
+            |(
+                tag,
+                occurrence,
+                field_index,
+                (filter, subfields),
+                value_index,
+            )| {
+                Selector::Field(Box::new(FieldSelector {
+                    field_index,
+                    value_index,
+                    ..FieldSelector::new(
+                        tag, occurrence, filter, subfields,
+                    )
+                }))
             },
         ),
     ))(i)
 }
 
+/// Parse a selector with optional fallbacks (`028A.a ?? 028@.a`). All
+/// alternatives must select the same number of columns.
+fn parse_selector(i: &[u8]) -> ParseResult<Selector> {
+    map(
+        verify(
+            separated_list1(ws(tag("??")), parse_selector_alternative),
+            |selectors: &Vec<Selector>| {
+                selectors.iter().all(|selector| {
+                    selector.width() == selectors[0].width()
+                })
+            },
+        ),
+        |mut selectors| {
+            if selectors.len() == 1 {
+                selectors.remove(0)
+            } else {
+                Selector::Fallback(selectors)
+            }
+        },
+    )(i)
+}
+
 fn parse_selectors(i: &[u8]) -> ParseResult<Selectors> {
     all_consuming(map(
         delimited(
             multispace0,
-            separated_list1(ws(char(',')), parse_selector),
+            separated_list1(ws(char(',')), cut(parse_selector)),
             multispace0,
         ),
         Selectors,
@@ -288,4 +371,63 @@ mod tests {
 
         Ok(())
     }
+    #[test]
+    fn test_parse_selector_index() -> TestResult {
+        assert_eq!(
+            parse_selector(b"044H[1].a[-1]")?.1,
+            Selector::Field(Box::new(FieldSelector {
+                field_index: Some(1),
+                value_index: Some(-1),
+                ..FieldSelector::new(
+                    TagMatcher::Some(Tag::new("044H")?),
+                    OccurrenceMatcher::None,
+                    None,
+                    vec!['a']
+                )
+            }))
+        );
+
+        assert_eq!(
+            parse_selector(b"044H[-1]{9, E}[0]")?.1,
+            Selector::Field(Box::new(FieldSelector {
+                field_index: Some(-1),
+                value_index: Some(0),
+                ..FieldSelector::new(
+                    TagMatcher::Some(Tag::new("044H")?),
+                    OccurrenceMatcher::None,
+                    None,
+                    vec!['9', 'E']
+                )
+            }))
+        );
+
+        assert!(parse_selector(b"044H[x].a").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_selector_fallback() -> TestResult {
+        assert_eq!(
+            parse_selector(b"028A.a ?? 028@.a ?? 'n/a'")?.1,
+            Selector::Fallback(vec![
+                Selector::Field(Box::new(FieldSelector::new(
+                    TagMatcher::Some(Tag::new("028A")?),
+                    OccurrenceMatcher::None,
+                    None,
+                    vec!['a']
+                ))),
+                Selector::Field(Box::new(FieldSelector::new(
+                    TagMatcher::Some(Tag::new("028@")?),
+                    OccurrenceMatcher::None,
+                    None,
+                    vec!['a']
+                ))),
+                Selector::Value("n/a".to_string()),
+            ])
+        );
+
+        assert!(parse_selectors(b"028A{a, d} ?? 028@.a").is_err());
+        assert!(parse_selectors(b"028A.a ??").is_err());
+        Ok(())
+    }
 }
@@ -553,6 +553,74 @@ fn pica_select_where() -> TestResult {
     Ok(())
 }
 
+#[test]
+fn pica_select_where_in_file() -> TestResult {
+    let mut cmd = Command::cargo_bin("pica")?;
+    let assert = cmd
+        .arg("select")
+        .arg("--skip-invalid")
+        .arg("003@.0")
+        .arg("--where")
+        .arg("003@.0 in @tests/data/idns.txt")
+        .arg("tests/data/dump.dat.gz")
+        .assert();
+
+    assert.success().stderr(predicate::str::is_empty()).stdout(
+        r#"000008672
+000009229
+"#,
+    );
+
+    Ok(())
+}
+
+#[test]
+fn pica_select_unicode_options() -> TestResult {
+    let expected = vec![
+        (
+            vec!["--normalize", "nfc"],
+            "029A{a == 'Goethe-Universität Frankfurt am Main', a}",
+        ),
+        (
+            vec!["--ignore-diacritics"],
+            "029A{a =^ 'Goethe-Universitat', a}",
+        ),
+        (vec!["--case-fold"], "029A{a =$ 'FRANKFURT AM MAIN', a}"),
+    ];
+
+    for (args, selector) in expected {
+        let mut cmd = Command::cargo_bin("pica")?;
+        let assert = cmd
+            .arg("select")
+            .args(args)
+            .arg(selector)
+            .arg("tests/data/004732650.dat.gz")
+            .assert();
+
+        assert
+            .success()
+            .stderr(predicate::str::is_empty())
+            .stdout("Goethe-Universita\u{308}t Frankfurt am Main\n");
+    }
+
+    let mut cmd = Command::cargo_bin("pica")?;
+    let assert = cmd
+        .arg("select")
+        .arg("--ignore-diacritics")
+        .arg("003@.0")
+        .arg("--where")
+        .arg("029A.a =^ 'Goethe-Universitat'")
+        .arg("tests/data/004732650.dat.gz")
+        .assert();
+
+    assert
+        .success()
+        .stderr(predicate::str::is_empty())
+        .stdout("004732650\n");
+
+    Ok(())
+}
+
 #[test]
 fn pica_select_skip_invalid() -> TestResult {
     let mut cmd = Command::cargo_bin("pica")?;
@@ -654,7 +722,11 @@ fn pica_select_invalid_selector() -> TestResult {
     assert
         .failure()
         .stdout(predicate::str::is_empty())
-        .stderr("error: invalid select list: 003@.0, \'foo, 002@.0\n");
+        .stderr(
+            "error: invalid select list: unexpected end of expression, \
+            expected `'` at line 1, column 21\n  |\n1 | 003@.0, 'foo, \
+            002@.0\n  |                     ^\n",
+        );
 
     Ok(())
 }
@@ -19,6 +19,13 @@ fn count() {
         .case("tests/snapshot/count/*.trycmd");
 }
 
+#[test]
+fn describe() {
+    trycmd::TestCases::new()
+        .case("tests/snapshot/describe/*.toml")
+        .case("tests/snapshot/describe/*.trycmd");
+}
+
 #[test]
 fn filter() {
     trycmd::TestCases::new()
@@ -26,6 +33,13 @@ fn filter() {
         .case("tests/snapshot/filter/*.trycmd");
 }
 
+#[test]
+fn fmt_filter() {
+    trycmd::TestCases::new()
+        .case("tests/snapshot/fmt-filter/*.toml")
+        .case("tests/snapshot/fmt-filter/*.trycmd");
+}
+
 #[test]
 fn frequency() {
     trycmd::TestCases::new()
@@ -47,6 +61,13 @@ fn partition() {
         .case("tests/snapshot/partition/*.trycmd");
 }
 
+#[test]
+fn schema() {
+    trycmd::TestCases::new()
+        .case("tests/snapshot/schema/*.toml")
+        .case("tests/snapshot/schema/*.trycmd");
+}
+
 #[test]
 fn select() {
     trycmd::TestCases::new()
@@ -67,3 +88,10 @@ fn split() {
         .case("tests/snapshot/split/*.toml")
         .case("tests/snapshot/split/*.trycmd");
 }
+
+#[test]
+fn validate() {
+    trycmd::TestCases::new()
+        .case("tests/snapshot/validate/*.toml")
+        .case("tests/snapshot/validate/*.trycmd");
+}